@@ -0,0 +1,53 @@
+use bevy_ecs::{component::Component, world::World};
+use criterion::{Criterion, criterion_group, criterion_main};
+use superstate::{SuperstateInfo, register_hooks};
+
+#[derive(Default, Component)]
+#[require(SuperstateInfo<Movement>)]
+struct Movement;
+
+#[derive(Component)]
+#[require(Movement)]
+struct Walking;
+
+#[derive(Component)]
+#[require(Movement)]
+struct Running;
+
+#[derive(Component)]
+#[require(Movement)]
+struct Flying;
+
+fn spawn_single_state(c: &mut Criterion) {
+    c.bench_function("spawn 10k entities with single state", |b| {
+        b.iter_batched_ref(
+            || {
+                let mut world = World::new();
+                register_hooks::<Movement, (Walking, Running, Flying)>(&mut world).unwrap();
+                world
+            },
+            |world| {
+                for _ in 0..10_000 {
+                    world.spawn(Walking);
+                }
+            },
+            criterion::BatchSize::LargeInput,
+        );
+    });
+}
+
+fn respawn(c: &mut Criterion) {
+    c.bench_function("spawn and despawn 10k entities with single state", |b| {
+        let mut world = World::new();
+        register_hooks::<Movement, (Walking, Running, Flying)>(&mut world).unwrap();
+        b.iter(|| {
+            for _ in 0..10_000 {
+                let e = world.spawn(Walking).id();
+                world.despawn(e);
+            }
+        });
+    });
+}
+
+criterion_group!(benches, spawn_single_state, respawn);
+criterion_main!(benches);
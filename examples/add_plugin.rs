@@ -1,6 +1,6 @@
 use bevy_app::{App, Update};
 use bevy_ecs::{component::Component, entity::Entity, query::With, system::Query};
-use superstate::{superstate_plugin, SuperstateInfo};
+use superstate::{SuperstateInfo, superstate_plugin};
 
 #[derive(Component, Default)]
 #[require(SuperstateInfo<Movement>)]
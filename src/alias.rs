@@ -0,0 +1,63 @@
+//! Stable names of states in saves, independent of Rust type names.
+
+use std::{borrow::Cow, collections::HashMap};
+
+use bevy_ecs::{
+    component::{Component, ComponentId},
+    resource::Resource,
+    world::World,
+};
+
+/// Resource with aliases of states registered with [`state_alias`].
+#[derive(Resource, Default)]
+pub(crate) struct StateAliases {
+    names: HashMap<ComponentId, String>,
+    ids: HashMap<String, ComponentId>,
+}
+
+impl StateAliases {
+    /// Alias of the state with `id`, or the type path of its component.
+    pub(crate) fn name(world: &World, id: ComponentId) -> Option<String> {
+        world
+            .get_resource::<Self>()
+            .and_then(|aliases| aliases.names.get(&id).cloned())
+            .or_else(|| world.components().get_name(id).map(Cow::into_owned))
+    }
+
+    /// Alias of the state with `id`.
+    pub(crate) fn get(&self, id: ComponentId) -> Option<&str> {
+        self.names.get(&id).map(String::as_str)
+    }
+
+    /// Id of the state with alias `name`, or of the component with type path `name`.
+    pub(crate) fn id(world: &World, name: &str) -> Option<ComponentId> {
+        world
+            .get_resource::<Self>()
+            .and_then(|aliases| aliases.ids.get(name).copied())
+            .or_else(|| {
+                world
+                    .components()
+                    .iter_registered()
+                    .find(|info| info.name() == name)
+                    .map(|info| info.id())
+            })
+    }
+}
+
+/// Saves `State` by `name` instead of the type path of its component,
+/// both in serialized [`SuperstateInfo`](crate::SuperstateInfo) and
+/// in [`snapshot_entity_states`](crate::snapshot_entity_states),
+/// so renaming the type, e.g. `Walking` to `Walk`, does not break old saves.
+///
+/// Loading resolves aliases first, and then type paths,
+/// so saves made before the alias was added still load.
+/// Register aliases before loading, with the same name in every version of the game.
+pub fn state_alias<State: Component>(world: &mut World, name: impl Into<String>) {
+    let id = world.register_component::<State>();
+    let name = name.into();
+    let mut aliases = world.get_resource_or_init::<StateAliases>();
+    if let Some(old) = aliases.names.insert(id, name.clone()) {
+        aliases.ids.remove(&old);
+    }
+    aliases.ids.insert(name, id);
+}
@@ -0,0 +1,252 @@
+#[cfg(any(feature = "bevy_state", feature = "asset"))]
+use bevy_app::PreUpdate;
+use std::any::type_name;
+
+use bevy_app::{App, Update};
+use bevy_ecs::{
+    bundle::Bundle,
+    component::{Component, ComponentId},
+    schedule::{IntoScheduleConfigs, ScheduleConfigs},
+    system::{IntoObserverSystem, ScheduleSystem},
+};
+
+use crate::{
+    StateInput, StateSet, SuperstateHooks, SuperstateSet,
+    condition::{entered_state, exited_state, transitioned},
+    event::{
+        StateEntered, StateExited, SuperstateEntered, SuperstateExited, add_state_entered_observer,
+        add_state_exited_observer, add_superstate_entered_observer, add_superstate_exited_observer,
+    },
+    input::{InputMap, apply_state_inputs},
+};
+#[cfg(feature = "bevy_state")]
+use {
+    crate::mirror::{StateMirror, mirror_state},
+    bevy_state::state::FreelyMutableState,
+};
+#[cfg(feature = "asset")]
+use {
+    crate::{
+        TransitionGraph, TransitionGraphLoader,
+        asset::{TransitionGraphHandle, apply_transition_graph},
+    },
+    bevy_asset::{AssetApp, AssetPath, AssetServer, Assets},
+};
+
+/// Extension trait for [`App`] with state-driven systems.
+pub trait SuperstateAppExt {
+    /// Adds `systems` to [`Update`] schedule, which run once
+    /// on the frame when any entity entered `State` of `Super`.
+    /// See [`entered_state`].
+    fn on_enter_state<Super: Component, State: Component, M>(
+        &mut self,
+        systems: impl IntoScheduleConfigs<ScheduleSystem, M>,
+    ) -> &mut Self;
+
+    /// Adds `systems` to [`Update`] schedule, which run once
+    /// on the frame when any entity exited `State` of `Super`.
+    /// See [`exited_state`].
+    fn on_exit_state<Super: Component, State: Component, M>(
+        &mut self,
+        systems: impl IntoScheduleConfigs<ScheduleSystem, M>,
+    ) -> &mut Self;
+
+    /// Adds `systems` to [`Update`] schedule, which run once
+    /// on the frame when any entity transitioned from `From` to `To` state of `Super`,
+    /// e.g. to blend animations from walking to running.
+    /// See [`transitioned`].
+    fn on_transition<Super: Component, From: Component, To: Component, M>(
+        &mut self,
+        systems: impl IntoScheduleConfigs<ScheduleSystem, M>,
+    ) -> &mut Self;
+
+    /// Adds `observer` of [`StateEntered<State>`], which runs
+    /// for every entity entering `State` of `Super`.
+    /// See [`add_state_entered_observer`].
+    fn on_state_entered<Super: Component, State: Component, B: Bundle, M>(
+        &mut self,
+        observer: impl IntoObserverSystem<StateEntered<State>, B, M>,
+    ) -> &mut Self;
+
+    /// Adds `observer` of [`StateExited<State>`], which runs
+    /// for every entity exiting `State` of `Super`.
+    /// See [`add_state_exited_observer`].
+    fn on_state_exited<Super: Component, State: Component, B: Bundle, M>(
+        &mut self,
+        observer: impl IntoObserverSystem<StateExited<State>, B, M>,
+    ) -> &mut Self;
+
+    /// Adds `observer` of [`SuperstateEntered<Super>`], which runs
+    /// for every entity entering the machine of `Super` in any state.
+    /// See [`add_superstate_entered_observer`].
+    fn on_super_entered<Super: Component, B: Bundle, M>(
+        &mut self,
+        observer: impl IntoObserverSystem<SuperstateEntered<Super>, B, M>,
+    ) -> &mut Self;
+
+    /// Adds `observer` of [`SuperstateExited<Super>`], which runs
+    /// for every entity leaving the machine of `Super`, e.g. despawned.
+    /// See [`add_superstate_exited_observer`].
+    fn on_super_exited<Super: Component, B: Bundle, M>(
+        &mut self,
+        observer: impl IntoObserverSystem<SuperstateExited<Super>, B, M>,
+    ) -> &mut Self;
+
+    /// Panics if `Super` with `States` is not registered yet, e.g. in `build`
+    /// of a plugin depending on the plugin of the superstate,
+    /// so a missing or later added plugin fails at startup.
+    ///
+    /// Machines of [`crate::lazy_superstate_plugin`] are registered
+    /// only on the first use, so they do not satisfy it.
+    fn require_superstate<Super: Component, States: StateSet>(&mut self) -> &mut Self;
+
+    /// Maps every `Input` event to the state of `Super` to enter, or `None`
+    /// to ignore the input in the current state, which is `None` when the entity
+    /// has no superstate. The transition is applied by [`apply_state_inputs`]
+    /// system added to [`Update`] schedule in [`SuperstateSet::ProcessTransitions`].
+    /// Calling it again for the same `Super` and `Input` replaces the map.
+    ///
+    /// The state is known only by its id, so it is constructed by its constructor
+    /// registered with [`crate::register_default_state`] or [`crate::register_state_constructor`].
+    fn register_input_map<Super: Component, Input: StateInput>(
+        &mut self,
+        map: impl Fn(&Input, Option<ComponentId>) -> Option<ComponentId> + Send + Sync + 'static,
+    ) -> &mut Self;
+
+    /// Inserts `mirror` resource and adds [`mirror_state`] system to [`PreUpdate`] schedule,
+    /// which queues [`NextState<T>`](bevy_state::state::NextState) when the mirrored
+    /// entity changes its state of `Super`. The queued state is applied
+    /// by `StateTransition` schedule after [`PreUpdate`].
+    ///
+    /// `T` should be initialized with `init_state` or `insert_state`.
+    #[cfg(feature = "bevy_state")]
+    fn mirror_state<Super: Component, T: FreelyMutableState>(
+        &mut self,
+        mirror: StateMirror<Super, T>,
+    ) -> &mut Self;
+
+    /// Loads [`TransitionGraph`] from `path` and replaces
+    /// [`TransitionTable<Super>`](crate::TransitionTable) by its table when the graph
+    /// is loaded and every time it is modified, e.g. by hot-reloading.
+    /// Calling it again switches `Super` to another graph.
+    ///
+    /// Registers [`TransitionGraph`] asset with its loader on the first call,
+    /// so `AssetPlugin` should be added before.
+    #[cfg(feature = "asset")]
+    fn transition_graph<Super: Component>(
+        &mut self,
+        path: impl Into<AssetPath<'static>>,
+    ) -> &mut Self;
+}
+
+impl SuperstateAppExt for App {
+    fn on_enter_state<Super: Component, State: Component, M>(
+        &mut self,
+        systems: impl IntoScheduleConfigs<ScheduleSystem, M>,
+    ) -> &mut Self {
+        let systems: ScheduleConfigs<ScheduleSystem> = systems.into_configs();
+        self.add_systems(Update, systems.run_if(entered_state::<Super, State>))
+    }
+
+    fn on_exit_state<Super: Component, State: Component, M>(
+        &mut self,
+        systems: impl IntoScheduleConfigs<ScheduleSystem, M>,
+    ) -> &mut Self {
+        let systems: ScheduleConfigs<ScheduleSystem> = systems.into_configs();
+        self.add_systems(Update, systems.run_if(exited_state::<Super, State>))
+    }
+
+    fn on_transition<Super: Component, From: Component, To: Component, M>(
+        &mut self,
+        systems: impl IntoScheduleConfigs<ScheduleSystem, M>,
+    ) -> &mut Self {
+        let systems: ScheduleConfigs<ScheduleSystem> = systems.into_configs();
+        self.add_systems(Update, systems.run_if(transitioned::<Super, From, To>))
+    }
+
+    fn on_state_entered<Super: Component, State: Component, B: Bundle, M>(
+        &mut self,
+        observer: impl IntoObserverSystem<StateEntered<State>, B, M>,
+    ) -> &mut Self {
+        add_state_entered_observer::<Super, State, B, M>(self.world_mut(), observer);
+        self
+    }
+
+    fn on_state_exited<Super: Component, State: Component, B: Bundle, M>(
+        &mut self,
+        observer: impl IntoObserverSystem<StateExited<State>, B, M>,
+    ) -> &mut Self {
+        add_state_exited_observer::<Super, State, B, M>(self.world_mut(), observer);
+        self
+    }
+
+    fn on_super_entered<Super: Component, B: Bundle, M>(
+        &mut self,
+        observer: impl IntoObserverSystem<SuperstateEntered<Super>, B, M>,
+    ) -> &mut Self {
+        add_superstate_entered_observer::<Super, B, M>(self.world_mut(), observer);
+        self
+    }
+
+    fn on_super_exited<Super: Component, B: Bundle, M>(
+        &mut self,
+        observer: impl IntoObserverSystem<SuperstateExited<Super>, B, M>,
+    ) -> &mut Self {
+        add_superstate_exited_observer::<Super, B, M>(self.world_mut(), observer);
+        self
+    }
+
+    fn require_superstate<Super: Component, States: StateSet>(&mut self) -> &mut Self {
+        assert!(
+            self.world()
+                .contains_resource::<SuperstateHooks<Super, States>>(),
+            "{} with {} is required, but not registered. Add its plugin before this one.",
+            type_name::<Super>(),
+            type_name::<States>(),
+        );
+        self
+    }
+
+    fn register_input_map<Super: Component, Input: StateInput>(
+        &mut self,
+        map: impl Fn(&Input, Option<ComponentId>) -> Option<ComponentId> + Send + Sync + 'static,
+    ) -> &mut Self {
+        if !self.world().contains_resource::<InputMap<Super, Input>>() {
+            self.add_event::<Input>().add_systems(
+                Update,
+                apply_state_inputs::<Super, Input>.in_set(SuperstateSet::ProcessTransitions),
+            );
+        }
+        self.insert_resource(InputMap::<Super, Input>::new(map))
+    }
+
+    #[cfg(feature = "bevy_state")]
+    fn mirror_state<Super: Component, T: FreelyMutableState>(
+        &mut self,
+        mirror: StateMirror<Super, T>,
+    ) -> &mut Self {
+        self.insert_resource(mirror).add_systems(
+            PreUpdate,
+            mirror_state::<Super, T>.in_set(SuperstateSet::Sync),
+        )
+    }
+
+    #[cfg(feature = "asset")]
+    fn transition_graph<Super: Component>(
+        &mut self,
+        path: impl Into<AssetPath<'static>>,
+    ) -> &mut Self {
+        if !self.world().contains_resource::<Assets<TransitionGraph>>() {
+            self.init_asset::<TransitionGraph>()
+                .init_asset_loader::<TransitionGraphLoader>();
+        }
+        if !self
+            .world()
+            .contains_resource::<TransitionGraphHandle<Super>>()
+        {
+            self.add_systems(PreUpdate, apply_transition_graph::<Super>);
+        }
+        let graph = self.world().resource::<AssetServer>().load(path);
+        self.insert_resource(TransitionGraphHandle::<Super>::new(graph))
+    }
+}
@@ -0,0 +1,224 @@
+//! Transition tables loaded from [`TransitionGraph`] assets in RON files,
+//! so the edges can be tuned without recompiling. Requires `asset` feature.
+
+use std::{
+    collections::{HashMap, HashSet},
+    error::Error,
+    fmt::{self, Display},
+    marker::PhantomData,
+};
+
+use bevy_asset::{Asset, AssetEvent, AssetLoader, Assets, Handle, LoadContext, io::Reader};
+use bevy_ecs::{
+    change_detection::DetectChanges,
+    component::{Component, ComponentId, Components},
+    event::EventReader,
+    resource::Resource,
+    system::{Commands, Res},
+};
+use bevy_reflect::TypePath;
+use log::warn;
+use serde::Deserialize;
+
+use crate::{SuperstateRegistry, TransitionTable, alias::StateAliases, label::short_name};
+
+/// Asset with allowed transitions between states, which maps the name
+/// of a state to the names of states it can transition to.
+///
+/// A state is named by its alias of [`crate::state_alias`],
+/// the type path of its component, e.g. `game::Walking`,
+/// or the type name without module paths, e.g. `Walking`.
+/// A type name shared by several states, e.g. `a::Idle` and `b::Idle`,
+/// is ambiguous and fails with [`TransitionGraphError::AmbiguousName`].
+///
+/// ```ron
+/// (
+///     edges: {
+///         "Walking": ["Running"],
+///         "Running": ["Walking"],
+///     },
+/// )
+/// ```
+///
+/// Loaded by [`TransitionGraphLoader`] from `.graph.ron` files, and applied
+/// to [`TransitionTable`] by
+/// [`SuperstateAppExt::transition_graph`](crate::SuperstateAppExt::transition_graph).
+#[derive(Asset, TypePath, Deserialize, Debug, Clone, Default)]
+pub struct TransitionGraph {
+    /// Names of states reachable from the state with the key name.
+    pub edges: HashMap<String, Vec<String>>,
+}
+
+impl TransitionGraph {
+    /// Parses the graph from RON `text`.
+    pub fn from_ron(text: &str) -> Result<Self, TransitionGraphError> {
+        ron::from_str(text).map_err(TransitionGraphError::Ron)
+    }
+
+    /// Builds the table of `Super` resolving state names to ids of states registered
+    /// in `registry`. Unknown names are skipped with `warn!`,
+    /// ambiguous type names fail.
+    fn table<Super: Component>(
+        &self,
+        registry: &SuperstateRegistry<Super>,
+        components: &Components,
+        aliases: Option<&StateAliases>,
+    ) -> Result<TransitionTable<Super>, TransitionGraphError> {
+        let mut ids = HashMap::<String, ComponentId>::new();
+        let mut short_names = HashMap::<String, ComponentId>::new();
+        let mut ambiguous = HashSet::<String>::new();
+        for &id in registry.state_ids() {
+            if let Some(name) = components.get_name(id) {
+                let short = short_name(&name);
+                if short_names.insert(short.clone(), id).is_some() {
+                    ambiguous.insert(short);
+                }
+                ids.insert(name.into_owned(), id);
+            }
+            if let Some(alias) = aliases.and_then(|aliases| aliases.get(id)) {
+                ids.insert(alias.to_owned(), id);
+            }
+        }
+        // Full type paths and aliases take precedence over type names.
+        for (short, id) in short_names {
+            if !ambiguous.contains(&short) {
+                ids.entry(short).or_insert(id);
+            }
+        }
+        let resolve = |name: &String| {
+            if ambiguous.contains(name) && !ids.contains_key(name) {
+                return Err(TransitionGraphError::AmbiguousName(name.clone()));
+            }
+            let id = ids.get(name).copied();
+            if id.is_none() {
+                warn!(
+                    "Transition graph has state {name}, which is not a state of {}.",
+                    std::any::type_name::<Super>()
+                );
+            }
+            Ok(id)
+        };
+        let mut table = TransitionTable::default();
+        for (from, targets) in &self.edges {
+            let Some(from) = resolve(from)? else {
+                continue;
+            };
+            for to in targets {
+                if let Some(to) = resolve(to)? {
+                    table.allow_by_id(from, to);
+                }
+            }
+        }
+        Ok(table)
+    }
+}
+
+/// Error of loading [`TransitionGraph`].
+#[derive(Debug)]
+pub enum TransitionGraphError {
+    /// The file could not be read.
+    Io(std::io::Error),
+    /// The file is not a valid RON graph.
+    Ron(ron::error::SpannedError),
+    /// The type name is shared by several states, so the state should be named
+    /// by its full type path or an alias.
+    AmbiguousName(String),
+}
+
+impl Display for TransitionGraphError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(error) => write!(f, "Could not read transition graph: {error}"),
+            Self::Ron(error) => write!(f, "Could not parse transition graph: {error}"),
+            Self::AmbiguousName(name) => write!(
+                f,
+                "State name {name} in transition graph is shared by several states, \
+                 use the full type path or an alias of the state"
+            ),
+        }
+    }
+}
+
+impl Error for TransitionGraphError {}
+
+/// Loader of [`TransitionGraph`] from `.graph.ron` files.
+#[derive(Default)]
+pub struct TransitionGraphLoader;
+
+impl AssetLoader for TransitionGraphLoader {
+    type Asset = TransitionGraph;
+    type Settings = ();
+    type Error = TransitionGraphError;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &(),
+        _load_context: &mut LoadContext<'_>,
+    ) -> Result<TransitionGraph, TransitionGraphError> {
+        let mut bytes = Vec::new();
+        reader
+            .read_to_end(&mut bytes)
+            .await
+            .map_err(TransitionGraphError::Io)?;
+        ron::de::from_bytes(&bytes).map_err(TransitionGraphError::Ron)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["graph.ron"]
+    }
+}
+
+/// Resource with the graph applied to [`TransitionTable<Super>`].
+#[derive(Resource)]
+pub(crate) struct TransitionGraphHandle<Super: Component> {
+    pub(crate) handle: Handle<TransitionGraph>,
+    _p: PhantomData<Super>,
+}
+
+impl<Super: Component> TransitionGraphHandle<Super> {
+    pub(crate) fn new(handle: Handle<TransitionGraph>) -> Self {
+        Self {
+            handle,
+            _p: PhantomData,
+        }
+    }
+}
+
+/// System replacing [`TransitionTable<Super>`] when its graph is loaded or modified,
+/// e.g. by hot-reloading, and when the graph or `Super` is registered after the load.
+pub(crate) fn apply_transition_graph<Super: Component>(
+    mut commands: Commands,
+    mut events: EventReader<AssetEvent<TransitionGraph>>,
+    graph: Res<TransitionGraphHandle<Super>>,
+    graphs: Res<Assets<TransitionGraph>>,
+    registry: Option<Res<SuperstateRegistry<Super>>>,
+    components: &Components,
+    aliases: Option<Res<StateAliases>>,
+) {
+    let id = graph.handle.id();
+    let mut loaded = false;
+    // All events are read, so they do not apply the graph again on the next run.
+    for event in events.read() {
+        if let AssetEvent::Added { id: changed } | AssetEvent::Modified { id: changed } = event {
+            loaded |= *changed == id;
+        }
+    }
+    let Some(registry) = registry else {
+        return;
+    };
+    if !(loaded || graph.is_changed() || registry.is_added()) {
+        return;
+    }
+    let Some(asset) = graphs.get(id) else {
+        return;
+    };
+    // The previous table is kept until the graph is fixed.
+    match asset.table(&registry, components, aliases.as_deref()) {
+        Ok(table) => commands.insert_resource(table),
+        Err(error) => warn!(
+            "{error}, {} keeps its table.",
+            std::any::type_name::<Super>()
+        ),
+    }
+}
@@ -0,0 +1,475 @@
+use std::{marker::PhantomData, sync::Arc, time::Duration};
+
+use bevy_ecs::{
+    component::{Component, ComponentId},
+    entity::Entity,
+    error::BevyError,
+    world::{DeferredWorld, EntityWorldMut, World},
+};
+use log::Level;
+#[cfg(feature = "bevy_app")]
+use {
+    crate::{
+        SuperstateSet,
+        current::update_current_state,
+        label::update_state_label,
+        request::process_transitions,
+        scoped::{StateScopedExits, despawn_state_scoped, despawn_super_scoped},
+    },
+    bevy_app::{App, PostUpdate, Update},
+    bevy_ecs::{
+        resource::Resource,
+        schedule::{InternedSystemSet, IntoScheduleConfigs, SystemSet},
+    },
+};
+
+use crate::{
+    ConflictPolicy, HookOrder, StateEnum, StateOccupancy, StateSet, SuperstateHooks,
+    SuperstateRegistry, TransitionGuard, TransitionLog, TransitionTable,
+    hooks::{self, UnknownStateError},
+    register_hooks_inner,
+    registry::{InitialFn, InsertFn},
+    state_enum::{StateEnumMap, map_state_enum},
+    states_ids,
+};
+
+/// Registers a state component, as the builder does not have access to the world.
+type RegisterFn = fn(&mut World) -> ComponentId;
+type MapEnumFn = fn(&mut World, &[ComponentId]) -> Result<StateEnumMap, UnknownStateError>;
+
+/// Builder of superstate `Super` with all concrete `States`,
+/// which collects all options of `register_hooks_*` functions.
+///
+#[cfg_attr(feature = "bevy_app", doc = "```")]
+#[cfg_attr(not(feature = "bevy_app"), doc = "```ignore")]
+/// # use bevy_app::App;
+/// # use bevy_ecs::component::Component;
+/// # use superstate::{ConflictPolicy, SuperstateBuilder, SuperstateInfo};
+/// # #[derive(Default, Component)]
+/// # #[require(SuperstateInfo<Movement>)]
+/// # struct Movement;
+/// # #[derive(Component, Default)]
+/// # #[require(Movement)]
+/// # struct Walking;
+/// # #[derive(Component)]
+/// # #[require(Movement)]
+/// # struct Running;
+/// let mut app = App::new();
+/// SuperstateBuilder::<Movement, (Walking, Running)>::new()
+///     .default_state::<Walking>()
+///     .conflict_policy(ConflictPolicy::KeepFirst)
+///     .build(&mut app)
+///     .unwrap();
+/// ```
+pub struct SuperstateBuilder<Super: Component, States: StateSet> {
+    initial: Option<(RegisterFn, InitialFn)>,
+    fallback: Option<(RegisterFn, InsertFn)>,
+    policy: ConflictPolicy,
+    order: HookOrder,
+    allow_empty: bool,
+    parallel: bool,
+    stack_depth: usize,
+    remove_info: bool,
+    occupancy: bool,
+    transition_log: Option<usize>,
+    #[cfg(feature = "bevy_app")]
+    current_state: bool,
+    #[cfg(feature = "bevy_app")]
+    state_label: bool,
+    #[cfg(feature = "bevy_app")]
+    process_transitions: Option<InternedSystemSet>,
+    min_dwell: Vec<(RegisterFn, Duration)>,
+    log_min_dwell: bool,
+    log_level: Option<Level>,
+    strict: bool,
+    table: Option<TransitionTable<Super>>,
+    guard: Option<TransitionGuard<Super>>,
+    state_enums: Vec<MapEnumFn>,
+    _p: PhantomData<States>,
+}
+
+impl<Super: Component, States: StateSet> Default for SuperstateBuilder<Super, States> {
+    fn default() -> Self {
+        Self {
+            initial: None,
+            fallback: None,
+            policy: ConflictPolicy::default(),
+            order: HookOrder::default(),
+            allow_empty: false,
+            parallel: false,
+            stack_depth: 0,
+            remove_info: false,
+            occupancy: false,
+            transition_log: None,
+            #[cfg(feature = "bevy_app")]
+            current_state: false,
+            #[cfg(feature = "bevy_app")]
+            state_label: false,
+            #[cfg(feature = "bevy_app")]
+            process_transitions: None,
+            min_dwell: Vec::new(),
+            log_min_dwell: false,
+            log_level: None,
+            strict: false,
+            table: None,
+            guard: None,
+            state_enums: Vec::new(),
+            _p: PhantomData,
+        }
+    }
+}
+
+impl<Super: Component, States: StateSet> SuperstateBuilder<Super, States> {
+    /// Creates a builder with the options of [`crate::register_hooks`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Inserting `Super` on entity without states puts it into `Initial` state,
+    /// see [`crate::register_hooks_with_default`].
+    pub fn default_state<Initial: Component + Default>(self) -> Self {
+        self.default_state_with(Initial::default)
+    }
+
+    /// Same as [`SuperstateBuilder::default_state`], but the initial state
+    /// is built by `factory`, e.g. `|| Walking(0)` for a state with data.
+    /// The factory is called by [`hooks::on_add_superstate`] for every entity.
+    pub fn default_state_with<Initial: Component>(
+        mut self,
+        factory: impl Fn() -> Initial + Send + Sync + 'static,
+    ) -> Self {
+        self.initial = Some((
+            |world| world.register_component::<Initial>(),
+            InitialFn(Arc::new(move |entity: &mut EntityWorldMut| {
+                entity.insert(factory());
+            })),
+        ));
+        self
+    }
+
+    /// Removing the last state puts the entity into `Fallback` state
+    /// instead of removing `Super`, see [`crate::register_hooks_with_fallback`].
+    pub fn fallback_state<Fallback: Component + Default>(mut self) -> Self {
+        self.fallback = Some((
+            |world| world.register_component::<Fallback>(),
+            |entity| {
+                entity.insert(Fallback::default());
+            },
+        ));
+        self
+    }
+
+    /// Which state remains when several states are added at once,
+    /// see [`crate::register_hooks_with_policy`].
+    pub fn conflict_policy(mut self, policy: ConflictPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// Order of superstate hooks relative to already registered hooks,
+    /// see [`crate::register_hooks_with_order`].
+    pub fn hook_order(mut self, order: HookOrder) -> Self {
+        self.order = order;
+        self
+    }
+
+    /// `Super` stays on entity without states,
+    /// see [`crate::register_hooks_allowing_empty`].
+    pub fn allow_empty(mut self) -> Self {
+        self.allow_empty = true;
+        self
+    }
+
+    /// States of `Super` are parallel regions active at once,
+    /// see [`crate::register_parallel_hooks`].
+    pub fn parallel(mut self) -> Self {
+        self.parallel = true;
+        self
+    }
+
+    /// [`SuperstateInfo`](crate::SuperstateInfo) is removed together with `Super`,
+    /// freeing its memory, instead of being kept for reuse.
+    pub fn remove_info(mut self) -> Self {
+        self.remove_info = true;
+        self
+    }
+
+    /// Inserts [`StateOccupancy`] resource counting entities in each state.
+    pub fn state_occupancy(mut self) -> Self {
+        self.occupancy = true;
+        self
+    }
+
+    /// Inserts [`TransitionLog`] resource keeping the last `capacity` transitions.
+    /// Without it, transitions are not recorded.
+    pub fn transition_log(mut self, capacity: usize) -> Self {
+        self.transition_log = Some(capacity);
+        self
+    }
+
+    /// Adds [`update_current_state`] system
+    /// to `PostUpdate` schedule, which mirrors the active state into
+    /// [`CurrentState`](crate::CurrentState) component.
+    /// Used only by [`SuperstateBuilder::build`].
+    #[cfg(feature = "bevy_app")]
+    pub fn current_state_component(mut self) -> Self {
+        self.current_state = true;
+        self
+    }
+
+    /// Adds a system to `PostUpdate` schedule, which writes the name of the active state into
+    /// [`StateLabel`](crate::StateLabel) component for debugging.
+    /// Used only by [`SuperstateBuilder::build`].
+    #[cfg(feature = "bevy_app")]
+    pub fn state_label(mut self) -> Self {
+        self.state_label = true;
+        self
+    }
+
+    /// Adds [`TransitionRequest`](crate::TransitionRequest) event and
+    /// [`process_transitions`] system to `set` of [`Update`] schedule,
+    /// so requested transitions land at a known point relative to other systems.
+    /// Used only by [`SuperstateBuilder::build`].
+    #[cfg(feature = "bevy_app")]
+    pub fn process_transitions_in(mut self, set: impl SystemSet) -> Self {
+        self.process_transitions = Some(set.intern());
+        self
+    }
+
+    /// Enables the history stack of states with maximum `depth`
+    /// for [`crate::SuperstateCommands::push_state`] and
+    /// [`crate::SuperstateCommands::pop_state`].
+    /// When the stack is deeper than `depth`, the oldest states are dropped.
+    pub fn history_stack(mut self, depth: usize) -> Self {
+        self.stack_depth = depth;
+        self
+    }
+
+    /// The entity cannot leave `State` until it has been active for `duration`,
+    /// e.g. to stop animation popping on rapid transitions.
+    /// Measured by the `Time` resource, not checked without it or `bevy_time` feature.
+    ///
+    /// Rejected transitions are silently dropped,
+    /// unless logging is enabled with [`SuperstateBuilder::log_min_dwell`].
+    pub fn min_dwell<State: Component>(mut self, duration: Duration) -> Self {
+        self.min_dwell
+            .push((|world| world.register_component::<State>(), duration));
+        self
+    }
+
+    /// Logs a warning for each transition rejected by [`SuperstateBuilder::min_dwell`].
+    pub fn log_min_dwell(mut self) -> Self {
+        self.log_min_dwell = true;
+        self
+    }
+
+    /// Logs every transition at `level`,
+    /// see [`crate::register_hooks_with_logging`].
+    pub fn log_transitions(mut self, level: Level) -> Self {
+        self.log_level = Some(level);
+        self
+    }
+
+    /// Transitions rejected by [`TransitionTable`] or [`TransitionGuard`] panic
+    /// with the names of both states in debug builds, to catch logic errors early.
+    /// Release builds drop them as usual.
+    /// Transitions rejected by minimum dwell time or [`StateLock`](crate::StateLock)
+    /// are not errors and never panic.
+    pub fn strict(mut self) -> Self {
+        self.strict = true;
+        self
+    }
+
+    /// Inserts `table` of allowed transitions.
+    pub fn transition_table(mut self, table: TransitionTable<Super>) -> Self {
+        self.table = Some(table);
+        self
+    }
+
+    /// Inserts [`TransitionGuard`] with `guard`,
+    /// see [`crate::register_hooks_with_guard`].
+    pub fn guard(
+        mut self,
+        guard: impl Fn(&DeferredWorld, Entity, ComponentId, ComponentId) -> bool + Send + Sync + 'static,
+    ) -> Self {
+        self.guard = Some(TransitionGuard::new(guard));
+        self
+    }
+
+    /// Maps states to variants of `E`, see [`crate::register_state_enum`].
+    pub fn state_enum<E: StateEnum<Super>>(mut self) -> Self {
+        self.state_enums.push(map_state_enum::<Super, E>);
+        self
+    }
+
+    /// Registers the superstate in the `app`, the same as [`SuperstateBuilder::build_world`],
+    /// registers types, diagnostics and validation of enabled features,
+    /// and adds cleanup of [`StateScoped`](crate::StateScoped) children
+    /// and [`SuperScoped`](crate::SuperScoped) helpers.
+    ///
+    /// Systems are added only after the superstate is registered,
+    /// so a failed build leaves the `app` untouched.
+    /// After [`crate::deregister_hooks`] the systems of options not used
+    /// by the earlier build are added, and the systems it added are kept,
+    /// as systems cannot be removed from schedules.
+    #[cfg(feature = "bevy_app")]
+    pub fn build(self, app: &mut App) -> Result<(), BevyError> {
+        if SuperstateHooks::<Super, States>::enabled(app.world()) {
+            return Ok(());
+        }
+        // Systems of a machine disabled by `deregister_hooks` are still in the schedules.
+        let installed = SuperstateHooks::<Super, States>::installed(app.world());
+        let (current_state, state_label, transitions_set) = (
+            self.current_state,
+            self.state_label,
+            self.process_transitions,
+        );
+        self.build_world(app.world_mut())?;
+        if !installed {
+            #[cfg(feature = "reflect")]
+            app.register_type::<crate::SuperstateInfo<Super>>();
+            #[cfg(feature = "diagnostic")]
+            crate::diagnostic::add_diagnostics::<Super>(app);
+            app.init_resource::<StateScopedExits<Super>>().add_systems(
+                PostUpdate,
+                (despawn_state_scoped::<Super>, despawn_super_scoped::<Super>)
+                    .in_set(SuperstateSet::Cleanup),
+            );
+            #[cfg(all(feature = "validate", debug_assertions))]
+            app.add_systems(
+                PostUpdate,
+                crate::validate::validate_invariants::<Super, States>
+                    .in_set(SuperstateSet::Diagnostics),
+            );
+        }
+        let mut added = app
+            .world_mut()
+            .remove_resource::<AppSystems<Super>>()
+            .unwrap_or_default();
+        if current_state && !added.current_state {
+            app.add_systems(
+                PostUpdate,
+                update_current_state::<Super>.in_set(SuperstateSet::Sync),
+            );
+            added.current_state = true;
+        }
+        if state_label && !added.state_label {
+            app.add_systems(
+                PostUpdate,
+                update_state_label::<Super>.in_set(SuperstateSet::Sync),
+            );
+            added.state_label = true;
+        }
+        if let Some(set) = transitions_set
+            && !added.process_transitions
+        {
+            app.add_event::<crate::TransitionRequest<Super>>()
+                .add_systems(
+                    Update,
+                    process_transitions::<Super, States>
+                        .in_set(set)
+                        .in_set(SuperstateSet::ProcessTransitions),
+                );
+            added.process_transitions = true;
+        }
+        app.insert_resource(added);
+        Ok(())
+    }
+
+    /// Registers hooks of the superstate in the `world`.
+    ///
+    /// Registering the same `Super` and `States` again is a no-op success,
+    /// so several plugins can depend on the same superstate.
+    /// The options of the first registration are kept.
+    /// After [`crate::deregister_hooks`] the hooks are turned back on,
+    /// and the machine is built again from the options of this builder.
+    ///
+    /// Returns [`hooks::UnknownStateError`] if the default or fallback state,
+    /// or a state mapped by [`SuperstateBuilder::state_enum`], is not one of `States`.
+    /// All options are checked before the hooks are installed,
+    /// so a failed build does not leave a half-registered superstate.
+    pub fn build_world(self, world: &mut World) -> Result<(), BevyError> {
+        if SuperstateHooks::<Super, States>::enabled(world) {
+            return Ok(());
+        }
+        let fallback = self.fallback.map(|(id, insert)| (id(world), insert));
+        let initial = self.initial.map(|(id, insert)| (id(world), insert));
+        let states = states_ids::<States>(world);
+        for id in initial
+            .iter()
+            .map(|(id, _)| *id)
+            .chain(fallback.map(|(id, _)| id))
+        {
+            if !states.contains(&id) {
+                return Err(UnknownStateError(id).into());
+            }
+        }
+        let state_enums = self
+            .state_enums
+            .iter()
+            .map(|map| map(world, &states))
+            .collect::<Result<Vec<_>, _>>()?;
+        register_hooks_inner::<Super, States>(
+            world,
+            hooks::on_add_superstate::<Super, States>,
+            self.order,
+        )?;
+        let min_dwell = self
+            .min_dwell
+            .into_iter()
+            .map(|(id, duration)| (id(world), duration))
+            .collect();
+        let mut registry = world.resource_mut::<SuperstateRegistry<Super>>();
+        registry.policy = self.policy;
+        registry.allow_empty = self.allow_empty;
+        registry.parallel = self.parallel;
+        registry.stack_depth = self.stack_depth;
+        registry.remove_info = self.remove_info;
+        registry.min_dwell = min_dwell;
+        registry.log_min_dwell = self.log_min_dwell;
+        registry.log_level = self.log_level;
+        registry.strict = self.strict;
+        registry.fallback = fallback.map(|(_, insert)| insert);
+        registry.initial = initial.map(|(_, insert)| insert);
+        if let Some(table) = self.table {
+            world.insert_resource(table);
+        }
+        if let Some(guard) = self.guard {
+            world.insert_resource(guard);
+        }
+        if self.occupancy {
+            world.init_resource::<StateOccupancy<Super>>();
+        }
+        if let Some(capacity) = self.transition_log {
+            world.insert_resource(TransitionLog::<Super>::new(capacity));
+        }
+        world
+            .resource_mut::<SuperstateRegistry<Super>>()
+            .state_enums
+            .extend(state_enums);
+        Ok(())
+    }
+}
+
+/// Optional systems of `Super` added by [`SuperstateBuilder::build`],
+/// kept after [`crate::deregister_hooks`] as the systems stay in the schedules.
+#[cfg(feature = "bevy_app")]
+#[derive(Resource)]
+struct AppSystems<Super: Component> {
+    current_state: bool,
+    state_label: bool,
+    process_transitions: bool,
+    _p: PhantomData<Super>,
+}
+
+#[cfg(feature = "bevy_app")]
+impl<Super: Component> Default for AppSystems<Super> {
+    fn default() -> Self {
+        Self {
+            current_state: false,
+            state_label: false,
+            process_transitions: false,
+            _p: PhantomData,
+        }
+    }
+}
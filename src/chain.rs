@@ -0,0 +1,158 @@
+//! A component can have only one hook of each kind,
+//! so superstate hooks are installed through a dispatcher,
+//! which calls all hooks chained for the component.
+//! This allows one component to be a state of one superstate
+//! and a superstate of other states at the same time.
+//!
+//! If the component already has a hook registered not by this crate,
+//! it cannot be wrapped or replaced, so the dispatcher runs
+//! as [`OnAdd`] or [`OnRemove`] observer instead, always after that hook.
+//! The same for a component already on some entity, as its hooks cannot be changed,
+//! e.g. when registered lazily by [`crate::lazy_superstate_plugin`].
+
+use std::{collections::HashMap, ptr::fn_addr_eq};
+
+use bevy_ecs::{
+    component::{ComponentHook, ComponentId, HookContext},
+    observer::{Observer, Trigger},
+    relationship::RelationshipHookMode,
+    resource::Resource,
+    world::{DeferredWorld, OnAdd, OnRemove, World},
+};
+use smallvec::SmallVec;
+
+use crate::hooks::HookKind;
+
+/// Order of a newly chained hook relative to the hooks
+/// already chained for the same component.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum HookOrder {
+    /// Already chained hooks run first.
+    #[default]
+    ExistingFirst,
+    /// The new hook runs first.
+    SuperstateFirst,
+}
+
+type Hooks = SmallVec<[ComponentHook; 2]>;
+
+#[derive(Default, Clone)]
+struct HookChain {
+    on_add: Hooks,
+    on_remove: Hooks,
+}
+
+/// Hooks chained for every component.
+#[derive(Resource, Default)]
+pub(crate) struct HookChains {
+    chains: HashMap<ComponentId, HookChain>,
+}
+
+/// Chains `on_add` and `on_remove` hooks for component with `id`.
+///
+/// Returns [`HookKind`] of the hook that failed if there is no component with `id`.
+pub(crate) fn chain_hooks(
+    world: &mut World,
+    id: ComponentId,
+    on_add: ComponentHook,
+    on_remove: ComponentHook,
+    order: HookOrder,
+) -> Result<(), HookKind> {
+    let chained = world
+        .get_resource::<HookChains>()
+        .is_some_and(|chains| chains.chains.contains_key(&id));
+    let in_use = world
+        .archetypes()
+        .iter()
+        .any(|archetype| archetype.contains(id));
+    if !chained && in_use {
+        world.spawn(Observer::new(observe_on_add).with_component(id));
+        world.spawn(Observer::new(observe_on_remove).with_component(id));
+    } else if !chained {
+        let hooks = world
+            .register_component_hooks_by_id(id)
+            .ok_or(HookKind::OnAdd)?;
+        if hooks.try_on_add(dispatch_on_add).is_none() {
+            world.spawn(Observer::new(observe_on_add).with_component(id));
+        }
+        let hooks = world
+            .register_component_hooks_by_id(id)
+            .ok_or(HookKind::OnRemove)?;
+        if hooks.try_on_remove(dispatch_on_remove).is_none() {
+            world.spawn(Observer::new(observe_on_remove).with_component(id));
+        }
+    }
+    let mut chains = world.get_resource_or_init::<HookChains>();
+    let chain = chains.chains.entry(id).or_default();
+    match order {
+        HookOrder::ExistingFirst => {
+            chain.on_add.push(on_add);
+            chain.on_remove.push(on_remove);
+        }
+        HookOrder::SuperstateFirst => {
+            chain.on_add.insert(0, on_add);
+            chain.on_remove.insert(0, on_remove);
+        }
+    }
+    Ok(())
+}
+
+/// Returns `true` if `on_add` is already chained for component with `id`,
+/// e.g. by a previous registration disabled by [`crate::deregister_hooks`].
+pub(crate) fn is_chained(world: &World, id: ComponentId, on_add: ComponentHook) -> bool {
+    world
+        .get_resource::<HookChains>()
+        .and_then(|chains| chains.chains.get(&id))
+        .is_some_and(|chain| chain.on_add.iter().any(|&hook| fn_addr_eq(hook, on_add)))
+}
+
+fn chained(
+    world: &DeferredWorld,
+    id: ComponentId,
+    hooks: impl FnOnce(&HookChain) -> &Hooks,
+) -> Hooks {
+    world
+        .get_resource::<HookChains>()
+        .and_then(|chains| chains.chains.get(&id))
+        .map(|chain| hooks(chain).clone())
+        .unwrap_or_default()
+}
+
+fn dispatch_on_add(mut world: DeferredWorld, ctx: HookContext) {
+    for hook in chained(&world, ctx.component_id, |chain| &chain.on_add) {
+        hook(world.reborrow(), ctx);
+    }
+}
+
+fn dispatch_on_remove(mut world: DeferredWorld, ctx: HookContext) {
+    for hook in chained(&world, ctx.component_id, |chain| &chain.on_remove) {
+        hook(world.reborrow(), ctx);
+    }
+}
+
+fn observer_contexts<E>(trigger: &Trigger<E>) -> impl Iterator<Item = HookContext> {
+    let entity = trigger.target();
+    let caller = trigger.caller();
+    trigger
+        .components()
+        .to_vec()
+        .into_iter()
+        .map(move |component_id| HookContext {
+            entity,
+            component_id,
+            caller,
+            relationship_hook_mode: RelationshipHookMode::Run,
+        })
+}
+
+fn observe_on_add(trigger: Trigger<OnAdd>, mut world: DeferredWorld) {
+    for ctx in observer_contexts(&trigger) {
+        dispatch_on_add(world.reborrow(), ctx);
+    }
+}
+
+fn observe_on_remove(trigger: Trigger<OnRemove>, mut world: DeferredWorld) {
+    for ctx in observer_contexts(&trigger) {
+        dispatch_on_remove(world.reborrow(), ctx);
+    }
+}
@@ -0,0 +1,227 @@
+use std::any::type_name;
+
+use bevy_ecs::{
+    bundle::Bundle,
+    change_detection::DetectChangesMut,
+    component::Component,
+    system::{Commands, EntityCommands},
+    world::{EntityWorldMut, World},
+};
+
+use log::warn;
+
+use crate::{
+    StateLock, SuperstateInfo, SuperstateRegistry, constructor::StateConstructors,
+    resolve_state_id, transition::force_insert,
+};
+
+/// Extension trait for [`EntityCommands`] with state transitions.
+pub trait SuperstateCommands {
+    /// Transition the entity to `state`.
+    /// The previous state is removed by [`crate::hooks::on_add_hook_state`].
+    fn transition_to<State: Component>(&mut self, state: State) -> &mut Self;
+
+    /// Same as [`SuperstateCommands::transition_to`], but uses `State::default()`.
+    /// Convenient for unit-struct states.
+    fn transition_to_default<State: Component + Default>(&mut self) -> &mut Self;
+
+    /// Remove all states of `Super`.
+    /// `Super` stays on the entity only if it is registered with
+    /// [`crate::register_hooks_allowing_empty`], otherwise it is removed too.
+    fn clear_state<Super: Component>(&mut self) -> &mut Self;
+
+    /// Remove `State` of `Super` only if it is active, e.g. to leave a state
+    /// from transition code without touching a component that is not an active state.
+    /// Does nothing if `State` is not active.
+    /// Leaving the last state follows the same rules as removing it.
+    fn exit_state<Super: Component, State: Component>(&mut self) -> &mut Self;
+
+    /// Transition the entity back to [`SuperstateInfo::previous_state`] of `Super`,
+    /// e.g. to return from pause. Does nothing if there is no previous state.
+    ///
+    /// The state is known only by its [`ComponentId`](bevy_ecs::component::ComponentId),
+    /// so it is constructed by constructor registered with
+    /// [`crate::register_default_state`] or [`crate::register_state_constructor`],
+    /// which can return e.g. the last stored value of the state.
+    /// States without a constructor are not re-entered.
+    fn transition_to_previous<Super: Component>(&mut self) -> &mut Self;
+
+    /// Transition the entity to `state`, and push the current state of `Super`
+    /// on top of [`SuperstateInfo::stack`], e.g. to open a menu on top of gameplay.
+    ///
+    /// The current state is still removed by [`crate::hooks::on_add_hook_state`],
+    /// as the last added state wins, only its id is kept on the stack.
+    /// The stack is kept only if enabled with [`crate::SuperstateBuilder::history_stack`],
+    /// otherwise this is the same as [`SuperstateCommands::transition_to`].
+    /// Other transitions do not change the stack, removing `Super` clears it.
+    ///
+    /// The current state is pushed only if the entity entered `state`, so a transition
+    /// rejected e.g. by [`crate::TransitionTable`], a guard or [`StateLock`]
+    /// leaves the stack untouched.
+    fn push_state<Super: Component, State: Component>(&mut self, state: State) -> &mut Self;
+
+    /// Pop the top state of `Super` from [`SuperstateInfo::stack`] and re-enter it.
+    /// Does nothing if the stack is empty.
+    ///
+    /// Like [`SuperstateCommands::transition_to_previous`], the state is constructed
+    /// by its registered constructor, states without a constructor are not re-entered.
+    /// The top state is popped only if it is re-entered, so it stays on the stack
+    /// if it has no constructor or the transition is rejected.
+    fn pop_state<Super: Component>(&mut self) -> &mut Self;
+
+    /// Freeze the state of `Super` by inserting [`StateLock`],
+    /// states inserted while locked are dropped.
+    fn lock_state<Super: Component>(&mut self) -> &mut Self;
+
+    /// Remove [`StateLock`] of `Super`, so transitions apply again.
+    /// Transitions dropped while locked are not replayed.
+    fn unlock_state<Super: Component>(&mut self) -> &mut Self;
+
+    /// Transition the entity to `state` of `Super` bypassing the transition rules,
+    /// see [`crate::force_state`].
+    fn force_state<Super: Component, State: Component>(&mut self, state: State) -> &mut Self;
+}
+
+impl SuperstateCommands for EntityCommands<'_> {
+    fn transition_to<State: Component>(&mut self, state: State) -> &mut Self {
+        self.insert(state)
+    }
+
+    fn transition_to_default<State: Component + Default>(&mut self) -> &mut Self {
+        self.insert(State::default())
+    }
+
+    fn clear_state<Super: Component>(&mut self) -> &mut Self {
+        self.queue(|mut entity: EntityWorldMut| {
+            let ids = entity.world_scope(|world: &mut World| {
+                world
+                    .get_resource::<SuperstateRegistry<Super>>()
+                    .map(|registry| registry.state_ids().to_vec())
+                    .unwrap_or_default()
+            });
+            entity.remove_by_ids(&ids);
+        })
+    }
+
+    fn exit_state<Super: Component, State: Component>(&mut self) -> &mut Self {
+        self.queue(|mut entity: EntityWorldMut| {
+            let Some(id) = resolve_state_id::<State>(entity.world()) else {
+                return;
+            };
+            if entity
+                .get::<SuperstateInfo<Super>>()
+                .is_some_and(|info| info.active_states().contains(&id))
+            {
+                entity.remove::<State>();
+            }
+        })
+    }
+
+    fn transition_to_previous<Super: Component>(&mut self) -> &mut Self {
+        self.queue(|mut entity: EntityWorldMut| {
+            let Some(prev) = entity
+                .get::<SuperstateInfo<Super>>()
+                .and_then(SuperstateInfo::previous_state)
+            else {
+                return;
+            };
+            if !StateConstructors::<Super>::insert(&mut entity, prev) {
+                warn!(
+                    "State({:?}) of {} has no constructor to transition back to it.",
+                    prev,
+                    entity.id()
+                );
+            }
+        })
+    }
+
+    fn push_state<Super: Component, State: Component>(&mut self, state: State) -> &mut Self {
+        self.queue(move |mut entity: EntityWorldMut| {
+            let depth = entity.world_scope(|world: &mut World| {
+                world
+                    .get_resource::<SuperstateRegistry<Super>>()
+                    .map_or(0, SuperstateRegistry::stack_depth)
+            });
+            let current = entity
+                .get::<SuperstateInfo<Super>>()
+                .and_then(SuperstateInfo::current_state);
+            entity.insert(state);
+            // The transition may be rejected, then there is nothing to return to.
+            if depth > 0
+                && let Some(id) = resolve_state_id::<State>(entity.world())
+                && let Some(current) = current.filter(|current| *current != id)
+                && let Some(mut info) = entity.get_mut::<SuperstateInfo<Super>>()
+                && info.current_state() == Some(id)
+            {
+                info.bypass_change_detection().push_stack(current, depth);
+            }
+        })
+    }
+
+    fn pop_state<Super: Component>(&mut self) -> &mut Self {
+        self.queue(|mut entity: EntityWorldMut| {
+            let Some(&top) = entity
+                .get::<SuperstateInfo<Super>>()
+                .and_then(|info| info.stack().last())
+            else {
+                return;
+            };
+            if !StateConstructors::<Super>::insert(&mut entity, top) {
+                warn!(
+                    "State({:?}) of {} has no constructor to pop back to it.",
+                    top,
+                    entity.id()
+                );
+                return;
+            }
+            if let Some(mut info) = entity.get_mut::<SuperstateInfo<Super>>()
+                && info.current_state() == Some(top)
+            {
+                info.bypass_change_detection().pop_stack();
+            }
+        })
+    }
+
+    fn lock_state<Super: Component>(&mut self) -> &mut Self {
+        self.insert(StateLock::<Super>::default())
+    }
+
+    fn unlock_state<Super: Component>(&mut self) -> &mut Self {
+        self.remove::<StateLock<Super>>()
+    }
+
+    fn force_state<Super: Component, State: Component>(&mut self, state: State) -> &mut Self {
+        self.queue(move |entity: EntityWorldMut| force_insert::<Super, State>(entity, state))
+    }
+}
+
+/// Spawns an entity with `bundle` and exactly one `state` of `Super`.
+///
+/// Spawning several states at once keeps only one of them by [`crate::ConflictPolicy`],
+/// and they briefly coexist until the hooks flush. Here `bundle` is inserted first,
+/// and then `state` alone, so the entity enters `state` without any transition.
+///
+/// `bundle` should not contain states of `Super`, otherwise it is logged,
+/// and `state` is inserted as a usual transition from the state of `bundle`.
+pub fn spawn_in_state<'a, Super: Component, State: Component>(
+    commands: &'a mut Commands,
+    state: State,
+    bundle: impl Bundle,
+) -> EntityCommands<'a> {
+    let mut entity = commands.spawn(bundle);
+    entity.queue(|entity: EntityWorldMut| {
+        if entity
+            .world()
+            .get_resource::<SuperstateRegistry<Super>>()
+            .is_some_and(|registry| registry.has_state(&entity.as_readonly()))
+        {
+            warn!(
+                "{} is spawned in a state of {} with another state.",
+                entity.id(),
+                type_name::<Super>()
+            );
+        }
+    });
+    entity.insert(state);
+    entity
+}
@@ -0,0 +1,64 @@
+//! Run conditions based on states of entities.
+
+use bevy_ecs::{
+    component::{Component, ComponentIdFor},
+    query::{Changed, With},
+    system::{Query, SystemChangeTick},
+};
+
+use crate::SuperstateInfo;
+
+/// Run condition that is `true` if any entity entered `State` of `Super`
+/// since the last time the condition was checked.
+/// Other changes of the info, e.g. another parallel region leaving, are not entries,
+/// see [`SuperstateInfo::entered_since`].
+pub fn entered_state<Super: Component, State: Component>(
+    state: ComponentIdFor<State>,
+    ticks: SystemChangeTick,
+    query: Query<&SuperstateInfo<Super>, Changed<SuperstateInfo<Super>>>,
+) -> bool {
+    let id = state.get();
+    query
+        .iter()
+        .any(|info| info.entered_since(id, ticks.last_run(), ticks.this_run()))
+}
+
+/// Run condition that is `true` if any entity exited `State` of `Super`
+/// since the last time the condition was checked, by a transition to another state,
+/// by removal of the state or of the superstate. See [`SuperstateInfo::exited_since`].
+pub fn exited_state<Super: Component, State: Component>(
+    state: ComponentIdFor<State>,
+    ticks: SystemChangeTick,
+    query: Query<&SuperstateInfo<Super>, Changed<SuperstateInfo<Super>>>,
+) -> bool {
+    let id = state.get();
+    query
+        .iter()
+        .any(|info| info.exited_since(id, ticks.last_run(), ticks.this_run()))
+}
+
+/// Run condition that is `true` if any entity transitioned from `From` to `To` state
+/// of `Super` since the last time the condition was checked.
+/// Like [`entered_state`], keyed on the entry of `To`, so other changes of the info
+/// do not match again.
+pub fn transitioned<Super: Component, From: Component, To: Component>(
+    from: ComponentIdFor<From>,
+    to: ComponentIdFor<To>,
+    ticks: SystemChangeTick,
+    query: Query<&SuperstateInfo<Super>, Changed<SuperstateInfo<Super>>>,
+) -> bool {
+    let (from, to) = (from.get(), to.get());
+    query.iter().any(|info| {
+        info.previous_state() == Some(from)
+            && info.entered_since(to, ticks.last_run(), ticks.this_run())
+    })
+}
+
+/// Run condition that is `true` while at least one entity is in `State` of `Super`,
+/// e.g. to run a global system only if anybody is flying.
+/// Per-entity analog of `bevy_state`'s `in_state`.
+pub fn in_any_entity_state<Super: Component, State: Component>(
+    query: Query<(), (With<State>, With<Super>)>,
+) -> bool {
+    !query.is_empty()
+}
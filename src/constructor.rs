@@ -0,0 +1,68 @@
+//! Constructors of states, which allow to re-enter a state knowing only its [`ComponentId`],
+//! e.g. by [`crate::SuperstateCommands::transition_to_previous`].
+
+use std::{collections::HashMap, marker::PhantomData, sync::Arc};
+
+use bevy_ecs::{
+    component::{Component, ComponentId},
+    resource::Resource,
+    world::{EntityWorldMut, World},
+};
+
+type ConstructorFn = Arc<dyn Fn(&mut EntityWorldMut) + Send + Sync>;
+
+/// Resource with registered constructors of `Super` states.
+#[derive(Resource)]
+pub(crate) struct StateConstructors<Super: Component> {
+    constructors: HashMap<ComponentId, ConstructorFn>,
+    _p: PhantomData<Super>,
+}
+
+impl<Super: Component> Default for StateConstructors<Super> {
+    fn default() -> Self {
+        Self {
+            constructors: HashMap::new(),
+            _p: PhantomData,
+        }
+    }
+}
+
+impl<Super: Component> StateConstructors<Super> {
+    /// Inserts state with `id` into `entity`.
+    /// Returns `false` if there is no constructor for the state.
+    pub(crate) fn insert(entity: &mut EntityWorldMut, id: ComponentId) -> bool {
+        let constructor = entity.world_scope(|world: &mut World| {
+            world
+                .get_resource::<Self>()
+                .and_then(|res| res.constructors.get(&id).cloned())
+        });
+        let Some(constructor) = constructor else {
+            return false;
+        };
+        constructor(entity);
+        true
+    }
+}
+
+/// Registers `constructor` of `State` of `Super`, which is used
+/// when the state is re-entered by its [`ComponentId`].
+pub fn register_state_constructor<Super: Component, State: Component>(
+    world: &mut World,
+    constructor: impl Fn() -> State + Send + Sync + 'static,
+) {
+    let id = world.register_component::<State>();
+    world
+        .get_resource_or_init::<StateConstructors<Super>>()
+        .constructors
+        .insert(
+            id,
+            Arc::new(move |entity: &mut EntityWorldMut| {
+                entity.insert(constructor());
+            }),
+        );
+}
+
+/// Same as [`register_state_constructor`], but uses `State::default()`.
+pub fn register_default_state<Super: Component, State: Component + Default>(world: &mut World) {
+    register_state_constructor::<Super, State>(world, State::default);
+}
@@ -0,0 +1,91 @@
+//! Component with only the active state of an entity,
+//! separate from the bookkeeping of [`SuperstateInfo`].
+
+use std::marker::PhantomData;
+
+use bevy_ecs::{
+    change_detection::DetectChangesMut,
+    component::{Component, ComponentId},
+    entity::Entity,
+    query::Changed,
+    removal_detection::RemovedComponents,
+    system::{Commands, Query},
+};
+
+use crate::SuperstateInfo;
+
+/// Component with the id of the active state of `Super`, for cheap
+/// `Changed<CurrentState<Super>>` detection, which is triggered
+/// only when the active state changes.
+///
+/// Written by [`update_current_state`] system, enabled with
+/// [`SuperstateBuilder::current_state_component`](crate::SuperstateBuilder::current_state_component).
+/// The system runs in `PostUpdate` schedule, so transitions made in `Update`
+/// are seen in it only on the next frame, while [`SuperstateInfo`] is updated
+/// immediately by the hooks. Removed when the entity has no state of `Super`.
+#[derive(Component, Debug)]
+pub struct CurrentState<Super: Component> {
+    id: ComponentId,
+    _p: PhantomData<Super>,
+}
+
+impl<Super: Component> CurrentState<Super> {
+    fn new(id: ComponentId) -> Self {
+        Self {
+            id,
+            _p: PhantomData,
+        }
+    }
+
+    /// Id of the active state.
+    pub fn id(&self) -> ComponentId {
+        self.id
+    }
+}
+
+impl<Super: Component> Clone for CurrentState<Super> {
+    fn clone(&self) -> Self {
+        Self::new(self.id)
+    }
+}
+
+impl<Super: Component> PartialEq for CurrentState<Super> {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id
+    }
+}
+
+type ChangedInfo<'a, Super> = (
+    Entity,
+    &'a SuperstateInfo<Super>,
+    Option<&'a mut CurrentState<Super>>,
+);
+
+/// System mirroring the active state of `Super` into [`CurrentState<Super>`].
+pub fn update_current_state<Super: Component>(
+    mut commands: Commands,
+    mut changed: Query<ChangedInfo<Super>, Changed<SuperstateInfo<Super>>>,
+    mut removed: RemovedComponents<SuperstateInfo<Super>>,
+) {
+    for (entity, info, current) in &mut changed {
+        match (info.current_state(), current) {
+            (Some(id), Some(mut current)) => {
+                current.set_if_neq(CurrentState::new(id));
+            }
+            (Some(id), None) => {
+                commands
+                    .entity(entity)
+                    .insert(CurrentState::<Super>::new(id));
+            }
+            (None, Some(_)) => {
+                commands.entity(entity).remove::<CurrentState<Super>>();
+            }
+            (None, None) => {}
+        }
+    }
+    for entity in removed.read() {
+        if let Ok(mut entity) = commands.get_entity(entity) {
+            entity.try_remove::<CurrentState<Super>>();
+        }
+    }
+}
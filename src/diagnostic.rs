@@ -0,0 +1,82 @@
+//! Diagnostics of superstate transitions. Requires `diagnostic` feature.
+
+use std::{any::type_name, marker::PhantomData};
+
+use bevy_app::{App, Last};
+use bevy_diagnostic::{Diagnostic, DiagnosticPath, Diagnostics, RegisterDiagnostic};
+use bevy_ecs::{
+    component::Component, resource::Resource, schedule::IntoScheduleConfigs, system::ResMut,
+    world::DeferredWorld,
+};
+
+use crate::SuperstateSet;
+
+/// Resource counting transitions of `Super`, i.e. changes of the active state
+/// recorded by [`crate::hooks::on_add_hook_state`].
+///
+/// Inserted by [`crate::superstate_plugin`] together with a [`Diagnostic`]
+/// at [`SuperstateDiagnostics::path`], which is measured with the number
+/// of transitions per frame in [`Last`] schedule.
+#[derive(Resource, Debug)]
+pub struct SuperstateDiagnostics<Super: Component> {
+    transitions: u32,
+    total: u64,
+    path: DiagnosticPath,
+    _p: PhantomData<Super>,
+}
+
+impl<Super: Component> Default for SuperstateDiagnostics<Super> {
+    fn default() -> Self {
+        Self {
+            transitions: 0,
+            total: 0,
+            path: DiagnosticPath::new(format!("superstate/{}/transitions", type_name::<Super>())),
+            _p: PhantomData,
+        }
+    }
+}
+
+impl<Super: Component> SuperstateDiagnostics<Super> {
+    /// Number of transitions in the current frame.
+    pub fn transitions(&self) -> u32 {
+        self.transitions
+    }
+
+    /// Number of transitions since the resource was inserted.
+    pub fn total(&self) -> u64 {
+        self.total
+    }
+
+    /// Path of the transitions per frame [`Diagnostic`].
+    pub fn path(&self) -> &DiagnosticPath {
+        &self.path
+    }
+
+    pub(crate) fn record(world: &mut DeferredWorld) {
+        if let Some(mut diagnostics) = world.get_resource_mut::<Self>() {
+            diagnostics.transitions += 1;
+            diagnostics.total += 1;
+        }
+    }
+}
+
+/// Inserts [`SuperstateDiagnostics`] and registers its [`Diagnostic`].
+pub(crate) fn add_diagnostics<Super: Component>(app: &mut App) {
+    let diagnostics = SuperstateDiagnostics::<Super>::default();
+    app.register_diagnostic(Diagnostic::new(diagnostics.path.clone()).with_suffix(" transitions"))
+        .insert_resource(diagnostics)
+        .add_systems(
+            Last,
+            measure_transitions::<Super>.in_set(SuperstateSet::Diagnostics),
+        );
+}
+
+/// System adding the number of transitions in the frame to [`Diagnostics`]
+/// and resetting it for the next frame.
+pub fn measure_transitions<Super: Component>(
+    mut transitions: ResMut<SuperstateDiagnostics<Super>>,
+    mut diagnostics: Diagnostics,
+) {
+    diagnostics.add_measurement(&transitions.path, || transitions.transitions as f64);
+    transitions.transitions = 0;
+}
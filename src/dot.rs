@@ -0,0 +1,79 @@
+//! Export of a state machine to Graphviz DOT, e.g. to draw it in documentation.
+
+use std::{any::type_name, fmt::Write};
+
+use bevy_ecs::{component::Component, world::World};
+
+use crate::{SuperstateRegistry, TransitionTable, label::short_name, occupancy::state_name};
+
+/// Returns the graph of `Super` in Graphviz DOT, with a node for every registered state
+/// and an edge for every transition allowed by [`TransitionTable<Super>`].
+///
+/// States are named like in [`crate::StateOccupancy::named`]. Without the table
+/// any transition is allowed, so the states are left unconnected and the graph
+/// is labeled as unrestricted. Empty graph if `Super` is not registered.
+///
+/// ```
+/// # use bevy_ecs::{component::Component, world::World};
+/// # use superstate::{SuperstateInfo, TransitionTable, export_dot, register_hooks};
+/// # #[derive(Default, Component)]
+/// # #[require(SuperstateInfo<Movement>)]
+/// # struct Movement;
+/// # #[derive(Component)]
+/// # #[require(Movement)]
+/// # struct Walking;
+/// # #[derive(Component)]
+/// # #[require(Movement)]
+/// # struct Running;
+/// let mut world = World::new();
+/// register_hooks::<Movement, (Walking, Running)>(&mut world).unwrap();
+/// let table = TransitionTable::<Movement>::builder(&mut world)
+///     .allow::<Walking, Running>()
+///     .build();
+/// world.insert_resource(table);
+/// let dot = export_dot::<Movement>(&world);
+/// assert!(dot.starts_with("digraph \"Movement\" {"));
+/// assert_eq!(dot.matches(" -> ").count(), 1);
+/// ```
+pub fn export_dot<Super: Component>(world: &World) -> String {
+    let mut dot = String::new();
+    let name = short_name(type_name::<Super>());
+    writeln!(dot, "digraph {} {{", quote(&name)).unwrap();
+    if let Some(registry) = world.get_resource::<SuperstateRegistry<Super>>() {
+        let states = registry
+            .state_ids()
+            .iter()
+            .map(|&id| {
+                (
+                    id,
+                    state_name(world, id).unwrap_or_else(|| format!("{id:?}")),
+                )
+            })
+            .collect::<Vec<_>>();
+        let table = world.get_resource::<TransitionTable<Super>>();
+        if table.is_none() {
+            writeln!(dot, "    label = \"Transitions are unrestricted\";").unwrap();
+        }
+        for (_, state) in &states {
+            writeln!(dot, "    {};", quote(state)).unwrap();
+        }
+        // Edges follow the order of states, so the output is stable.
+        if let Some(table) = table {
+            for (from, from_name) in &states {
+                for (to, to_name) in &states {
+                    if from != to && table.is_allowed(*from, *to) {
+                        writeln!(dot, "    {} -> {};", quote(from_name), quote(to_name)).unwrap();
+                    }
+                }
+            }
+        }
+    }
+    dot.push('}');
+    dot.push('\n');
+    dot
+}
+
+/// Quotes `id` as a DOT string.
+fn quote(id: &str) -> String {
+    format!("\"{}\"", id.replace('\\', "\\\\").replace('"', "\\\""))
+}
@@ -0,0 +1,287 @@
+//! Observer events triggered on state transitions.
+
+use std::{collections::HashMap, marker::PhantomData};
+
+use bevy_ecs::{
+    bundle::Bundle,
+    component::{Component, ComponentId},
+    entity::Entity,
+    event::Event,
+    hierarchy::ChildOf,
+    resource::Resource,
+    system::{Commands, IntoObserverSystem},
+    world::World,
+};
+
+use crate::RejectReason;
+
+/// Event triggered for the entity that entered `State`,
+/// after the old state has been removed from it.
+///
+/// Triggered only for states with an observer added by
+/// [`add_state_entered_observer`] or [`crate::SuperstateAppExt::on_state_entered`].
+///
+/// The event bubbles up the hierarchy: after the observers of the entity,
+/// it is triggered for its [`ChildOf`] parent, then for the parent of the parent, and so on,
+/// so a container can react to a state change of any child.
+/// [`Trigger::target`](bevy_ecs::observer::Trigger::target) is the entity
+/// the event is currently at, and [`StateEntered::entity`] is the one that entered `State`.
+/// Call [`Trigger::propagate(false)`](bevy_ecs::observer::Trigger::propagate)
+/// in an observer to stop bubbling.
+///
+/// Observers added by [`add_state_entered_observer`] watch all entities,
+/// so they run once for every entity on the way up. Compare `target` with
+/// [`StateEntered::entity`] to react only to the entity itself.
+#[derive(Event, Debug)]
+#[event(traversal = &'static ChildOf, auto_propagate)]
+pub struct StateEntered<State: Component> {
+    /// The entity that entered `State`.
+    pub entity: Entity,
+    /// The state that was active before `State`.
+    pub previous: Option<ComponentId>,
+    _p: PhantomData<State>,
+}
+
+impl<State: Component> StateEntered<State> {
+    fn trigger(cmd: &mut Commands, entity: Entity, previous: Option<ComponentId>) {
+        cmd.trigger_targets(
+            Self {
+                entity,
+                previous,
+                _p: PhantomData,
+            },
+            entity,
+        );
+    }
+}
+
+/// Event triggered for the entity that exited active `State`, when the state
+/// is removed by a transition, by [`crate::SuperstateCommands::exit_state`]
+/// or together with the superstate.
+///
+/// Triggered only for states with an observer added by
+/// [`add_state_exited_observer`] or [`crate::SuperstateAppExt::on_state_exited`].
+///
+/// Like [`StateEntered`], the event bubbles up the [`ChildOf`] hierarchy,
+/// [`StateExited::entity`] is the one that exited `State`.
+/// Call [`Trigger::propagate(false)`](bevy_ecs::observer::Trigger::propagate)
+/// in an observer to stop bubbling.
+#[derive(Event, Debug)]
+#[event(traversal = &'static ChildOf, auto_propagate)]
+pub struct StateExited<State: Component> {
+    /// The entity that exited `State`.
+    pub entity: Entity,
+    _p: PhantomData<State>,
+}
+
+impl<State: Component> StateExited<State> {
+    fn trigger(cmd: &mut Commands, entity: Entity) {
+        cmd.trigger_targets(
+            Self {
+                entity,
+                _p: PhantomData,
+            },
+            entity,
+        );
+    }
+}
+
+/// Event triggered for the entity when [`crate::hooks::on_add_hook_state`]
+/// removes the incoming state of `Super`, because the transition is rejected
+/// by [`crate::TransitionTable`], [`crate::TransitionGuard`], minimum dwell time
+/// or [`crate::StateLock`],
+/// e.g. to show "can't fly while exhausted".
+///
+/// States dropped by [`crate::ConflictPolicy`] are not rejected transitions.
+#[derive(Event, Debug)]
+pub struct TransitionRejected<Super: Component> {
+    /// The entity that stays in `from` state.
+    pub entity: Entity,
+    /// The active state, which stays on the entity.
+    pub from: ComponentId,
+    /// The rejected incoming state.
+    pub attempted: ComponentId,
+    /// Why the transition is rejected.
+    pub reason: RejectReason,
+    _p: PhantomData<Super>,
+}
+
+impl<Super: Component> TransitionRejected<Super> {
+    pub(crate) fn trigger(
+        cmd: &mut Commands,
+        entity: Entity,
+        from: ComponentId,
+        attempted: ComponentId,
+        reason: RejectReason,
+    ) {
+        cmd.trigger_targets(
+            Self {
+                entity,
+                from,
+                attempted,
+                reason,
+                _p: PhantomData,
+            },
+            entity,
+        );
+    }
+}
+
+/// Event triggered for the entity which entered the machine of `Super` as a whole,
+/// i.e. `Super` was added and stays on the entity, regardless of the state,
+/// e.g. to set up machine-wide resources of the entity.
+///
+/// Triggered by [`crate::hooks::on_add_superstate`], only if there is an observer added by
+/// [`add_superstate_entered_observer`] or [`crate::SuperstateAppExt::on_super_entered`],
+/// or by the `exited` counterparts.
+/// `Super` added without any state and removed by the hook does not enter the machine.
+#[derive(Event, Debug)]
+pub struct SuperstateEntered<Super: Component> {
+    /// The entity that entered the machine.
+    pub entity: Entity,
+    _p: PhantomData<Super>,
+}
+
+impl<Super: Component> SuperstateEntered<Super> {
+    pub(crate) fn trigger(cmd: &mut Commands, entity: Entity) {
+        cmd.trigger_targets(
+            Self {
+                entity,
+                _p: PhantomData,
+            },
+            entity,
+        );
+    }
+
+    /// Returns `true` if [`SuperstateEntered`] and [`SuperstateExited`] are observed.
+    pub(crate) fn observed(world: &World) -> bool {
+        world.contains_resource::<SuperstateObserved<Super>>()
+    }
+}
+
+/// Event triggered for the entity which left the machine of `Super`
+/// that it entered, see [`SuperstateEntered`], e.g. to tear down machine-wide resources.
+///
+/// Triggered by [`crate::hooks::on_remove_superstate`] after the states are removed,
+/// also when the entity is despawned.
+#[derive(Event, Debug)]
+pub struct SuperstateExited<Super: Component> {
+    /// The entity that left the machine.
+    pub entity: Entity,
+    _p: PhantomData<Super>,
+}
+
+impl<Super: Component> SuperstateExited<Super> {
+    pub(crate) fn trigger(cmd: &mut Commands, entity: Entity) {
+        cmd.trigger_targets(
+            Self {
+                entity,
+                _p: PhantomData,
+            },
+            entity,
+        );
+    }
+}
+
+/// Marks that [`SuperstateEntered`] and [`SuperstateExited`] of `Super` have observers,
+/// so they are not triggered without them.
+#[derive(Resource)]
+struct SuperstateObserved<Super: Component>(PhantomData<Super>);
+
+/// Adds `observer` of [`SuperstateEntered<Super>`], which runs when any entity enters the machine of `Super`.
+pub fn add_superstate_entered_observer<Super: Component, B: Bundle, M>(
+    world: &mut World,
+    observer: impl IntoObserverSystem<SuperstateEntered<Super>, B, M>,
+) {
+    world.insert_resource(SuperstateObserved::<Super>(PhantomData));
+    world.add_observer(observer);
+}
+
+/// Adds `observer` of [`SuperstateExited<Super>`], which runs when any entity leaves the machine of `Super`.
+pub fn add_superstate_exited_observer<Super: Component, B: Bundle, M>(
+    world: &mut World,
+    observer: impl IntoObserverSystem<SuperstateExited<Super>, B, M>,
+) {
+    world.insert_resource(SuperstateObserved::<Super>(PhantomData));
+    world.add_observer(observer);
+}
+
+type TriggerFn = fn(&mut Commands, Entity, Option<ComponentId>);
+
+/// Resource with functions triggering [`StateEntered`] for states of `Super`,
+/// used by [`crate::hooks::on_add_hook_state`].
+#[derive(Resource)]
+pub(crate) struct StateEnteredTriggers<Super: Component> {
+    triggers: HashMap<ComponentId, TriggerFn>,
+    _p: PhantomData<Super>,
+}
+
+impl<Super: Component> Default for StateEnteredTriggers<Super> {
+    fn default() -> Self {
+        Self {
+            triggers: HashMap::new(),
+            _p: PhantomData,
+        }
+    }
+}
+
+impl<Super: Component> StateEnteredTriggers<Super> {
+    pub(crate) fn get(world: &World, state: ComponentId) -> Option<TriggerFn> {
+        world
+            .get_resource::<Self>()
+            .and_then(|res| res.triggers.get(&state).copied())
+    }
+}
+
+/// Adds `observer` of [`StateEntered<State>`], which runs when any entity enters `State` of `Super`.
+pub fn add_state_entered_observer<Super: Component, State: Component, B: Bundle, M>(
+    world: &mut World,
+    observer: impl IntoObserverSystem<StateEntered<State>, B, M>,
+) {
+    let id = world.register_component::<State>();
+    world
+        .get_resource_or_init::<StateEnteredTriggers<Super>>()
+        .triggers
+        .insert(id, StateEntered::<State>::trigger);
+    world.add_observer(observer);
+}
+
+type ExitTriggerFn = fn(&mut Commands, Entity);
+
+/// Resource with functions triggering [`StateExited`] for states of `Super`,
+/// used by [`crate::hooks::on_remove_hook_state`] and [`crate::hooks::on_remove_superstate`].
+#[derive(Resource)]
+pub(crate) struct StateExitedTriggers<Super: Component> {
+    triggers: HashMap<ComponentId, ExitTriggerFn>,
+    _p: PhantomData<Super>,
+}
+
+impl<Super: Component> Default for StateExitedTriggers<Super> {
+    fn default() -> Self {
+        Self {
+            triggers: HashMap::new(),
+            _p: PhantomData,
+        }
+    }
+}
+
+impl<Super: Component> StateExitedTriggers<Super> {
+    pub(crate) fn get(world: &World, state: ComponentId) -> Option<ExitTriggerFn> {
+        world
+            .get_resource::<Self>()
+            .and_then(|res| res.triggers.get(&state).copied())
+    }
+}
+
+/// Adds `observer` of [`StateExited<State>`], which runs when any entity exits `State` of `Super`.
+pub fn add_state_exited_observer<Super: Component, State: Component, B: Bundle, M>(
+    world: &mut World,
+    observer: impl IntoObserverSystem<StateExited<State>, B, M>,
+) {
+    let id = world.register_component::<State>();
+    world
+        .get_resource_or_init::<StateExitedTriggers<Super>>()
+        .triggers
+        .insert(id, StateExited::<State>::trigger);
+    world.add_observer(observer);
+}
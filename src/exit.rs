@@ -0,0 +1,69 @@
+//! Handlers of exiting a state, which read the value of the state before it is removed.
+
+use std::{collections::HashMap, marker::PhantomData, sync::Arc};
+
+use bevy_ecs::{
+    component::{Component, ComponentId},
+    entity::Entity,
+    resource::Resource,
+    system::Commands,
+    world::{DeferredWorld, World},
+};
+use smallvec::SmallVec;
+
+type ExitFn = Arc<dyn Fn(&mut DeferredWorld, Entity) + Send + Sync>;
+
+/// Resource with registered exit handlers of states of `Super`,
+/// used by [`crate::hooks::on_remove_hook_state`].
+#[derive(Resource)]
+pub(crate) struct StateExitHandlers<Super: Component> {
+    handlers: HashMap<ComponentId, SmallVec<[ExitFn; 1]>>,
+    _p: PhantomData<Super>,
+}
+
+impl<Super: Component> Default for StateExitHandlers<Super> {
+    fn default() -> Self {
+        Self {
+            handlers: HashMap::new(),
+            _p: PhantomData,
+        }
+    }
+}
+
+impl<Super: Component> StateExitHandlers<Super> {
+    /// Handlers of state with `id`, cloned so they can borrow the world.
+    pub(crate) fn get(world: &World, id: ComponentId) -> SmallVec<[ExitFn; 1]> {
+        world
+            .get_resource::<Self>()
+            .and_then(|res| res.handlers.get(&id).cloned())
+            .unwrap_or_default()
+    }
+}
+
+/// Registers `handler` called with the value of `State` of `Super`
+/// when the entity exits it, e.g. to read `Walking(speed)` before it is dropped.
+///
+/// Called by [`crate::hooks::on_remove_hook_state`] while the state is still
+/// on the entity, for any exit: a transition to another state, removal of the state
+/// or `Super`, or despawn. States that were never active, e.g. rejected ones, do not call it.
+/// Commands queued by `handler` are applied after the state is removed.
+/// Several handlers of the same state run in order of registration.
+pub fn on_exit_with<Super: Component, State: Component>(
+    world: &mut World,
+    handler: impl Fn(&State, Entity, &mut Commands) + Send + Sync + 'static,
+) {
+    let id = world.register_component::<State>();
+    world
+        .get_resource_or_init::<StateExitHandlers<Super>>()
+        .handlers
+        .entry(id)
+        .or_default()
+        .push(Arc::new(move |world: &mut DeferredWorld, entity| {
+            let (entities, mut commands) = world.entities_and_commands();
+            if let Ok(entity_ref) = entities.get(entity)
+                && let Some(state) = entity_ref.get::<State>()
+            {
+                handler(state, entity, &mut commands);
+            }
+        }));
+}
@@ -0,0 +1,123 @@
+//! Log of recent transitions of all entities, e.g. for a state timeline
+//! in a debug panel or to compare the transitions of replays.
+
+use std::{collections::VecDeque, marker::PhantomData};
+
+use bevy_ecs::{
+    component::{Component, ComponentId, Tick},
+    entity::Entity,
+    resource::Resource,
+    world::DeferredWorld,
+};
+
+/// Transition recorded in [`TransitionLog`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TransitionRecord {
+    /// Entity which changed its state.
+    pub entity: Entity,
+    /// Previous active state, `None` when the entity entered its first state.
+    pub from: Option<ComponentId>,
+    /// New active state.
+    pub to: ComponentId,
+    /// Change tick of the world when the state was entered.
+    pub tick: Tick,
+}
+
+/// Resource with the last `capacity` transitions of `Super` of all entities,
+/// appended by [`crate::hooks::on_add_hook_state`] when an entity enters a state.
+/// When the log is full, the oldest transition is dropped.
+///
+/// Optional: recorded only while the resource exists, inserted by
+/// [`crate::SuperstateBuilder::transition_log`] or `insert_resource`.
+///
+/// ```
+/// # use bevy_ecs::{component::Component, world::World};
+/// # use superstate::{SuperstateBuilder, SuperstateInfo, TransitionLog};
+/// # #[derive(Default, Component)]
+/// # #[require(SuperstateInfo<Movement>)]
+/// # struct Movement;
+/// # #[derive(Component)]
+/// # #[require(Movement)]
+/// # struct Walking;
+/// # #[derive(Component)]
+/// # #[require(Movement)]
+/// # struct Running;
+/// let mut world = World::new();
+/// SuperstateBuilder::<Movement, (Walking, Running)>::new()
+///     .transition_log(16)
+///     .build_world(&mut world)
+///     .unwrap();
+/// let e = world.spawn(Walking).id();
+/// world.entity_mut(e).insert(Running);
+/// let running = world.register_component::<Running>();
+/// let log = world.resource::<TransitionLog<Movement>>();
+/// let last = log.iter().next().unwrap();
+/// assert_eq!((last.entity, last.to), (e, running));
+/// ```
+#[derive(Resource, Debug, Clone)]
+pub struct TransitionLog<Super: Component> {
+    records: VecDeque<TransitionRecord>,
+    capacity: usize,
+    _p: PhantomData<Super>,
+}
+
+impl<Super: Component> TransitionLog<Super> {
+    /// Creates an empty log keeping the last `capacity` transitions.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            records: VecDeque::with_capacity(capacity),
+            capacity,
+            _p: PhantomData,
+        }
+    }
+
+    /// Maximum number of kept transitions.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Number of kept transitions.
+    pub fn len(&self) -> usize {
+        self.records.len()
+    }
+
+    /// Returns `true` if no transition is kept.
+    pub fn is_empty(&self) -> bool {
+        self.records.is_empty()
+    }
+
+    /// Iterator over kept transitions, newest first.
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = &TransitionRecord> + '_ {
+        self.records.iter().rev()
+    }
+
+    /// Removes all kept transitions.
+    pub fn clear(&mut self) {
+        self.records.clear();
+    }
+
+    /// Appends the transition of `entity` from `from` to `to`.
+    pub(crate) fn record(
+        world: &mut DeferredWorld,
+        entity: Entity,
+        from: Option<ComponentId>,
+        to: ComponentId,
+    ) {
+        let tick = world.read_change_tick();
+        let Some(mut log) = world.get_resource_mut::<Self>() else {
+            return;
+        };
+        if log.capacity == 0 {
+            return;
+        }
+        if log.records.len() == log.capacity {
+            log.records.pop_front();
+        }
+        log.records.push_back(TransitionRecord {
+            entity,
+            from,
+            to,
+            tick,
+        });
+    }
+}
@@ -0,0 +1,90 @@
+//! Transitions mapped from typed input events, e.g. `MovementInput::Jump`,
+//! so the transition logic of a machine is kept in one place.
+
+use std::{marker::PhantomData, sync::Arc};
+
+use bevy_ecs::{
+    change_detection::Mut,
+    component::{Component, ComponentId},
+    entity::Entity,
+    event::{Event, EventCursor, Events},
+    resource::Resource,
+    system::Local,
+    world::World,
+};
+
+use crate::{SuperstateInfo, request::apply_transition};
+
+/// Input event addressed to an entity, mapped to states of a superstate
+/// by [`SuperstateAppExt::register_input_map`](crate::SuperstateAppExt::register_input_map).
+///
+/// ```
+/// # use bevy_ecs::{entity::Entity, event::Event};
+/// # use superstate::StateInput;
+/// #[derive(Event)]
+/// struct MovementInput {
+///     entity: Entity,
+///     jump: bool,
+/// }
+///
+/// impl StateInput for MovementInput {
+///     fn entity(&self) -> Entity {
+///         self.entity
+///     }
+/// }
+/// ```
+pub trait StateInput: Event {
+    /// The entity receiving the input.
+    fn entity(&self) -> Entity;
+}
+
+type MapFn<Input> = dyn Fn(&Input, Option<ComponentId>) -> Option<ComponentId> + Send + Sync;
+
+/// Resource with the map of `Input` to states of `Super`.
+#[derive(Resource)]
+pub(crate) struct InputMap<Super: Component, Input: StateInput> {
+    map: Arc<MapFn<Input>>,
+    _p: PhantomData<Super>,
+}
+
+impl<Super: Component, Input: StateInput> InputMap<Super, Input> {
+    #[cfg_attr(not(feature = "bevy_app"), allow(dead_code))]
+    pub(crate) fn new(
+        map: impl Fn(&Input, Option<ComponentId>) -> Option<ComponentId> + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            map: Arc::new(map),
+            _p: PhantomData,
+        }
+    }
+}
+
+/// System mapping every `Input` to a state of `Super` by the map of
+/// [`SuperstateAppExt::register_input_map`](crate::SuperstateAppExt::register_input_map),
+/// and inserting the state by its constructor, like [`crate::process_transitions`].
+///
+/// Inputs are mapped in the order they were sent, and commands queued
+/// by the hooks of each transition are applied before the next input is mapped,
+/// so later inputs see the state entered by earlier ones.
+pub fn apply_state_inputs<Super: Component, Input: StateInput>(
+    world: &mut World,
+    mut cursor: Local<EventCursor<Input>>,
+) {
+    let Some(map) = world
+        .get_resource::<InputMap<Super, Input>>()
+        .map(|map| map.map.clone())
+    else {
+        return;
+    };
+    world.try_resource_scope(|world, events: Mut<Events<Input>>| {
+        for input in cursor.read(&events) {
+            let entity = input.entity();
+            let current = world
+                .get::<SuperstateInfo<Super>>(entity)
+                .and_then(SuperstateInfo::current_state);
+            if let Some(state) = map(input, current) {
+                apply_transition::<Super>(world, entity, state);
+            }
+        }
+    });
+}
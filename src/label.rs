@@ -0,0 +1,129 @@
+//! Human-readable label of the active state, e.g. for world inspectors.
+
+use std::{any::type_name, fmt, marker::PhantomData};
+
+use bevy_ecs::{
+    change_detection::DetectChangesMut,
+    component::{Component, ComponentId, Components},
+    entity::Entity,
+    query::Changed,
+    removal_detection::RemovedComponents,
+    system::{Commands, Query},
+};
+#[cfg(any(feature = "serde", feature = "bincode"))]
+use {crate::alias::StateAliases, bevy_ecs::system::Res};
+
+use crate::SuperstateInfo;
+
+/// Component with the name of the active state of `Super`, e.g. `"Movement::Walking"`,
+/// so world inspectors show states without custom code.
+/// The state is named by its alias of `state_alias` with `serde` or `bincode`
+/// feature, otherwise by the type name of its component without the module path.
+///
+/// Written by a system enabled with
+/// [`SuperstateBuilder::state_label`](crate::SuperstateBuilder::state_label),
+/// only when the active state changes. Like [`crate::CurrentState`],
+/// it is updated in `PostUpdate` schedule and removed with the last state.
+#[derive(Component, Debug)]
+pub struct StateLabel<Super: Component> {
+    label: String,
+    _p: PhantomData<Super>,
+}
+
+impl<Super: Component> StateLabel<Super> {
+    fn new(state: String) -> Self {
+        Self {
+            label: format!("{}::{state}", short_name(type_name::<Super>())),
+            _p: PhantomData,
+        }
+    }
+
+    /// Label of the active state.
+    pub fn as_str(&self) -> &str {
+        &self.label
+    }
+}
+
+impl<Super: Component> Clone for StateLabel<Super> {
+    fn clone(&self) -> Self {
+        Self {
+            label: self.label.clone(),
+            _p: PhantomData,
+        }
+    }
+}
+
+impl<Super: Component> PartialEq for StateLabel<Super> {
+    fn eq(&self, other: &Self) -> bool {
+        self.label == other.label
+    }
+}
+
+impl<Super: Component> fmt::Display for StateLabel<Super> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.label)
+    }
+}
+
+type ChangedInfo<'a, Super> = (
+    Entity,
+    &'a SuperstateInfo<Super>,
+    Option<&'a mut StateLabel<Super>>,
+);
+
+/// System writing the active state of `Super` into [`StateLabel<Super>`].
+pub(crate) fn update_state_label<Super: Component>(
+    mut commands: Commands,
+    components: &Components,
+    #[cfg(any(feature = "serde", feature = "bincode"))] aliases: Option<Res<StateAliases>>,
+    mut changed: Query<ChangedInfo<Super>, Changed<SuperstateInfo<Super>>>,
+    mut removed: RemovedComponents<SuperstateInfo<Super>>,
+) {
+    #[cfg(any(feature = "serde", feature = "bincode"))]
+    let alias = |id| aliases.as_deref().and_then(|aliases| aliases.get(id));
+    #[cfg(not(any(feature = "serde", feature = "bincode")))]
+    let alias = |_| None;
+    let name = |id: ComponentId| {
+        alias(id)
+            .map(str::to_owned)
+            .or_else(|| components.get_name(id).map(|name| short_name(&name)))
+    };
+    for (entity, info, label) in &mut changed {
+        match (info.current_state().and_then(name), label) {
+            (Some(state), Some(mut label)) => {
+                label.set_if_neq(StateLabel::new(state));
+            }
+            (Some(state), None) => {
+                commands
+                    .entity(entity)
+                    .insert(StateLabel::<Super>::new(state));
+            }
+            (None, Some(_)) => {
+                commands.entity(entity).remove::<StateLabel<Super>>();
+            }
+            (None, None) => {}
+        }
+    }
+    for entity in removed.read() {
+        if let Ok(mut entity) = commands.get_entity(entity) {
+            entity.try_remove::<StateLabel<Super>>();
+        }
+    }
+}
+
+/// Strips module paths from a type name, including its generic arguments,
+/// e.g. `game::Charge<game::Heavy>` becomes `Charge<Heavy>`.
+pub(crate) fn short_name(name: &str) -> String {
+    fn segment(path: &str) -> &str {
+        path.rsplit("::").next().unwrap_or(path)
+    }
+    let mut short = String::with_capacity(name.len());
+    let mut rest = name;
+    while let Some(i) = rest.find(['<', '>', ',', ' ', '(', ')', '[', ']', '&', ';']) {
+        short.push_str(segment(&rest[..i]));
+        short.push_str(&rest[i..=i]);
+        rest = &rest[i + 1..];
+    }
+    short.push_str(segment(rest));
+    short
+}
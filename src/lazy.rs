@@ -0,0 +1,91 @@
+//! Registration of superstate hooks on the first use of the superstate.
+
+use bevy_app::{App, PostUpdate};
+use bevy_ecs::{
+    change_detection::MaybeLocation,
+    component::{Component, HookContext},
+    entity::Entity,
+    observer::Trigger,
+    relationship::RelationshipHookMode,
+    schedule::IntoScheduleConfigs,
+    system::Commands,
+    world::{DeferredWorld, OnAdd, World},
+};
+
+use crate::{
+    StateSet, SuperstateBuilder, SuperstateDeclared, SuperstateRegistry, SuperstateSet, hooks,
+    scoped::{StateScopedExits, despawn_state_scoped, despawn_super_scoped},
+};
+
+/// Same as [`crate::superstate_plugin`], but registers the hooks only when `Super`
+/// is added to an entity for the first time, e.g. by spawning a state,
+/// so machines never used do not cost anything at startup.
+///
+/// Hooks of components that are already on an entity cannot be changed,
+/// so the superstate hooks of a lazily registered machine run as
+/// [`OnAdd`] and [`OnRemove`](bevy_ecs::world::OnRemove) observers,
+/// after all component hooks of the same insert. The entities spawned before
+/// the registration are put through the hooks right after it, as if the hooks
+/// were registered in time. Several entities spawned at once register the hooks only once.
+///
+/// Only the hooks and cleanup of [`StateScoped`](crate::StateScoped) children
+/// and [`SuperScoped`](crate::SuperScoped) helpers are registered,
+/// types, diagnostics and validation of enabled features
+/// need [`crate::superstate_plugin`] or [`SuperstateBuilder::build`].
+pub fn lazy_superstate_plugin<Super: Component, States: StateSet>(app: &mut App) {
+    app.init_resource::<SuperstateDeclared<Super>>()
+        .init_resource::<StateScopedExits<Super>>()
+        .add_observer(register_on_first_use::<Super, States>)
+        .add_systems(
+            PostUpdate,
+            (despawn_state_scoped::<Super>, despawn_super_scoped::<Super>)
+                .in_set(SuperstateSet::Cleanup),
+        );
+}
+
+fn register_on_first_use<Super: Component, States: StateSet>(
+    trigger: Trigger<OnAdd, Super>,
+    mut commands: Commands,
+) {
+    let (entity, caller) = (trigger.target(), trigger.caller());
+    commands.entity(trigger.observer()).try_despawn();
+    commands.queue(move |world: &mut World| {
+        if !world.contains_resource::<SuperstateRegistry<Super>>() {
+            SuperstateBuilder::<Super, States>::new()
+                .build_world(world)
+                .unwrap();
+        }
+        replay_hooks::<Super, States>(world, entity, caller);
+    });
+}
+
+/// Runs the on_add hooks of `Super` and its states present on `entity`,
+/// which was spawned before the hooks were registered.
+fn replay_hooks<Super: Component, States: StateSet>(
+    world: &mut World,
+    entity: Entity,
+    caller: MaybeLocation,
+) {
+    let Ok(entity_ref) = world.get_entity(entity) else {
+        return;
+    };
+    let registry = world.resource::<SuperstateRegistry<Super>>();
+    let ids = registry
+        .state_ids()
+        .iter()
+        .copied()
+        .filter(|id| entity_ref.contains_id(*id))
+        .collect::<Vec<_>>();
+    let super_id = registry.super_id();
+    let ctx = |component_id| HookContext {
+        entity,
+        component_id,
+        caller,
+        relationship_hook_mode: RelationshipHookMode::Run,
+    };
+    for id in ids {
+        hooks::on_add_hook_state::<Super, States>(DeferredWorld::from(&mut *world), ctx(id));
+    }
+    hooks::on_add_superstate::<Super, States>(DeferredWorld::from(&mut *world), ctx(super_id));
+    world.flush();
+}
@@ -1,6 +1,7 @@
 //! # Example
 //!
-//! ```
+#![cfg_attr(feature = "bevy_app", doc = "```")]
+#![cfg_attr(not(feature = "bevy_app"), doc = "```ignore")]
 //! use superstate::{superstate_plugin, SuperstateInfo};
 //! use bevy_app::App;
 //! use bevy_ecs::component::Component;
@@ -26,7 +27,8 @@
 //!
 //! To register different super states, call the plugin with different types.
 //!
-//! ```
+#![cfg_attr(feature = "bevy_app", doc = "```")]
+#![cfg_attr(not(feature = "bevy_app"), doc = "```ignore")]
 //! use superstate::{superstate_plugin, SuperstateInfo};
 //! use bevy_app::App;
 //! use bevy_ecs::component::Component;
@@ -63,120 +65,1157 @@
 //! }
 //! ```
 
-use std::marker::PhantomData;
+use std::{any::type_name, marker::PhantomData, time::Duration};
 
+#[cfg(feature = "bevy_app")]
 use bevy_app::App;
 use bevy_ecs::{
     bundle::Bundle,
-    component::{Component, ComponentId},
+    component::{Component, ComponentHook, ComponentId, Components, Tick},
+    entity::Entity,
     error::BevyError,
-    world::World,
+    resource::Resource,
+    world::{DeferredWorld, World},
 };
-use hooks::HookBusyError;
+#[cfg(feature = "bevy_time")]
+use bevy_time::Time;
+use log::Level;
+use smallvec::SmallVec;
+
+#[cfg(feature = "reflect")]
+use bevy_ecs::reflect::ReflectComponent;
+#[cfg(feature = "reflect")]
+use bevy_reflect::{Reflect, std_traits::ReflectDefault};
+
+use chain::{chain_hooks, is_chained};
+use hooks::{MissingRequireError, NotRegisteredError, RegisterError, StateCollisionError};
+use registry::Superstates;
+
+#[cfg(any(feature = "serde", feature = "bincode"))]
+pub use alias::state_alias;
+#[cfg(feature = "bevy_app")]
+pub use app::SuperstateAppExt;
+#[cfg(feature = "asset")]
+pub use asset::{TransitionGraph, TransitionGraphError, TransitionGraphLoader};
+pub use builder::SuperstateBuilder;
+pub use chain::HookOrder;
+pub use commands::{SuperstateCommands, spawn_in_state};
+pub use constructor::{register_default_state, register_state_constructor};
+pub use current::CurrentState;
+#[cfg(feature = "diagnostic")]
+pub use diagnostic::SuperstateDiagnostics;
+pub use dot::export_dot;
+pub use event::{
+    StateEntered, StateExited, SuperstateEntered, SuperstateExited, TransitionRejected,
+};
+pub use exit::on_exit_with;
+pub use history::{TransitionLog, TransitionRecord};
+pub use input::{StateInput, apply_state_inputs};
+pub use label::StateLabel;
+#[cfg(feature = "bevy_app")]
+pub use lazy::lazy_superstate_plugin;
+#[cfg(feature = "bevy_state")]
+pub use mirror::{StateMirror, StateMirrorBuilder};
+pub use occupancy::StateOccupancy;
+#[cfg(feature = "bevy_app")]
+pub use plugin::SuperstatePlugin;
+pub use policy::{
+    ConflictPolicy, StatePriorities, StatePrioritiesBuilder, StatePriority, resolve_transition,
+};
+pub use query::{
+    AnyOfStates, StateChanged, StateEnumQuery, StateJustChanged, SuperstateQuery,
+    dump_entity_states, entities_in_state, try_current_state,
+};
+pub use registry::{SuperstateRegistry, resolve_state_id, super_component_id};
+pub use request::{TransitionRequest, process_transitions};
+pub use scoped::{StateScoped, SuperScoped, SuperScopedEntities};
+pub use set::SuperstateSet;
+#[cfg(feature = "bincode")]
+pub use snapshot::{restore_entity_states, snapshot_entity_states};
+pub use state_enum::{StateEnum, StateEnumBuilder, register_state_enum};
+pub use state_set::StateSet;
+pub use transfer::{TransferState, register_transfer};
+pub use transition::{
+    RejectReason, StateLock, TransitionGuard, TransitionTable, TransitionTableBuilder,
+    can_transition, force_state,
+};
+#[cfg(feature = "async")]
+pub use wait::{WaitCancelled, WaitForState, wait_for_state};
+
+#[cfg(any(feature = "serde", feature = "bincode"))]
+mod alias;
+#[cfg(feature = "bevy_app")]
+mod app;
+#[cfg(feature = "asset")]
+pub mod asset;
+pub mod builder;
+pub mod chain;
+pub mod commands;
+pub mod condition;
+pub mod constructor;
+pub mod current;
+#[cfg(feature = "diagnostic")]
+pub mod diagnostic;
+mod dot;
+pub mod event;
+pub mod exit;
+pub mod history;
+pub mod input;
+// Only the component is used without `bevy_app`, the system is added by `build`.
+#[cfg_attr(not(feature = "bevy_app"), allow(dead_code))]
+pub mod label;
+#[cfg(feature = "bevy_app")]
+mod lazy;
+#[cfg(feature = "bevy_state")]
+pub mod mirror;
+pub mod occupancy;
+#[cfg(feature = "bevy_app")]
+mod plugin;
+pub mod policy;
+mod query;
+#[cfg(feature = "reflect")]
+mod reflect;
+pub mod registry;
+pub mod request;
+pub mod scoped;
+#[cfg(feature = "serde")]
+mod serialize;
+mod set;
+#[cfg(feature = "bincode")]
+mod snapshot;
+pub mod state_enum;
+pub mod state_set;
+#[cfg(feature = "testing")]
+pub mod testing;
+pub mod transfer;
+pub mod transition;
+#[cfg(feature = "validate")]
+pub mod validate;
+#[cfg(feature = "async")]
+pub mod wait;
 
 pub mod hooks {
-    use std::{error::Error, fmt::Display};
+    use std::{
+        any::{TypeId, type_name},
+        collections::BTreeSet,
+        error::Error,
+        fmt::Display,
+        sync::Mutex,
+    };
 
     use bevy_ecs::{
-        bundle::Bundle,
+        change_detection::DetectChangesMut,
         component::{Component, ComponentId, HookContext},
-        world::DeferredWorld,
+        entity::Entity,
+        system::Commands,
+        world::{DeferredWorld, EntityWorldMut, World},
     };
 
-    use crate::SuperstateInfo;
+    #[cfg(feature = "bevy_time")]
+    use bevy_time::Time;
+    use log::{log, warn};
+    use smallvec::SmallVec;
+
+    use crate::{
+        ConflictPolicy, CurrentState, StateLabel, StateLock, StateOccupancy, StatePriorities,
+        StateSet, SuperstateHooks, SuperstateInfo, SuperstateRegistry, TransitionLog,
+        event::{
+            StateEnteredTriggers, StateExitedTriggers, SuperstateEntered, SuperstateExited,
+            TransitionRejected,
+        },
+        exit::StateExitHandlers,
+        policy::resolve_transition,
+        registry::InsertFn,
+        transfer::StateTransfers,
+        transition::{RejectReason, check_transition},
+    };
+
+    /// Kind of a component hook.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub enum HookKind {
+        /// `on_add` hook.
+        OnAdd,
+        /// `on_remove` hook.
+        OnRemove,
+    }
+
+    impl Display for HookKind {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            match self {
+                HookKind::OnAdd => write!(f, "on_add"),
+                HookKind::OnRemove => write!(f, "on_remove"),
+            }
+        }
+    }
 
     /// When registering components as states or as super states,
     /// a case may occur where the component hooks are already registered.
     /// For example, if you use the same components for relationships.
-    /// Currently, this limitation cannot be bypassed, since a component can only have one hook.
+    /// Hooks of different superstates are chained, and hooks registered
+    /// not by this crate are bypassed with observers, see [`crate::chain`].
+    /// Returned if the component is not registered in the world.
+    ///
+    /// Use [`Components::get_name`](bevy_ecs::component::Components::get_name)
+    /// with [`RegisterError::id`] to get the type name of the component.
+    #[derive(Debug, Clone)]
+    pub enum RegisterError {
+        /// Hook of a state component is busy.
+        StateHookBusy { id: ComponentId, kind: HookKind },
+        /// Hook of the superstate component is busy.
+        SuperHookBusy { id: ComponentId, kind: HookKind },
+    }
+
+    impl RegisterError {
+        /// Id of the component with busy hook.
+        pub fn id(&self) -> ComponentId {
+            match self {
+                RegisterError::StateHookBusy { id, .. }
+                | RegisterError::SuperHookBusy { id, .. } => *id,
+            }
+        }
+
+        /// Kind of the busy hook.
+        pub fn kind(&self) -> HookKind {
+            match self {
+                RegisterError::StateHookBusy { kind, .. }
+                | RegisterError::SuperHookBusy { kind, .. } => *kind,
+            }
+        }
+    }
+
+    impl Display for RegisterError {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            match self {
+                RegisterError::StateHookBusy { id, kind } => {
+                    write!(f, "Hook {kind} on state component({id:?}) is busy.")
+                }
+                RegisterError::SuperHookBusy { id, kind } => {
+                    write!(f, "Hook {kind} on superstate component({id:?}) is busy.")
+                }
+            }
+        }
+    }
+
+    impl Error for RegisterError {}
+
+    /// Old name of [`RegisterError`].
+    #[deprecated(note = "use `RegisterError`")]
+    pub type HookBusyError = RegisterError;
+
+    /// Returned by [`crate::deregister_hooks`] when there are no hooks to deregister,
+    /// and by [`crate::register_state`] when `Super` is not registered.
+    #[derive(Debug, Clone)]
+    pub struct NotRegisteredError;
+
+    impl Display for NotRegisteredError {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            write!(f, "Superstate hooks are not registered.")
+        }
+    }
+
+    impl Error for NotRegisteredError {}
+
+    /// Returned when a component expected to be one of `States` is not in the `States` bundle.
     #[derive(Debug, Clone)]
-    pub struct HookBusyError(pub ComponentId);
+    pub struct UnknownStateError(pub ComponentId);
+
+    impl Display for UnknownStateError {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            write!(f, "Component({:?}) is not a registered state.", self.0)
+        }
+    }
+
+    impl Error for UnknownStateError {}
+
+    /// Returned when a state component does not require the superstate,
+    /// or the superstate does not require its [`SuperstateInfo`].
+    /// Without it inserting the component panics in the hooks.
+    #[derive(Debug, Clone)]
+    pub struct MissingRequireError {
+        /// Id of the component without `#[require]`.
+        pub id: ComponentId,
+        /// Type name of the component without `#[require]`.
+        pub name: String,
+        /// Type name of the component that should be required.
+        pub required: &'static str,
+    }
 
-    impl Display for HookBusyError {
+    impl Display for MissingRequireError {
         fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-            write!(f, "Hook on this component({:?}) is busy.", self.0)
+            write!(
+                f,
+                "Component {}({:?}) should have `#[require({})]` attribute.",
+                self.name, self.id, self.required
+            )
         }
     }
 
-    impl Error for HookBusyError {}
+    impl Error for MissingRequireError {}
+
+    /// Returned when the same component is listed in both `Super` and `States`,
+    /// or more than once in `States`, e.g. after a copy-paste mistake.
+    /// Otherwise hooks of the component would overwrite each other.
+    #[derive(Debug, Clone)]
+    pub enum StateCollisionError {
+        /// The superstate component is also one of `States`.
+        SuperIsState { id: ComponentId, name: String },
+        /// The state component is listed in `States` more than once.
+        DuplicateState { id: ComponentId, name: String },
+    }
+
+    impl Display for StateCollisionError {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            match self {
+                StateCollisionError::SuperIsState { id, name } => {
+                    write!(f, "Superstate component {name}({id:?}) is also a state.")
+                }
+                StateCollisionError::DuplicateState { id, name } => {
+                    write!(
+                        f,
+                        "State component {name}({id:?}) is listed more than once."
+                    )
+                }
+            }
+        }
+    }
+
+    impl Error for StateCollisionError {}
+
+    /// Any error of this crate, e.g. for hooks of downstream libraries
+    /// composing several fallible calls with `?`.
+    /// Registration errors are wrapped as they are, so they can still be matched.
+    #[derive(Debug, Clone)]
+    pub enum SuperstateError {
+        /// The entity does not exist.
+        NoEntity(Entity),
+        /// The entity has no [`SuperstateInfo`] of the superstate.
+        NoInfo(Entity),
+        /// See [`RegisterError`].
+        Register(RegisterError),
+        /// See [`NotRegisteredError`].
+        NotRegistered(NotRegisteredError),
+        /// See [`UnknownStateError`].
+        UnknownState(UnknownStateError),
+        /// See [`MissingRequireError`].
+        MissingRequire(MissingRequireError),
+        /// See [`StateCollisionError`].
+        StateCollision(StateCollisionError),
+    }
+
+    impl Display for SuperstateError {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            match self {
+                SuperstateError::NoEntity(entity) => write!(f, "{entity} does not exist."),
+                SuperstateError::NoInfo(entity) => write!(f, "{entity} has no superstate info."),
+                SuperstateError::Register(error) => error.fmt(f),
+                SuperstateError::NotRegistered(error) => error.fmt(f),
+                SuperstateError::UnknownState(error) => error.fmt(f),
+                SuperstateError::MissingRequire(error) => error.fmt(f),
+                SuperstateError::StateCollision(error) => error.fmt(f),
+            }
+        }
+    }
+
+    impl Error for SuperstateError {
+        fn source(&self) -> Option<&(dyn Error + 'static)> {
+            match self {
+                SuperstateError::NoEntity(_) | SuperstateError::NoInfo(_) => None,
+                SuperstateError::Register(error) => Some(error),
+                SuperstateError::NotRegistered(error) => Some(error),
+                SuperstateError::UnknownState(error) => Some(error),
+                SuperstateError::MissingRequire(error) => Some(error),
+                SuperstateError::StateCollision(error) => Some(error),
+            }
+        }
+    }
+
+    macro_rules! from_error {
+        ($($error:ident => $variant:ident),* $(,)?) => {
+            $(
+                impl From<$error> for SuperstateError {
+                    fn from(error: $error) -> Self {
+                        SuperstateError::$variant(error)
+                    }
+                }
+            )*
+        };
+    }
+
+    from_error! {
+        RegisterError => Register,
+        NotRegisteredError => NotRegistered,
+        UnknownStateError => UnknownState,
+        MissingRequireError => MissingRequire,
+        StateCollisionError => StateCollision,
+    }
+
+    /// Logs that `entity` has no [`SuperstateInfo`], only the first time,
+    /// so misconfigured entities do not flood the log.
+    fn warn_missing_info<Super: Component>(entity: Entity) {
+        static WARNED: Mutex<BTreeSet<TypeId>> = Mutex::new(BTreeSet::new());
+        if first_warning::<Super>(&WARNED) {
+            warn!(
+                "{entity} has no {}, superstate hooks are skipped.",
+                type_name::<SuperstateInfo<Super>>()
+            );
+        }
+    }
+
+    /// Returns `true` the first time it is called for `Super` with `warned`.
+    /// Statics in generic functions are shared by all `Super`, so they are keyed by type.
+    fn first_warning<Super: Component>(warned: &Mutex<BTreeSet<TypeId>>) -> bool {
+        warned
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .insert(TypeId::of::<Super>())
+    }
+
+    /// Hook that called when adding [`SuperstateInfo`], usually required by `Super`.
+    /// In debug builds warns if `Super` is not registered, e.g. its plugin is forgotten,
+    /// so states of `Super` are never deduplicated. Warns only once for each `Super`.
+    ///
+    /// Drops ids of states that are not registered states of `Super` on the entity,
+    /// e.g. of an info copied from another world, see [`crate::resolve_state_id`],
+    /// together with the previous state and the history stack.
+    pub fn on_add_info<Super: Component>(mut world: DeferredWorld, ctx: HookContext) {
+        drop_foreign_ids::<Super>(&mut world, ctx.entity);
+        #[cfg(debug_assertions)]
+        {
+            static WARNED: Mutex<BTreeSet<TypeId>> = Mutex::new(BTreeSet::new());
+            if !world.contains_resource::<crate::SuperstateDeclared<Super>>()
+                && first_warning::<Super>(&WARNED)
+            {
+                warn!(
+                    "{} is added, but {} is not registered, superstate hooks never run for it. \
+                     Add its plugin or call register_hooks.",
+                    type_name::<SuperstateInfo<Super>>(),
+                    type_name::<Super>()
+                );
+            }
+        }
+    }
+
+    fn drop_foreign_ids<Super: Component>(world: &mut DeferredWorld, entity: Entity) {
+        let (Some(registry), Ok(entity_ref)) = (
+            world.get_resource::<SuperstateRegistry<Super>>(),
+            world.get_entity(entity),
+        ) else {
+            return;
+        };
+        let Some(info) = entity_ref.get::<SuperstateInfo<Super>>() else {
+            return;
+        };
+        let registered = |id: &ComponentId| registry.state_ids().contains(id);
+        let active = info
+            .states_on_entity
+            .iter()
+            .copied()
+            .filter(|id| registered(id) && entity_ref.contains_id(*id))
+            .collect::<SmallVec<_>>();
+        if active.len() == info.states_on_entity.len()
+            && info.prev_state.is_none_or(|id| registered(&id))
+            && info.exited.is_none_or(|id| registered(&id))
+            && info.stack.iter().all(registered)
+        {
+            return;
+        }
+        warn!(
+            "{} of {entity} has ids of states, which are not states on the entity. \
+             Component ids are not valid across worlds, they are dropped.",
+            type_name::<SuperstateInfo<Super>>()
+        );
+        // The previous state and the stack are not on the entity to be checked,
+        // so the history of a foreign info is dropped as a whole.
+        let mut info = world.get_mut::<SuperstateInfo<Super>>(entity).unwrap();
+        let info = info.bypass_change_detection();
+        info.states_on_entity = active;
+        info.prev_state = None;
+        info.exited = None;
+        info.stack.clear();
+    }
 
     /// Hook that called when adding any state component from `States`.
     /// Removes the rest of the state components because the state should be unique.
-    /// If you add multiple states to an entity at once, only the last new one will remain.
-    pub fn on_add_hook_state<Super: Component, States: Bundle>(
+    /// If you add multiple states to an entity at once, only one of them will remain,
+    /// by default the last new one. See [`crate::ConflictPolicy`] and [`crate::resolve_transition`].
+    ///
+    /// If [`crate::TransitionTable`] resource exists and does not allow the transition,
+    /// removes the added state instead and keeps the current one.
+    /// The same for [`crate::TransitionGuard`] resource.
+    /// Rejected transitions trigger [`TransitionRejected`].
+    /// States inserted by [`crate::force_state`] skip these checks.
+    /// The current state is never removed and stays [`SuperstateInfo::current_state`],
+    /// so the entity is not seen without a state while the rejected one is removed.
+    ///
+    /// Calls [`crate::TransferState`] registered for the transition
+    /// before the old state is removed.
+    ///
+    /// With [`crate::register_parallel_hooks`] other states are kept,
+    /// all added states are entered in the order they are added,
+    /// and the transition checks do not apply.
+    ///
+    /// Triggers [`crate::StateEntered`] after the old state is removed.
+    ///
+    /// Bevy inserts components required by the state, `Super` and [`SuperstateInfo`],
+    /// in the same archetype move as the state and runs on_add hooks only after it,
+    /// so the info is present here whatever order the hooks run in.
+    /// [`crate::register_hooks`] checks that the `require` chain exists.
+    /// If the entity still has no [`SuperstateInfo`], e.g. a dynamic state of
+    /// [`crate::register_state`] is inserted without `Super`, inserts it
+    /// together with `Super` by a command, and then runs again.
+    pub fn on_add_hook_state<Super: Component, States: StateSet>(
         mut world: DeferredWorld,
         ctx: HookContext,
     ) {
-        let mut ids = Vec::new();
-        States::get_component_ids(world.components(), &mut |id| ids.push(id.unwrap()));
+        if !SuperstateHooks::<Super, States>::enabled(&world) {
+            return;
+        }
+        let registry = world.resource::<SuperstateRegistry<Super>>();
+        let ids = registry.state_ids();
+        let parallel = registry.parallel;
+        let policy = registry.policy;
+        let Ok(entity) = world.get_entity(ctx.entity) else {
+            return;
+        };
+        let Some(info) = entity.get::<SuperstateInfo<Super>>() else {
+            // The state was inserted without `Super`, e.g. a dynamic state by id,
+            // so `Super` is inserted as `require` would do, and the hook runs again.
+            let insert_super = registry.insert_super;
+            world.commands().queue(move |world: &mut World| {
+                insert_missing_super::<Super, States>(world, ctx, insert_super);
+            });
+            return;
+        };
+        // State restored from a deserialized info, which is not yet in `states_on_entity`.
+        #[cfg(feature = "serde")]
+        let restored = info.restored_state(&world, ids, &entity);
+        #[cfg(not(feature = "serde"))]
+        let restored = None;
+        // Hooks of states added together run one after another in the order they are added,
+        // before the commands they queue, so the states whose hooks already ran
+        // are kept in `batch` until a command clears it. The states added after this one
+        // are not known yet, only that they are on entity but not in `states_on_entity`,
+        // so they are resolved only by priority, which does not depend on their order.
+        let mut batch = info.batch.clone();
+        batch.push(ctx.component_id);
+        let first = batch.len() == 1;
+        let known = batch.len();
+        if policy == ConflictPolicy::KeepHighestPriority {
+            batch.extend(ids.iter().copied().filter(|id| {
+                entity.contains_id(*id)
+                    && !info.states_on_entity.contains(id)
+                    && !info.batch.contains(id)
+                    && *id != ctx.component_id
+                    && restored != Some(*id)
+            }));
+        }
+        let priorities = world.get_resource::<StatePriorities<Super>>();
+        // Parallel states are all kept.
+        let mut accepted =
+            parallel || resolve_transition(&batch, policy, priorities) == Some(ctx.component_id);
+        batch.truncate(known);
+        let mut rejected = None;
+        let forced = info.forced == Some(ctx.component_id);
+        if accepted
+            && !forced
+            && !parallel
+            && let Some(current) = info.current_state().or(restored)
+            && let Err(rejection) =
+                check_transition(&world, ctx.entity, info, current, ctx.component_id)
+        {
+            let components = world.components();
+            let (from, to) = (
+                components.get_name(current).unwrap_or_default(),
+                components.get_name(ctx.component_id).unwrap_or_default(),
+            );
+            #[cfg(debug_assertions)]
+            if registry.strict {
+                let by = match rejection {
+                    RejectReason::Table => Some("TransitionTable"),
+                    RejectReason::Guard => Some("TransitionGuard"),
+                    RejectReason::MinDwell(_) | RejectReason::Locked => None,
+                };
+                if let Some(by) = by {
+                    panic!(
+                        "Transition of {} from {from} to {to} is not allowed by {by} in strict mode.",
+                        ctx.entity,
+                    );
+                }
+            }
+            match rejection {
+                RejectReason::Table => warn!(
+                    "Transition of {} from {from} to {to} is not allowed by TransitionTable.",
+                    ctx.entity,
+                ),
+                RejectReason::Guard => warn!(
+                    "Transition of {} from {from} to {to} is not allowed by TransitionGuard.",
+                    ctx.entity,
+                ),
+                RejectReason::MinDwell(min_dwell) if registry.log_min_dwell => warn!(
+                    "Transition of {} from {from} to {to} is rejected by minimum dwell time {min_dwell:?}.",
+                    ctx.entity,
+                ),
+                RejectReason::MinDwell(_) | RejectReason::Locked => {}
+            }
+            rejected = Some((current, rejection));
+            accepted = false;
+        }
+        if accepted
+            && let Some(level) = registry.log_level
+            && info.current_state() != Some(ctx.component_id)
+        {
+            let components = world.components();
+            log!(
+                level,
+                "{}: {} -> {}",
+                type_name::<Super>(),
+                info.current_state()
+                    .or(restored)
+                    .and_then(|id| components.get_name(id))
+                    .unwrap_or("None".into()),
+                components.get_name(ctx.component_id).unwrap_or_default(),
+            );
+        }
+        if accepted
+            && let Some(current) = info.current_state().or(restored)
+            && let Some(transfer) = StateTransfers::<Super>::get(&world, current, ctx.component_id)
+        {
+            transfer(&mut world, ctx.entity);
+        }
+        #[cfg(feature = "bevy_time")]
+        let now = world.get_resource::<Time>().map(Time::elapsed);
+        #[cfg(not(feature = "bevy_time"))]
+        let now = None;
+        let tick = world.read_change_tick();
+        let trigger = StateEnteredTriggers::<Super>::get(&world, ctx.component_id);
+        #[cfg(feature = "serde")]
+        let state_name = crate::alias::StateAliases::name(&world, ctx.component_id);
         let (mut entities, mut cmd) = world.entities_and_commands();
-        let mut entity = entities.get_mut(ctx.entity).unwrap();
-        let mut info = entity.get_mut::<SuperstateInfo<Super>>().unwrap();
-        if info.state_ids.len() == 0 {
-            info.state_ids = ids.into();
+        let Ok(mut entity) = entities.get_mut(ctx.entity) else {
+            return;
+        };
+        let Some(mut info) = entity.get_mut::<SuperstateInfo<Super>>() else {
+            warn_missing_info::<Super>(ctx.entity);
+            return;
+        };
+        // Bookkeeping does not change the active state,
+        // so it bypasses change detection.
+        let bookkeeping = info.bypass_change_detection();
+        let mut occupied = SmallVec::<[ComponentId; 2]>::new();
+        if let Some(restored) = restored
+            && restored != ctx.component_id
+        {
+            bookkeeping.states_on_entity.push(restored);
+            occupied.push(restored);
+        }
+        bookkeeping.batch = batch;
+        if first {
+            queue_if_exists(&mut cmd, ctx.entity, |entity| {
+                if let Some(mut info) = entity.get_mut::<SuperstateInfo<Super>>() {
+                    info.bypass_change_detection().batch.clear();
+                }
+            });
+        }
+        if !accepted {
+            // Only the rejected state is removed, the current one keeps its place.
+            cmd.entity(ctx.entity).remove_by_id(ctx.component_id);
+            if let Some((current, reason)) = rejected {
+                TransitionRejected::<Super>::trigger(
+                    &mut cmd,
+                    ctx.entity,
+                    current,
+                    ctx.component_id,
+                    reason,
+                );
+            }
+            StateOccupancy::<Super>::enter(&mut world, &occupied);
+            return;
+        }
+        let entered = info.current_state() != Some(ctx.component_id);
+        let prev = info.current_state();
+        // Re-adding the active state does not change it, so the info is marked
+        // as changed only when the state is entered, see `StateJustChanged`.
+        let state = info.bypass_change_detection();
+        if entered {
+            state.state_since = now;
+            state.exited = None;
+            state.last_entered = Some((ctx.component_id, tick));
         }
-        info.states_on_entity.push(ctx.component_id);
-        for id in info.states_on_entity.iter() {
-            if *id != ctx.component_id {
-                cmd.entity(ctx.entity).remove_by_id(*id);
+        if entered && prev.is_some() {
+            state.prev_state = prev;
+        }
+        if !state.states_on_entity.contains(&ctx.component_id) {
+            occupied.push(ctx.component_id);
+        }
+        // Re-added state is moved to the end instead of duplicated,
+        // otherwise its removal would leave a stale id behind.
+        state.states_on_entity.retain(|id| *id != ctx.component_id);
+        state.states_on_entity.push(ctx.component_id);
+        #[cfg(feature = "serde")]
+        {
+            state.state_name = state_name;
+        }
+        if !parallel {
+            for id in state.states_on_entity.iter() {
+                if *id != ctx.component_id {
+                    cmd.entity(ctx.entity).remove_by_id(*id);
+                }
             }
         }
+        if entered {
+            info.set_changed();
+        }
+        if entered && let Some(trigger) = trigger {
+            trigger(&mut cmd, ctx.entity, prev);
+        }
+        StateOccupancy::<Super>::enter(&mut world, &occupied);
+        if entered {
+            TransitionLog::<Super>::record(&mut world, ctx.entity, prev, ctx.component_id);
+        }
+        #[cfg(feature = "async")]
+        if entered {
+            crate::wait::StateWaiters::<Super>::entered(&mut world, ctx.entity, ctx.component_id);
+        }
+        #[cfg(feature = "diagnostic")]
+        if entered {
+            crate::SuperstateDiagnostics::<Super>::record(&mut world);
+        }
+    }
+
+    fn insert_missing_super<Super: Component, States: StateSet>(
+        world: &mut World,
+        ctx: HookContext,
+        insert_super: Option<InsertFn>,
+    ) {
+        let Ok(mut entity) = world.get_entity_mut(ctx.entity) else {
+            return;
+        };
+        if !entity.contains_id(ctx.component_id) || entity.contains::<SuperstateInfo<Super>>() {
+            return;
+        }
+        if entity.contains::<Super>() {
+            // Other states on the entity are tracked again, so the transition removes them.
+            let ids = entity.world_scope(|world: &mut World| {
+                world
+                    .resource::<SuperstateRegistry<Super>>()
+                    .state_ids()
+                    .to_vec()
+            });
+            let mut info = SuperstateInfo::<Super>::default();
+            info.states_on_entity.extend(
+                ids.into_iter()
+                    .filter(|id| *id != ctx.component_id && entity.contains_id(*id)),
+            );
+            info.entered = true;
+            let occupied = info.states_on_entity.clone();
+            entity.insert(info);
+            entity.world_scope(|world: &mut World| {
+                StateOccupancy::<Super>::enter(&mut world.into(), &occupied);
+            });
+        } else if let Some(insert_super) = insert_super {
+            insert_super(&mut entity);
+        } else {
+            warn_missing_info::<Super>(ctx.entity);
+            return;
+        }
+        on_add_hook_state::<Super, States>(world.into(), ctx);
+        world.flush();
     }
 
     /// Hook that called when removing any state component from `States`.
-    /// Remove `Super` componet if no others states.
-    pub fn on_remove_hook_state<Super: Component, States: Bundle>(
+    /// Calls handlers registered with [`crate::on_exit_with`]
+    /// and triggers [`crate::StateExited`] if the state was active.
+    /// Remove `Super` componet if no others states,
+    /// unless it is registered with [`crate::register_hooks_allowing_empty`]
+    /// or [`crate::register_hooks_with_fallback`].
+    ///
+    /// The state and `Super` can be removed together in any order:
+    /// the state already dropped by [`on_remove_superstate`] is ignored,
+    /// and the fallback state is not inserted without `Super`.
+    pub fn on_remove_hook_state<Super: Component, States: StateSet>(
         mut world: DeferredWorld,
         ctx: HookContext,
     ) {
+        if !SuperstateHooks::<Super, States>::enabled(&world) {
+            return;
+        }
+        exit_state::<Super>(&mut world, ctx.entity, ctx.component_id);
+        let registry = world.resource::<SuperstateRegistry<Super>>();
+        let (allow_empty, fallback) = (registry.allow_empty, registry.fallback);
+        let tick = world.read_change_tick();
         let (mut entities, mut cmd) = world.entities_and_commands();
-        let mut entity = entities.get_mut(ctx.entity).unwrap();
-        let mut info = entity.get_mut::<SuperstateInfo<Super>>().unwrap();
-        info.remove_by_id(ctx.component_id);
+        let Ok(mut entity) = entities.get_mut(ctx.entity) else {
+            return;
+        };
+        let Some(mut info) = entity.get_mut::<SuperstateInfo<Super>>() else {
+            warn_missing_info::<Super>(ctx.entity);
+            return;
+        };
+        // Rejected states were never on `states_on_entity`,
+        // their removal should not be detected as a change.
+        let bookkeeping = info.bypass_change_detection();
+        let removed = bookkeeping.remove_by_id(ctx.component_id);
+        if removed {
+            bookkeeping.exited = Some(ctx.component_id);
+            bookkeeping.last_exited = Some((ctx.component_id, tick));
+            info.set_changed();
+        }
         if info.states_on_entity.is_empty() {
-            cmd.entity(ctx.entity).remove::<Super>();
+            #[cfg(feature = "serde")]
+            {
+                info.state_name = None;
+            }
+            // Only removal of an active state falls back, so a rejected
+            // or removed with `Super` fallback state is not inserted again.
+            // `Super` removed in the same flush would be brought back by `require`
+            // of the fallback state, so it is checked when the command runs.
+            if let Some(fallback) = fallback {
+                if removed {
+                    queue_if_exists(&mut cmd, ctx.entity, move |entity| {
+                        if entity.contains::<Super>() {
+                            fallback(entity);
+                        }
+                    });
+                }
+            } else if !allow_empty {
+                queue_if_exists(&mut cmd, ctx.entity, |entity| {
+                    entity.remove::<Super>();
+                });
+            }
+        }
+        if removed {
+            StateOccupancy::<Super>::exit(&mut world, &[ctx.component_id]);
         }
     }
 
     /// Hook that called when adding `Super` component.
-    /// If you try inset `Super` component when no any states component on entity, `Super` no will be added.
-    pub fn on_add_superstate<Super: Component, States: Bundle>(
+    /// If you try inset `Super` component when no any states component on entity, `Super` no will be added,
+    /// unless it is registered with [`crate::register_hooks_allowing_empty`].
+    ///
+    /// With a default state set by [`crate::SuperstateBuilder::default_state_with`]
+    /// the entity is put into the state built by its factory instead.
+    ///
+    /// Triggers [`SuperstateEntered`] if `Super` stays on the entity.
+    pub fn on_add_superstate<Super: Component, States: StateSet>(
         mut world: DeferredWorld,
         ctx: HookContext,
     ) {
-        let (entities, mut cmd) = world.entities_and_commands();
-        let entity = entities.get(ctx.entity).unwrap();
-        let info = entity.get::<SuperstateInfo<Super>>().unwrap();
-        if info.states_on_entity.is_empty() {
-            cmd.entity(ctx.entity).remove::<Super>();
+        if !SuperstateHooks::<Super, States>::enabled(&world) {
+            return;
+        }
+        let registry = world.resource::<SuperstateRegistry<Super>>();
+        let (initial, allow_empty) = (registry.initial.clone(), registry.allow_empty);
+        // Hooks of states added together with `Super` may not have run yet,
+        // so the entity itself is checked instead of `states_on_entity`.
+        if world
+            .get_entity(ctx.entity)
+            .is_ok_and(|entity| !registry.has_state(&entity))
+        {
+            let mut cmd = world.commands();
+            match initial {
+                Some(initial) => queue_if_exists(&mut cmd, ctx.entity, move |entity| {
+                    (initial.0)(entity);
+                }),
+                None if allow_empty => {}
+                None => {
+                    cmd.entity(ctx.entity).remove::<Super>();
+                    return;
+                }
+            }
+        }
+        enter_superstate::<Super>(&mut world, ctx.entity);
+    }
+
+    /// Marks that `entity` keeps `Super`, and triggers [`SuperstateEntered`] if observed.
+    fn enter_superstate<Super: Component>(world: &mut DeferredWorld, entity: Entity) {
+        if let Some(mut info) = world.get_mut::<SuperstateInfo<Super>>(entity) {
+            info.bypass_change_detection().entered = true;
+        }
+        if SuperstateEntered::<Super>::observed(world) {
+            SuperstateEntered::<Super>::trigger(&mut world.commands(), entity);
         }
     }
 
-    /// Hook that called when removing `Super` component. Remove all `States`.
-    pub fn on_remove_superstate<Super: Component, States: Bundle>(
+    /// Hook that called when removing `Super` component. Remove all `States`
+    /// and reset [`SuperstateInfo`], or remove it if enabled with
+    /// [`crate::SuperstateBuilder::remove_info`].
+    /// Also removes [`CurrentState`], [`StateLabel`] and [`StateLock`] of `Super`,
+    /// so pooled entities do not keep components of the machine.
+    ///
+    /// Triggers [`SuperstateExited`] if the entity entered the machine.
+    pub fn on_remove_superstate<Super: Component, States: StateSet>(
         mut world: DeferredWorld,
         ctx: HookContext,
     ) {
+        if !SuperstateHooks::<Super, States>::enabled(&world) {
+            return;
+        }
+        // States are removed after the info is reset, so they exit here.
+        let active = world
+            .get::<SuperstateInfo<Super>>(ctx.entity)
+            .map(|info| info.states_on_entity.clone())
+            .unwrap_or_default();
+        for id in &active {
+            exit_state::<Super>(&mut world, ctx.entity, *id);
+        }
+        StateOccupancy::<Super>::exit(&mut world, &active);
+        #[cfg(feature = "async")]
+        crate::wait::StateWaiters::<Super>::cancel(&mut world, ctx.entity);
+        let registry = world.resource::<SuperstateRegistry<Super>>();
+        let remove_info = registry.remove_info;
+        let dynamic = registry.dynamic_state_ids().to_vec();
+        let observed = SuperstateEntered::<Super>::observed(&world);
+        let tick = world.read_change_tick();
         let (mut entities, mut cmd) = world.entities_and_commands();
-        let mut entity = entities.get_mut(ctx.entity).unwrap();
-        let mut info = entity.get_mut::<SuperstateInfo<Super>>().unwrap();
-        cmd.entity(ctx.entity).remove::<States>();
-        info.states_on_entity.clear();
+        let Ok(mut entity) = entities.get_mut(ctx.entity) else {
+            return;
+        };
+        let Some(mut info) = entity.get_mut::<SuperstateInfo<Super>>() else {
+            warn_missing_info::<Super>(ctx.entity);
+            return;
+        };
+        let entered = info.entered;
+        // Reset all bookkeeping, so the entity can be reused
+        // as if the info was just inserted.
+        *info = SuperstateInfo::default();
+        info.exited = active.last().copied();
+        info.last_exited = info.exited.map(|id| (id, tick));
+        queue_if_exists(&mut cmd, ctx.entity, move |entity| {
+            entity.remove::<States>().remove_by_ids(&dynamic).remove::<(
+                CurrentState<Super>,
+                StateLabel<Super>,
+                StateLock<Super>,
+            )>();
+            // Removed after `States`, so their hooks still find the info.
+            if remove_info {
+                entity.remove::<SuperstateInfo<Super>>();
+            }
+        });
+        // Triggered after the states are removed, as the machine is left as a whole.
+        if entered && observed {
+            SuperstateExited::<Super>::trigger(&mut cmd, ctx.entity);
+        }
+    }
+
+    /// Calls exit handlers of the state with `id`, triggers [`crate::StateExited`]
+    /// and records the exit for despawning of [`crate::StateScoped`] children,
+    /// if it is active on `entity`.
+    fn exit_state<Super: Component>(world: &mut DeferredWorld, entity: Entity, id: ComponentId) {
+        let handlers = StateExitHandlers::<Super>::get(world, id);
+        let trigger = StateExitedTriggers::<Super>::get(world, id);
+        #[cfg(feature = "bevy_app")]
+        let scoped = world.contains_resource::<crate::scoped::StateScopedExits<Super>>();
+        #[cfg(not(feature = "bevy_app"))]
+        let scoped = false;
+        if (handlers.is_empty() && trigger.is_none() && !scoped)
+            || !world
+                .get::<SuperstateInfo<Super>>(entity)
+                .is_some_and(|info| info.states_on_entity.contains(&id))
+        {
+            return;
+        }
+        for handler in handlers {
+            handler(world, entity);
+        }
+        if let Some(trigger) = trigger {
+            trigger(&mut world.commands(), entity);
+        }
+        #[cfg(feature = "bevy_app")]
+        if let Some(mut exits) = world.get_resource_mut::<crate::scoped::StateScopedExits<Super>>()
+        {
+            exits.push(entity, id);
+        }
+    }
+
+    /// Queues `f` for `entity`, if it still exists when the command is applied.
+    /// Unlike `try_*` methods of `EntityCommands`, does not create an error
+    /// for a despawned entity, which is expensive when hooks run on despawn.
+    fn queue_if_exists(
+        cmd: &mut Commands,
+        entity: Entity,
+        f: impl FnOnce(&mut EntityWorldMut) + Send + 'static,
+    ) {
+        cmd.queue(move |world: &mut World| {
+            if let Ok(mut entity) = world.get_entity_mut(entity) {
+                f(&mut entity);
+            }
+        });
     }
 }
 
-/// Just call [`register_hooks`].
+/// Just call [`register_hooks`]. Use [`SuperstatePlugin`] or [`SuperstateBuilder`] for other options.
+///
+/// With `diagnostic` feature also inserts `SuperstateDiagnostics`.
 ///
 /// -`Super` - superstate component type.
 ///
 /// -`States` - bundle with all concrete states component types.
 ///
-pub fn superstate_plugin<Super: Component, States: Bundle>(app: &mut App) {
-    register_hooks::<Super, States>(app.world_mut()).unwrap();
+#[cfg(feature = "bevy_app")]
+pub fn superstate_plugin<Super: Component, States: StateSet>(app: &mut App) {
+    SuperstateBuilder::<Super, States>::new()
+        .build(app)
+        .unwrap();
+}
+
+/// Plugin of several superstates at once, expands to [`superstate_plugin`]
+/// call for every `(Super, States)` pair.
+///
+/// ```
+/// use superstate::{superstate_plugins, SuperstateInfo};
+/// use bevy_app::App;
+/// use bevy_ecs::component::Component;
+///
+/// #[derive(Default, Component)]
+/// #[require(SuperstateInfo<Movement>)]
+/// struct Movement;
+///
+/// #[derive(Component)]
+/// #[require(Movement)]
+/// struct Walking;
+///
+/// #[derive(Component)]
+/// #[require(Movement)]
+/// struct Running;
+///
+/// #[derive(Default, Component)]
+/// #[require(SuperstateInfo<Health>)]
+/// struct Health;
+///
+/// #[derive(Component)]
+/// #[require(Health)]
+/// struct Alive;
+///
+/// #[derive(Component)]
+/// #[require(Health)]
+/// struct Dead;
+///
+/// App::new().add_plugins(superstate_plugins!(
+///     (Movement, (Walking, Running)),
+///     (Health, (Alive, Dead)),
+/// ));
+/// ```
+#[cfg(feature = "bevy_app")]
+#[macro_export]
+macro_rules! superstate_plugins {
+    ($(($super:ty, $states:ty)),+ $(,)?) => {
+        |app: &mut $crate::__macro_export::App| {
+            $($crate::superstate_plugin::<$super, $states>(app);)+
+        }
+    };
+}
+
+/// Declares a superstate with all its states from an enum-like definition.
+/// The enum becomes the `Super` unit struct, and every variant becomes
+/// a state struct with public fields, with all `require` attributes in place.
+///
+/// Attributes of the enum are applied to `Super`, and attributes of each variant
+/// are applied to its state. Generates `Super::register` calling [`register_hooks`],
+/// and `Super::plugin` calling [`superstate_plugin`] with `bevy_app` feature.
+///
+/// ```
+/// use bevy_ecs::world::World;
+/// use superstate::superstate;
+///
+/// superstate! {
+///     pub enum Movement {
+///         #[derive(Default)]
+///         Walking(u32),
+///         Running { speed: f32 },
+///         Flying,
+///     }
+/// }
+///
+/// let mut world = World::new();
+/// Movement::register(&mut world).unwrap();
+/// let e = world.spawn(Walking(1)).id();
+/// world.entity_mut(e).insert(Running { speed: 2.0 });
+/// assert!(!world.entity(e).contains::<Walking>());
+/// ```
+#[macro_export]
+macro_rules! superstate {
+    (
+        $(#[$meta:meta])*
+        $vis:vis enum $super:ident {
+            $(
+                $(#[$state_meta:meta])*
+                $state:ident $(($($tuple:tt)*))? $({$($named:tt)*})?
+            ),+ $(,)?
+        }
+    ) => {
+        $(#[$meta])*
+        #[derive(Default, $crate::__macro_export::Component)]
+        #[require($crate::SuperstateInfo<$super>)]
+        $vis struct $super;
+
+        $(
+            $crate::superstate!(
+                @state
+                $(#[$state_meta])*
+                #[derive($crate::__macro_export::Component)]
+                #[require($super)]
+                $vis $state $(($($tuple)*))? $({$($named)*})?
+            );
+        )+
+
+        impl $super {
+            /// Registers hooks of the superstate, see `superstate::register_hooks`.
+            #[allow(dead_code)]
+            $vis fn register(
+                world: &mut $crate::__macro_export::World,
+            ) -> Result<(), $crate::__macro_export::BevyError> {
+                $crate::register_hooks::<$super, ($($state,)+)>(world)
+            }
+
+            $crate::__superstate_plugin!($vis, $super, ($($state,)+));
+        }
+    };
+    (@state $(#[$meta:meta])* $vis:vis $state:ident) => {
+        $(#[$meta])*
+        $vis struct $state;
+    };
+    (@state $(#[$meta:meta])* $vis:vis $state:ident ($($ty:ty),* $(,)?)) => {
+        $(#[$meta])*
+        $vis struct $state($(pub $ty),*);
+    };
+    (@state $(#[$meta:meta])* $vis:vis $state:ident {$($field:ident: $ty:ty),* $(,)?}) => {
+        $(#[$meta])*
+        $vis struct $state {
+            $(pub $field: $ty),*
+        }
+    };
+}
+
+#[cfg(feature = "bevy_app")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __superstate_plugin {
+    ($vis:vis, $super:ident, $states:ty) => {
+        /// Plugin of the superstate, see `superstate::superstate_plugin`.
+        #[allow(dead_code)]
+        $vis fn plugin(app: &mut $crate::__macro_export::App) {
+            $crate::superstate_plugin::<$super, $states>(app);
+        }
+    };
+}
+
+#[cfg(not(feature = "bevy_app"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __superstate_plugin {
+    ($vis:vis, $super:ident, $states:ty) => {};
+}
+
+#[doc(hidden)]
+pub mod __macro_export {
+    #[cfg(feature = "bevy_app")]
+    pub use bevy_app::App;
+    pub use bevy_ecs::{component::Component, error::BevyError, world::World};
 }
 
 /// Called when building a plugin to register component hooks.
-/// Use this function if you are not using the [`App`] and only work with the [`World`].
+/// Use this function if you are not using the `App` and only work with the [`World`],
+/// e.g. with only `bevy_ecs` and default features disabled.
 ///
 /// Registers on_add: [`hooks::on_add_hook_state`], and
 /// on_remove: [`hooks::on_remove_hook_state`] component hooks
@@ -187,51 +1226,592 @@ pub fn superstate_plugin<Super: Component, States: Bundle>(app: &mut App) {
 /// for superstate component.
 ///
 /// More details about each hook can be found in the [`hooks`] module.
-pub fn register_hooks<Super: Component, States: Bundle>(
+///
+/// A component can be a state of one superstate and a superstate
+/// of other states at the same time, e.g. `Combat` state of `Mode`
+/// with `Melee` and `Ranged` states. Its hooks run in order of registration.
+/// When the parent state is exited, it is removed from the parent superstate first,
+/// and then its own states are removed with the next command flush.
+///
+/// Returns [`hooks::MissingRequireError`] if a state does not require `Super`,
+/// or `Super` does not require [`SuperstateInfo<Super>`].
+/// Returns [`hooks::StateCollisionError`] if `Super` is one of `States`,
+/// or a state is listed twice.
+///
+/// Calling it again for the same `Super` and `States` does nothing and returns `Ok`,
+/// so independent plugins can register the superstate they depend on.
+///
+/// Ids of states are derived from the component ids of `States`, which registers
+/// only the components: `States` is not registered as a bundle,
+/// so [`World::bundles`] is left untouched. The ids are kept in
+/// [`SuperstateRegistry::state_ids`] for tools that need them.
+pub fn register_hooks<Super: Component, States: StateSet>(
     world: &mut World,
+) -> Result<(), BevyError> {
+    SuperstateBuilder::<Super, States>::new().build_world(world)
+}
+
+/// Same as [`register_hooks`], but inserting `Super` on entity without states
+/// puts it into `Initial` state instead of removing `Super`.
+/// For example, `commands.entity(e).insert(Movement)` will land in `Walking`.
+///
+/// Use [`SuperstateBuilder::default_state_with`] for an initial state with data.
+///
+/// `Super` does not need to implement `Default`: only `Initial` is constructed here,
+/// while `Super` is constructed by `require` of states,
+/// e.g. `#[require(Movement = Movement::new())]`.
+///
+/// Returns [`hooks::UnknownStateError`] if `Initial` is not one of `States`.
+pub fn register_hooks_with_default<
+    Super: Component,
+    States: StateSet,
+    Initial: Component + Default,
+>(
+    world: &mut World,
+) -> Result<(), BevyError> {
+    SuperstateBuilder::<Super, States>::new()
+        .default_state::<Initial>()
+        .build_world(world)
+}
+
+/// Same as [`register_hooks`], but with `policy` to choose which state remains
+/// when several states are added to an entity at once.
+/// [`register_hooks`] uses [`ConflictPolicy::KeepLast`].
+///
+/// For [`ConflictPolicy::KeepHighestPriority`] insert [`StatePriorities`] resource.
+pub fn register_hooks_with_policy<Super: Component, States: StateSet>(
+    world: &mut World,
+    policy: ConflictPolicy,
+) -> Result<(), BevyError> {
+    SuperstateBuilder::<Super, States>::new()
+        .conflict_policy(policy)
+        .build_world(world)
+}
+
+/// Same as [`register_hooks`], but logs every transition of `Super`
+/// as `"{Super}: {from} -> {to}"` at `level`, e.g. to debug a complex machine.
+/// Transitions are not logged by default.
+pub fn register_hooks_with_logging<Super: Component, States: StateSet>(
+    world: &mut World,
+    level: Level,
+) -> Result<(), BevyError> {
+    SuperstateBuilder::<Super, States>::new()
+        .log_transitions(level)
+        .build_world(world)
+}
+
+/// Same as [`register_hooks`], but with `guard` deciding whether an entity
+/// may change the state of `Super` at the moment, for example
+/// "only to `Flying` if `Stamina > 0`". Arguments of `guard` are the entity,
+/// the id of the current state and the id of the new one.
+///
+/// The guard is stored in [`TransitionGuard`] resource, see it for
+/// the constraints of running inside a hook.
+pub fn register_hooks_with_guard<Super: Component, States: StateSet>(
+    world: &mut World,
+    guard: impl Fn(&DeferredWorld, Entity, ComponentId, ComponentId) -> bool + Send + Sync + 'static,
+) -> Result<(), BevyError> {
+    SuperstateBuilder::<Super, States>::new()
+        .guard(guard)
+        .build_world(world)
+}
+
+/// Same as [`register_hooks`], but removing the last state puts the entity
+/// into `Fallback` state instead of removing `Super`,
+/// for machines that must always be in some state.
+///
+/// `Fallback` is inserted by [`hooks::on_remove_hook_state`] with commands,
+/// so its own hooks run after and never recurse into removal.
+/// Removing `Super` itself or despawning the entity does not insert `Fallback`.
+///
+/// Like in [`register_hooks_with_default`], `Super` does not need to implement `Default`,
+/// it stays on the entity and only `Fallback` is constructed.
+///
+/// Returns [`hooks::UnknownStateError`] if `Fallback` is not one of `States`.
+pub fn register_hooks_with_fallback<
+    Super: Component,
+    States: StateSet,
+    Fallback: Component + Default,
+>(
+    world: &mut World,
+) -> Result<(), BevyError> {
+    SuperstateBuilder::<Super, States>::new()
+        .fallback_state::<Fallback>()
+        .build_world(world)
+}
+
+/// Same as [`register_hooks`], but `Super` is not removed
+/// when the last state is removed, and can be inserted without states.
+/// Entities with `Super` and without states are "between states",
+/// e.g. systems querying `With<Super>` can represent a transient "deciding" phase.
+///
+/// Use [`SuperstateCommands::clear_state`] to remove all states and keep `Super`.
+pub fn register_hooks_allowing_empty<Super: Component, States: StateSet>(
+    world: &mut World,
+) -> Result<(), BevyError> {
+    SuperstateBuilder::<Super, States>::new()
+        .allow_empty()
+        .build_world(world)
+}
+
+/// Same as [`register_hooks`], but states of `Super` are parallel regions:
+/// adding a state does not remove the others, so several of them
+/// can be active at once, e.g. `Moving` and `Shooting` of `Combat`.
+///
+/// In the default exclusive mode an entity has at most one state,
+/// and [`SuperstateInfo::active_states`] holds only it.
+/// Here it holds all active states in order of entering,
+/// and [`SuperstateInfo::current_state`] is the last entered one.
+/// Entering a region is not a transition from another one, so [`ConflictPolicy`],
+/// [`TransitionTable`], [`TransitionGuard`], [`StateLock`] and minimum dwell time
+/// are not checked. `Super` is removed with the last active state,
+/// and removing `Super` removes all of them.
+pub fn register_parallel_hooks<Super: Component, States: StateSet>(
+    world: &mut World,
+) -> Result<(), BevyError> {
+    SuperstateBuilder::<Super, States>::new()
+        .parallel()
+        .build_world(world)
+}
+
+/// Same as [`register_hooks`], but with `order` of superstate hooks
+/// relative to hooks already registered for the same components,
+/// e.g. by other superstates or by other crates.
+///
+/// Hooks registered not by this crate cannot be wrapped, since a component
+/// can only have one hook. In this case superstate hooks run as
+/// [`OnAdd`](bevy_ecs::world::OnAdd) and [`OnRemove`](bevy_ecs::world::OnRemove)
+/// observers, which always run after the hooks, regardless of `order`.
+pub fn register_hooks_with_order<Super: Component, States: StateSet>(
+    world: &mut World,
+    order: HookOrder,
+) -> Result<(), BevyError> {
+    SuperstateBuilder::<Super, States>::new()
+        .hook_order(order)
+        .build_world(world)
+}
+
+fn register_hooks_inner<Super: Component, States: StateSet>(
+    world: &mut World,
+    on_add_superstate: ComponentHook,
+    order: HookOrder,
 ) -> Result<(), BevyError> {
     let super_id = world.register_component::<Super>();
-    let states_ids = world
-        .register_bundle::<States>()
-        .iter_explicit_components()
-        .collect::<Vec<_>>();
-    for id in states_ids {
+    let info_id = world.register_component::<SuperstateInfo<Super>>();
+    let states_ids = states_ids::<States>(world).into_boxed_slice();
+    check_collisions(world, super_id, &states_ids)?;
+    check_required::<SuperstateInfo<Super>>(world, super_id, info_id)?;
+    for &id in &states_ids {
+        check_required::<Super>(world, id, super_id)?;
+    }
+    // Hooks disabled by `deregister_hooks` are still installed.
+    if !SuperstateHooks::<Super, States>::installed(world) {
+        for &id in &states_ids {
+            chain_hooks(
+                world,
+                id,
+                hooks::on_add_hook_state::<Super, States>,
+                hooks::on_remove_hook_state::<Super, States>,
+                order,
+            )
+            .map_err(|kind| RegisterError::StateHookBusy { id, kind })?;
+        }
+        chain_hooks(
+            world,
+            super_id,
+            on_add_superstate,
+            hooks::on_remove_superstate::<Super, States>,
+            order,
+        )
+        .map_err(|kind| RegisterError::SuperHookBusy { id: super_id, kind })?;
+    }
+    world.insert_resource(SuperstateRegistry::<Super>::new(super_id, states_ids));
+    world.init_resource::<SuperstateDeclared<Super>>();
+    Superstates::insert::<Super>(world);
+    world.insert_resource(SuperstateHooks::<Super, States> {
+        enabled: true,
+        _p: PhantomData,
+    });
+    Ok(())
+}
+
+/// Appends the state with `id` to already registered superstate `Super`,
+/// for data-driven machines whose states are not known at compile time,
+/// e.g. components of mods discovered at runtime.
+/// Registering the same state again does nothing.
+///
+/// The state cannot require `Super`, so if it is inserted into an entity
+/// without `Super`, its hook inserts `Super::default()` as `require` would do.
+/// Dynamic states are removed with `Super`, and their hooks
+/// are disabled by [`deregister_hooks`] with empty `States`: `deregister_hooks::<Super, ()>`.
+///
+/// Returns [`hooks::NotRegisteredError`] if `Super` is not registered,
+/// or [`hooks::RegisterError`] if there is no component with `id`.
+pub fn register_state<Super: Component + Default>(
+    world: &mut World,
+    id: ComponentId,
+) -> Result<(), BevyError> {
+    let registry = world
+        .get_resource::<SuperstateRegistry<Super>>()
+        .ok_or(NotRegisteredError)?;
+    if registry.state_ids().contains(&id) {
+        return Ok(());
+    }
+    // Hooks of a registration disabled by `deregister_hooks` are still chained.
+    if !is_chained(world, id, hooks::on_add_hook_state::<Super, ()>) {
+        chain_hooks(
+            world,
+            id,
+            hooks::on_add_hook_state::<Super, ()>,
+            hooks::on_remove_hook_state::<Super, ()>,
+            HookOrder::default(),
+        )
+        .map_err(|kind| RegisterError::StateHookBusy { id, kind })?;
+    }
+    let mut registry = world.resource_mut::<SuperstateRegistry<Super>>();
+    registry.push_state(id);
+    registry.insert_super = Some(|entity| {
+        entity.insert(Super::default());
+    });
+    world.insert_resource(SuperstateHooks::<Super, ()> {
+        enabled: true,
+        _p: PhantomData,
+    });
+    Ok(())
+}
+
+/// Ids of `States` components in bundle order.
+/// Unlike registering the bundle, does not panic on duplicates.
+pub(crate) fn states_ids<States: Bundle>(world: &mut World) -> Vec<ComponentId> {
+    let mut ids = Vec::new();
+    States::component_ids(&mut world.components_registrator(), &mut |id| ids.push(id));
+    ids
+}
+
+/// Returns [`hooks::StateCollisionError`] if `super_id` is one of `states_ids`,
+/// or `states_ids` contain duplicates.
+fn check_collisions(
+    world: &World,
+    super_id: ComponentId,
+    states_ids: &[ComponentId],
+) -> Result<(), StateCollisionError> {
+    let name = |id| {
         world
-            .register_component_hooks_by_id(id)
-            .ok_or(HookBusyError(id))?
-            .on_add(hooks::on_add_hook_state::<Super, States>)
-            .on_remove(hooks::on_remove_hook_state::<Super, States>);
-    }
-    world
-        .register_component_hooks_by_id(super_id)
-        .ok_or(HookBusyError(super_id))?
-        .on_add(hooks::on_add_superstate::<Super, States>)
-        .on_remove(hooks::on_remove_superstate::<Super, States>);
+            .components()
+            .get_name(id)
+            .unwrap_or_default()
+            .into_owned()
+    };
+    for (i, &id) in states_ids.iter().enumerate() {
+        if id == super_id {
+            return Err(StateCollisionError::SuperIsState { id, name: name(id) });
+        }
+        if states_ids[..i].contains(&id) {
+            return Err(StateCollisionError::DuplicateState { id, name: name(id) });
+        }
+    }
     Ok(())
 }
 
+/// Returns [`hooks::MissingRequireError`] if component with `id` does not require `R`.
+fn check_required<R: Component>(
+    world: &World,
+    id: ComponentId,
+    required: ComponentId,
+) -> Result<(), MissingRequireError> {
+    let info = world.components().get_info(id);
+    if info.is_some_and(|info| info.required_components().iter_ids().any(|r| r == required)) {
+        return Ok(());
+    }
+    Err(MissingRequireError {
+        id,
+        name: info.map(|info| info.name().to_owned()).unwrap_or_default(),
+        required: type_name::<R>(),
+    })
+}
+
+/// Inverse of [`register_hooks`]. Use it to tear down a superstate,
+/// for example when unloading a plugin or to get a clean world in tests.
+///
+/// Bevy does not allow to unset or replace component hooks,
+/// so the hooks stay installed, but become no-ops until
+/// [`register_hooks`] is called again for the same `Super` and `States`.
+/// Hooks of dynamic states of [`register_state`] are disabled too.
+/// Components already on entities are not touched.
+///
+/// Resources of `Super` are removed: [`SuperstateRegistry`], [`TransitionTable`],
+/// [`TransitionGuard`], [`StatePriorities`], [`TransitionLog`] and [`StateOccupancy`],
+/// so registering again, e.g. by a reloaded mod, builds the machine
+/// from the new options. Registered transfers, exit handlers, state constructors
+/// and observers of entered and exited states are kept.
+///
+/// Returns [`hooks::NotRegisteredError`] if hooks were never registered.
+pub fn deregister_hooks<Super: Component, States: StateSet>(
+    world: &mut World,
+) -> Result<(), BevyError> {
+    let mut hooks = world
+        .get_resource_mut::<SuperstateHooks<Super, States>>()
+        .ok_or(NotRegisteredError)?;
+    hooks.enabled = false;
+    if let Some(mut hooks) = world.get_resource_mut::<SuperstateHooks<Super, ()>>() {
+        hooks.enabled = false;
+    }
+    world.remove_resource::<SuperstateRegistry<Super>>();
+    world.remove_resource::<SuperstateDeclared<Super>>();
+    world.remove_resource::<TransitionTable<Super>>();
+    world.remove_resource::<TransitionGuard<Super>>();
+    world.remove_resource::<StatePriorities<Super>>();
+    world.remove_resource::<TransitionLog<Super>>();
+    world.remove_resource::<StateOccupancy<Super>>();
+    Superstates::remove::<Super>(world);
+    Ok(())
+}
+
+/// Marks that `Super` is registered, or will be by [`lazy_superstate_plugin`],
+/// checked by [`hooks::on_add_info`].
+#[derive(Resource)]
+pub(crate) struct SuperstateDeclared<Super: Component>(PhantomData<Super>);
+
+impl<Super: Component> Default for SuperstateDeclared<Super> {
+    fn default() -> Self {
+        Self(PhantomData)
+    }
+}
+
+/// Marks that hooks of `Super` and `States` are installed in the world.
+/// Hooks do nothing while `enabled` is `false`.
+#[derive(Resource)]
+pub(crate) struct SuperstateHooks<Super: Component, States: StateSet> {
+    enabled: bool,
+    _p: PhantomData<(Super, States)>,
+}
+
+impl<Super: Component, States: StateSet> SuperstateHooks<Super, States> {
+    pub(crate) fn enabled(world: &World) -> bool {
+        world
+            .get_resource::<Self>()
+            .is_some_and(|hooks| hooks.enabled)
+    }
+
+    /// Returns `true` if the hooks are installed, even if disabled.
+    pub(crate) fn installed(world: &World) -> bool {
+        world.contains_resource::<Self>()
+    }
+}
+
 /// A component for storing auxiliary information to ensure
 /// that only one state exists at a time. Used in component hooks.
 /// Type `S` is a superstate component type.
 ///
+/// Ids of all possible states are shared by entities
+/// in [`SuperstateRegistry`] resource.
+///
 /// The component initialization requires dynamic memory allocations,
-/// and is never deleted once created, even if the entity
-/// has no state components left. You can safely delete this component
+/// and by default is never deleted once created, even if the entity
+/// has no state components left, unless enabled with
+/// [`SuperstateBuilder::remove_info`]. You can safely delete this component
 /// if you verify that the entity does not have a superstate.
 /// It is recommended to delete this component if your entity
 /// will no longer accept previously registered states.
-#[derive(Component, Default, Debug, Clone)]
+///
+/// With `reflect` feature only states on entity and the previous state are reflected,
+/// editor tools should treat them as read-only.
+///
+/// State ids are valid only in the world of the entity, see [`resolve_state_id`].
+#[derive(Component, Debug, Clone)]
+#[component(on_add = hooks::on_add_info::<S>)]
+#[cfg_attr(
+    feature = "reflect",
+    derive(Reflect),
+    reflect(Component, Default, from_reflect = false, type_path = false)
+)]
 pub struct SuperstateInfo<S: Component> {
-    state_ids: Box<[ComponentId]>,
     // Vector of states which on entity on one momemet.
     // Can be more than 1, when user spawn entity with
-    // several different states.
-    states_on_entity: Vec<ComponentId>,
+    // several different states. Usually there is exactly one,
+    // so it is stored inline.
+    states_on_entity: SmallVec<[ComponentId; 1]>,
+    // State that was active before the current one.
+    prev_state: Option<ComponentId>,
+    // State that left `states_on_entity` last, cleared when a state is entered.
+    exited: Option<ComponentId>,
+    // State that left `states_on_entity` last and the change tick of the exit,
+    // not cleared when a state is entered.
+    #[cfg_attr(feature = "reflect", reflect(ignore))]
+    last_exited: Option<(ComponentId, Tick)>,
+    // State entered last and the change tick of the entry,
+    // not changed by other regions leaving or re-added states.
+    #[cfg_attr(feature = "reflect", reflect(ignore))]
+    last_entered: Option<(ComponentId, Tick)>,
+    // `Time::elapsed` when the active state was added.
+    #[cfg_attr(not(feature = "bevy_time"), allow(dead_code))]
+    state_since: Option<Duration>,
+    // States left by `push_state`, the top is the last.
+    stack: Vec<ComponentId>,
+    // States added together, whose hooks have already run, in the order they are added,
+    // cleared by a command queued by the first of them.
+    #[cfg_attr(feature = "reflect", reflect(ignore))]
+    batch: Vec<ComponentId>,
+    // State inserted by `force_state`, whose hook skips the transition checks.
+    #[cfg_attr(feature = "reflect", reflect(ignore))]
+    forced: Option<ComponentId>,
+    // `Super` is kept on the entity, so its removal exits the machine.
+    #[cfg_attr(feature = "reflect", reflect(ignore))]
+    entered: bool,
+    // Type path of the active state. After deserialization
+    // `states_on_entity` is empty and it is resolved in the next hook.
+    #[cfg(feature = "serde")]
+    #[cfg_attr(feature = "reflect", reflect(ignore))]
+    state_name: Option<String>,
+    #[cfg_attr(feature = "reflect", reflect(ignore))]
     _p: PhantomData<S>,
 }
 
+/// Lightweight copy of the active and the previous state of [`SuperstateInfo`],
+/// returned by [`SuperstateInfo::snapshot`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct StateSnapshot {
+    /// [`SuperstateInfo::current_state`].
+    pub active: Option<ComponentId>,
+    /// [`SuperstateInfo::previous_state`].
+    pub previous: Option<ComponentId>,
+}
+
+impl<S: Component> Default for SuperstateInfo<S> {
+    fn default() -> Self {
+        Self {
+            states_on_entity: SmallVec::new(),
+            prev_state: None,
+            exited: None,
+            last_exited: None,
+            last_entered: None,
+            state_since: None,
+            stack: Vec::new(),
+            batch: Vec::new(),
+            forced: None,
+            entered: false,
+            #[cfg(feature = "serde")]
+            state_name: None,
+            _p: PhantomData,
+        }
+    }
+}
+
 impl<S: Component> SuperstateInfo<S> {
-    fn remove_by_id(&mut self, id: ComponentId) {
+    /// Returns the [`ComponentId`] of the active state,
+    /// or `None` if the entity has no superstate.
+    ///
+    /// The result is only meaningful after the hooks have flushed their commands.
+    /// While several states are being added at once, this is the last added one.
+    pub fn current_state(&self) -> Option<ComponentId> {
+        self.states_on_entity.last().copied()
+    }
+
+    /// Returns the [`ComponentId`]s of all active states in order of entering.
+    /// Holds several states only with [`crate::register_parallel_hooks`],
+    /// otherwise at most [`SuperstateInfo::current_state`].
+    pub fn active_states(&self) -> &[ComponentId] {
+        &self.states_on_entity
+    }
+
+    /// Returns the type name of the active state component, e.g. for logs,
+    /// or `None` if the entity has no superstate or the component is not registered.
+    pub fn current_state_name<'a>(&self, components: &'a Components) -> Option<&'a str> {
+        self.current_state()
+            .and_then(|id| components.get_info(id))
+            .map(|info| info.name())
+    }
+
+    /// Returns the [`ComponentId`] of the state that was active before the current one,
+    /// or `None` if there was no transition since the superstate was added.
+    pub fn previous_state(&self) -> Option<ComponentId> {
+        self.prev_state
+    }
+
+    /// Returns the [`ComponentId`] of the state the entity exited last, by a transition,
+    /// by removal of the state or of the superstate, or `None` if it entered a state since.
+    /// Unlike [`SuperstateInfo::previous_state`], it is recorded also when no other
+    /// state is entered, e.g. with [`crate::register_hooks_allowing_empty`].
+    pub fn exited_state(&self) -> Option<ComponentId> {
+        self.exited
+    }
+
+    /// Returns `true` if the entity entered the state with `id` after `last_run`,
+    /// e.g. the last run of a system, also with parallel regions
+    /// whose other changes do not enter it again.
+    pub fn entered_since(&self, id: ComponentId, last_run: Tick, this_run: Tick) -> bool {
+        self.last_entered
+            .is_some_and(|(entered, tick)| entered == id && tick.is_newer_than(last_run, this_run))
+    }
+
+    /// Returns `true` if the entity exited the state with `id` after `last_run`,
+    /// e.g. the last run of a system, even if it entered another state since.
+    /// See [`SuperstateInfo::exited_state`].
+    pub fn exited_since(&self, id: ComponentId, last_run: Tick, this_run: Tick) -> bool {
+        self.last_exited
+            .is_some_and(|(exited, tick)| exited == id && tick.is_newer_than(last_run, this_run))
+    }
+
+    /// Returns the active state as a variant of `E`, e.g. to `match` on it,
+    /// or `None` if there is no active state or it is not mapped by `E`.
+    /// See [`StateEnum`].
+    pub fn current_as<E: StateEnum<S>>(&self, registry: &SuperstateRegistry<S>) -> Option<E> {
+        registry.state_as(self.current_state()?)
+    }
+
+    /// Returns the active and the previous state without the rest of the info,
+    /// e.g. to compare states before and after a system or send them over a channel.
+    pub fn snapshot(&self) -> StateSnapshot {
+        StateSnapshot {
+            active: self.current_state(),
+            previous: self.previous_state(),
+        }
+    }
+
+    /// Returns the history stack of states left by
+    /// [`SuperstateCommands::push_state`], the top is the last.
+    pub fn stack(&self) -> &[ComponentId] {
+        &self.stack
+    }
+
+    /// Pushes `id` on top of the stack,
+    /// and drops the bottom states if the stack is deeper than `depth`.
+    pub(crate) fn push_stack(&mut self, id: ComponentId, depth: usize) {
+        self.stack.push(id);
+        if self.stack.len() > depth {
+            self.stack.drain(..self.stack.len() - depth);
+        }
+    }
+
+    pub(crate) fn pop_stack(&mut self) -> Option<ComponentId> {
+        self.stack.pop()
+    }
+
+    /// Returns how long the entity has been in the active state.
+    ///
+    /// The start of the state is taken from the [`Time`] resource
+    /// when the state is added, so it is zero without the resource.
+    /// Requires `bevy_time` feature.
+    #[cfg(feature = "bevy_time")]
+    pub fn time_in_state(&self, time: &Time) -> Duration {
+        self.state_since
+            .map(|since| time.elapsed().saturating_sub(since))
+            .unwrap_or_default()
+    }
+
+    /// Returns `true` if `State` is among the states on the entity.
+    /// Returns `false` if `State` was never registered as a component.
+    ///
+    /// Ids are resolved by `components` of the world of the entity,
+    /// see [`resolve_state_id`].
+    pub fn is_in_state<State: Component>(&self, components: &Components) -> bool {
+        components
+            .component_id::<State>()
+            .is_some_and(|id| self.states_on_entity.contains(&id))
+    }
+
+    /// Returns `true` if the state was on entity.
+    fn remove_by_id(&mut self, id: ComponentId) -> bool {
         // Find item`s index with equal ComponentId.
         if let Some((index, _)) = self
             .states_on_entity
@@ -240,7 +1820,10 @@ impl<S: Component> SuperstateInfo<S> {
             .find(|(_, _id)| **_id == id)
         {
             // Never panic, because index never out of bounds.
-            self.states_on_entity.swap_remove(index);
+            // Order is kept for parallel states.
+            self.states_on_entity.remove(index);
+            return true;
         }
+        false
     }
 }
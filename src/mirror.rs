@@ -0,0 +1,123 @@
+//! Mirroring the active state of one entity into a global
+//! [`bevy_state`] [`State`] resource. Requires `bevy_state` feature.
+
+use std::{collections::HashMap, marker::PhantomData};
+
+use bevy_ecs::{
+    change_detection::DetectChanges,
+    component::{Component, ComponentId},
+    entity::Entity,
+    resource::Resource,
+    system::{Query, Res, ResMut},
+    world::{Ref, World},
+};
+use bevy_state::state::{FreelyMutableState, NextState, State};
+
+use crate::SuperstateInfo;
+
+/// Resource mapping states of `Super` to variants of global state `T`.
+///
+/// When the active state of [`StateMirror::entity`] changes to a mapped state,
+/// [`mirror_state`] queues the variant to [`NextState<T>`].
+/// Unmapped states and removal of the superstate leave `T` as is.
+#[derive(Resource, Debug, Clone)]
+pub struct StateMirror<Super: Component, T: FreelyMutableState> {
+    entity: Option<Entity>,
+    variants: HashMap<ComponentId, T>,
+    _p: PhantomData<Super>,
+}
+
+impl<Super: Component, T: FreelyMutableState> Default for StateMirror<Super, T> {
+    fn default() -> Self {
+        Self {
+            entity: None,
+            variants: HashMap::new(),
+            _p: PhantomData,
+        }
+    }
+}
+
+impl<Super: Component, T: FreelyMutableState> StateMirror<Super, T> {
+    /// Creates a [`StateMirrorBuilder`] which registers state components in the `world`.
+    pub fn builder(world: &mut World) -> StateMirrorBuilder<'_, Super, T> {
+        StateMirrorBuilder {
+            world,
+            mirror: Self::default(),
+        }
+    }
+
+    /// The entity whose state is mirrored.
+    pub fn entity(&self) -> Option<Entity> {
+        self.entity
+    }
+
+    /// Set the entity whose state is mirrored, `None` to stop mirroring.
+    pub fn set_entity(&mut self, entity: Option<Entity>) -> &mut Self {
+        self.entity = entity;
+        self
+    }
+
+    /// Map state with `id` to `variant`.
+    pub fn map_by_id(&mut self, id: ComponentId, variant: T) -> &mut Self {
+        self.variants.insert(id, variant);
+        self
+    }
+
+    /// Returns the variant mapped to state with `id`.
+    pub fn get(&self, id: ComponentId) -> Option<&T> {
+        self.variants.get(&id)
+    }
+}
+
+/// Builder of [`StateMirror`].
+pub struct StateMirrorBuilder<'w, Super: Component, T: FreelyMutableState> {
+    world: &'w mut World,
+    mirror: StateMirror<Super, T>,
+}
+
+impl<Super: Component, T: FreelyMutableState> StateMirrorBuilder<'_, Super, T> {
+    /// Map `State` to `variant`.
+    pub fn map<State: Component>(mut self, variant: T) -> Self {
+        let id = self.world.register_component::<State>();
+        self.mirror.map_by_id(id, variant);
+        self
+    }
+
+    /// Set the entity whose state is mirrored.
+    pub fn entity(mut self, entity: Entity) -> Self {
+        self.mirror.set_entity(Some(entity));
+        self
+    }
+
+    /// Returns the built mirror. Insert it into the world as a resource.
+    pub fn build(self) -> StateMirror<Super, T> {
+        self.mirror
+    }
+}
+
+/// System queueing [`NextState<T>`] when the mirrored entity changes its state,
+/// or another entity is set to [`StateMirror`].
+/// Does nothing if `T` is not initialized.
+/// Added by [`crate::SuperstateAppExt::mirror_state`].
+pub fn mirror_state<Super: Component, T: FreelyMutableState>(
+    mirror: Option<Res<StateMirror<Super, T>>>,
+    infos: Query<Ref<SuperstateInfo<Super>>>,
+    state: Option<Res<State<T>>>,
+    next: Option<ResMut<NextState<T>>>,
+) {
+    let (Some(mirror), Some(mut next)) = (mirror, next) else {
+        return;
+    };
+    let Some(info) = mirror.entity.and_then(|entity| infos.get(entity).ok()) else {
+        return;
+    };
+    if !info.is_changed() && !mirror.is_changed() {
+        return;
+    }
+    let Some(variant) = info.current_state().and_then(|id| mirror.get(id)) else {
+        return;
+    };
+    if state.is_none_or(|state| state.get() != variant) {
+        next.set(variant.clone());
+    }
+}
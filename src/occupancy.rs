@@ -0,0 +1,122 @@
+//! Number of entities in each state, e.g. for balancing or debug UIs.
+
+use std::{collections::HashMap, marker::PhantomData};
+
+use bevy_ecs::{
+    component::{Component, ComponentId},
+    resource::Resource,
+    world::{DeferredWorld, FromWorld, World},
+};
+
+use crate::SuperstateInfo;
+
+/// Resource with the number of entities in each state of `Super`,
+/// maintained by the hooks on every transition instead of scanning entities.
+///
+/// Optional: counted only while the resource exists, inserted by
+/// [`crate::SuperstateBuilder::state_occupancy`] or `init_resource`,
+/// which counts the entities already in states.
+///
+/// ```
+/// # use bevy_ecs::{component::Component, world::World};
+/// # use superstate::{StateOccupancy, SuperstateBuilder, SuperstateInfo};
+/// # #[derive(Default, Component)]
+/// # #[require(SuperstateInfo<Movement>)]
+/// # struct Movement;
+/// # #[derive(Component)]
+/// # #[require(Movement)]
+/// # struct Walking;
+/// # #[derive(Component)]
+/// # #[require(Movement)]
+/// # struct Running;
+/// let mut world = World::new();
+/// SuperstateBuilder::<Movement, (Walking, Running)>::new()
+///     .state_occupancy()
+///     .build_world(&mut world)
+///     .unwrap();
+/// world.spawn(Walking);
+/// world.spawn(Walking);
+/// let walking = world.register_component::<Walking>();
+/// let occupancy = world.resource::<StateOccupancy<Movement>>();
+/// assert_eq!(occupancy.count_in(walking), 2);
+/// ```
+#[derive(Resource, Debug)]
+pub struct StateOccupancy<Super: Component> {
+    counts: HashMap<ComponentId, usize>,
+    _p: PhantomData<Super>,
+}
+
+impl<Super: Component> FromWorld for StateOccupancy<Super> {
+    fn from_world(world: &mut World) -> Self {
+        let mut counts = HashMap::new();
+        for info in world.query::<&SuperstateInfo<Super>>().iter(world) {
+            for id in info.active_states() {
+                *counts.entry(*id).or_default() += 1;
+            }
+        }
+        Self {
+            counts,
+            _p: PhantomData,
+        }
+    }
+}
+
+impl<Super: Component> StateOccupancy<Super> {
+    /// Number of entities in the state with `id`.
+    pub fn count_in(&self, id: ComponentId) -> usize {
+        self.counts.get(&id).copied().unwrap_or_default()
+    }
+
+    /// Ids of occupied states with their number of entities, in no particular order.
+    pub fn iter(&self) -> impl Iterator<Item = (ComponentId, usize)> + '_ {
+        self.counts.iter().map(|(id, count)| (*id, *count))
+    }
+
+    /// Same as [`StateOccupancy::iter`], but with names of states for debug UIs:
+    /// aliases of `state_alias` with `serde` or `bincode` feature,
+    /// otherwise type names of components.
+    pub fn named<'a>(&'a self, world: &'a World) -> impl Iterator<Item = (String, usize)> + 'a {
+        self.iter()
+            .filter_map(|(id, count)| Some((state_name(world, id)?, count)))
+    }
+
+    /// Counts entities entering states with `ids`.
+    pub(crate) fn enter(world: &mut DeferredWorld, ids: &[ComponentId]) {
+        if ids.is_empty() {
+            return;
+        }
+        if let Some(mut occupancy) = world.get_resource_mut::<Self>() {
+            for id in ids {
+                *occupancy.counts.entry(*id).or_default() += 1;
+            }
+        }
+    }
+
+    /// Counts entities leaving states with `ids`.
+    pub(crate) fn exit(world: &mut DeferredWorld, ids: &[ComponentId]) {
+        if ids.is_empty() {
+            return;
+        }
+        if let Some(mut occupancy) = world.get_resource_mut::<Self>() {
+            for id in ids {
+                if let Some(count) = occupancy.counts.get_mut(id) {
+                    *count -= 1;
+                    if *count == 0 {
+                        occupancy.counts.remove(id);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Alias of the state with `id`, otherwise the type name of its component.
+#[cfg(any(feature = "serde", feature = "bincode"))]
+pub(crate) fn state_name(world: &World, id: ComponentId) -> Option<String> {
+    crate::alias::StateAliases::name(world, id)
+}
+
+#[cfg(not(any(feature = "serde", feature = "bincode")))]
+pub(crate) fn state_name(world: &World, id: ComponentId) -> Option<String> {
+    world.components().get_name(id).map(Into::into)
+}
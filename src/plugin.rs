@@ -0,0 +1,107 @@
+//! [`Plugin`] of a superstate, configured at the app level.
+
+use std::sync::Mutex;
+
+use bevy_app::{App, Plugin};
+use bevy_ecs::component::Component;
+use log::Level;
+
+use crate::{ConflictPolicy, StateSet, SuperstateBuilder};
+
+/// [`Plugin`] registering `Super` with `States` by [`SuperstateBuilder::build`],
+/// the configurable alternative to [`crate::superstate_plugin`].
+///
+/// ```
+/// # use bevy_app::App;
+/// # use bevy_ecs::component::Component;
+/// # use superstate::{SuperstateInfo, SuperstatePlugin};
+/// # #[derive(Default, Component)]
+/// # #[require(SuperstateInfo<Movement>)]
+/// # struct Movement;
+/// # #[derive(Component, Default)]
+/// # #[require(Movement)]
+/// # struct Walking;
+/// # #[derive(Component)]
+/// # #[require(Movement)]
+/// # struct Running;
+/// App::new().add_plugins(
+///     SuperstatePlugin::<Movement, (Walking, Running)>::default()
+///         .with_default_state::<Walking>()
+///         .with_logging(),
+/// );
+/// ```
+///
+/// Panics when added if the registration fails, like [`crate::superstate_plugin`].
+pub struct SuperstatePlugin<Super: Component, States: StateSet> {
+    // `Plugin::build` takes `&self`, while the builder is consumed.
+    builder: Mutex<Option<SuperstateBuilder<Super, States>>>,
+}
+
+impl<Super: Component, States: StateSet> Default for SuperstatePlugin<Super, States> {
+    fn default() -> Self {
+        Self {
+            builder: Mutex::new(Some(SuperstateBuilder::new())),
+        }
+    }
+}
+
+impl<Super: Component, States: StateSet> SuperstatePlugin<Super, States> {
+    /// Applies `f` to the builder, for options without a method here.
+    pub fn configure(
+        self,
+        f: impl FnOnce(SuperstateBuilder<Super, States>) -> SuperstateBuilder<Super, States>,
+    ) -> Self {
+        let mut builder = self.builder.into_inner().unwrap_or_else(|e| e.into_inner());
+        Self {
+            builder: Mutex::new(builder.take().map(f)),
+        }
+    }
+
+    /// See [`SuperstateBuilder::conflict_policy`].
+    pub fn with_policy(self, policy: ConflictPolicy) -> Self {
+        self.configure(|builder| builder.conflict_policy(policy))
+    }
+
+    /// See [`SuperstateBuilder::default_state`].
+    pub fn with_default_state<Initial: Component + Default>(self) -> Self {
+        self.configure(|builder| builder.default_state::<Initial>())
+    }
+
+    /// See [`SuperstateBuilder::fallback_state`].
+    pub fn with_fallback_state<Fallback: Component + Default>(self) -> Self {
+        self.configure(|builder| builder.fallback_state::<Fallback>())
+    }
+
+    /// Logs transitions at [`Level::Debug`], see [`SuperstateBuilder::log_transitions`].
+    pub fn with_logging(self) -> Self {
+        self.with_logging_at(Level::Debug)
+    }
+
+    /// See [`SuperstateBuilder::log_transitions`].
+    pub fn with_logging_at(self, level: Level) -> Self {
+        self.configure(|builder| builder.log_transitions(level))
+    }
+
+    /// See [`SuperstateBuilder::strict`].
+    pub fn strict(self) -> Self {
+        self.configure(SuperstateBuilder::strict)
+    }
+
+    /// See [`SuperstateBuilder::allow_empty`].
+    pub fn allowing_empty(self) -> Self {
+        self.configure(SuperstateBuilder::allow_empty)
+    }
+}
+
+impl<Super: Component, States: StateSet> Plugin for SuperstatePlugin<Super, States> {
+    fn build(&self, app: &mut App) {
+        let builder = self
+            .builder
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .take();
+        if let Some(builder) = builder {
+            builder.build(app).unwrap();
+        }
+    }
+}
@@ -0,0 +1,128 @@
+use std::{collections::HashMap, marker::PhantomData};
+
+use bevy_ecs::{
+    component::{Component, ComponentId},
+    resource::Resource,
+    world::World,
+};
+
+/// Which state remains when several states are added to an entity at once,
+/// for example when spawning an entity with `(Walking, Running)`.
+///
+/// Set with [`crate::register_hooks_with_policy`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ConflictPolicy {
+    /// The last added state remains.
+    #[default]
+    KeepLast,
+    /// The first added state remains.
+    KeepFirst,
+    /// The state with the highest priority from [`StatePriorities`] remains.
+    /// States without priority have priority `0`.
+    /// With equal priorities, the state added first remains.
+    KeepHighestPriority,
+}
+
+/// Returns the state that remains when the states of `batch` are added to an entity at once,
+/// the decision of [`crate::hooks::on_add_hook_state`] without a world.
+///
+/// `batch` holds the added states in the order they are added, that is the order
+/// of the inserted bundle, not of `States`. The state that was active before
+/// is not in `batch`, as it is always replaced.
+/// `priorities` are used by [`ConflictPolicy::KeepHighestPriority`],
+/// without them all states have priority `0`.
+///
+/// - [`ConflictPolicy::KeepLast`] keeps the last state of `batch`,
+/// - [`ConflictPolicy::KeepFirst`] keeps the first one,
+/// - [`ConflictPolicy::KeepHighestPriority`] keeps the one with the highest priority,
+///   the earliest of `batch` among equal ones.
+///
+/// Returns `None` if `batch` is empty.
+pub fn resolve_transition<Super: Component>(
+    batch: &[ComponentId],
+    policy: ConflictPolicy,
+    priorities: Option<&StatePriorities<Super>>,
+) -> Option<ComponentId> {
+    match policy {
+        ConflictPolicy::KeepLast => batch.last().copied(),
+        ConflictPolicy::KeepFirst => batch.first().copied(),
+        ConflictPolicy::KeepHighestPriority => batch
+            .iter()
+            .copied()
+            .rev()
+            .max_by_key(|id| priorities.map_or(0, |p| p.get(*id))),
+    }
+}
+
+/// Resource with priorities of `Super` states
+/// used by [`ConflictPolicy::KeepHighestPriority`].
+#[derive(Resource, Debug, Clone)]
+pub struct StatePriorities<Super: Component> {
+    priorities: HashMap<ComponentId, u32>,
+    _p: PhantomData<Super>,
+}
+
+impl<Super: Component> Default for StatePriorities<Super> {
+    fn default() -> Self {
+        Self {
+            priorities: HashMap::new(),
+            _p: PhantomData,
+        }
+    }
+}
+
+impl<Super: Component> StatePriorities<Super> {
+    /// Creates a [`StatePrioritiesBuilder`] which registers state components in the `world`.
+    pub fn builder(world: &mut World) -> StatePrioritiesBuilder<'_, Super> {
+        StatePrioritiesBuilder {
+            world,
+            priorities: Self::default(),
+        }
+    }
+
+    /// Set priority of state with `id`.
+    pub fn set_by_id(&mut self, id: ComponentId, priority: u32) -> &mut Self {
+        self.priorities.insert(id, priority);
+        self
+    }
+
+    /// Returns priority of state with `id`, or `0` if it is not set.
+    pub fn get(&self, id: ComponentId) -> u32 {
+        self.priorities.get(&id).copied().unwrap_or_default()
+    }
+}
+
+/// State with a compile-time priority for [`ConflictPolicy::KeepHighestPriority`],
+/// registered with [`StatePrioritiesBuilder::state`].
+///
+/// The priority is read by [`ComponentId`] inside hooks, so it is
+/// copied into [`StatePriorities`] when registered.
+pub trait StatePriority: Component {
+    /// Priority of the state, higher one remains.
+    const PRIORITY: u32;
+}
+
+/// Builder of [`StatePriorities`].
+pub struct StatePrioritiesBuilder<'w, Super: Component> {
+    world: &'w mut World,
+    priorities: StatePriorities<Super>,
+}
+
+impl<Super: Component> StatePrioritiesBuilder<'_, Super> {
+    /// Set priority of `State`.
+    pub fn priority<State: Component>(mut self, priority: u32) -> Self {
+        let id = self.world.register_component::<State>();
+        self.priorities.set_by_id(id, priority);
+        self
+    }
+
+    /// Set priority of `State` to [`StatePriority::PRIORITY`].
+    pub fn state<State: StatePriority>(self) -> Self {
+        self.priority::<State>(State::PRIORITY)
+    }
+
+    /// Returns the built priorities. Insert them into the world as a resource.
+    pub fn build(self) -> StatePriorities<Super> {
+        self.priorities
+    }
+}
@@ -0,0 +1,336 @@
+use std::{any::TypeId, collections::HashMap, marker::PhantomData};
+
+use bevy_ecs::{
+    component::{Component, ComponentId, Components},
+    entity::Entity,
+    query::{Changed, With},
+    removal_detection::RemovedComponents,
+    system::{Local, Query, Res, SystemParam},
+    world::{FromWorld, World},
+};
+
+use crate::{
+    StateEnum, StateSet, SuperstateInfo, SuperstateRegistry,
+    hooks::{NotRegisteredError, SuperstateError},
+    registry::Superstates,
+};
+
+/// Query filter of entities with `Super` in any of `States`,
+/// e.g. `Query<Entity, AnyOfStates<Movement, (Walking, Running)>>`
+/// instead of `(With<Movement>, Or<(With<Walking>, With<Running>)>)`.
+/// Use the same `States` as the registered ones to keep them in sync.
+pub type AnyOfStates<Super, States> = (With<Super>, <States as StateSet>::AnyOf);
+
+/// Query filter of entities whose active state of `Super` changed since the last run
+/// of the system, e.g. `Query<Entity, StateJustChanged<Movement>>`.
+///
+/// Backed by `Changed<SuperstateInfo<Super>>`: the hooks update the internal
+/// bookkeeping of the info bypassing change detection, and mark it as changed
+/// only when the active state changes. Entities that lost the superstate
+/// also match, as the info is kept on them with no active state.
+/// Re-adding the active state or a rejected transition does not match.
+///
+/// Like any change detection, it is relative to the last run of the system,
+/// so a system that does not run every frame sees every entity whose state
+/// changed in between, once, even if it changed several times or changed back.
+/// Use [`StateChanged`] to get the previous and the new state.
+pub type StateJustChanged<Super> = Changed<SuperstateInfo<Super>>;
+
+/// [`SystemParam`] for reading states of `Super` on entities.
+///
+/// ```
+/// # use bevy_ecs::{component::Component, entity::Entity, system::Query};
+/// # use superstate::{SuperstateInfo, SuperstateQuery};
+/// # #[derive(Default, Component)]
+/// # #[require(SuperstateInfo<Movement>)]
+/// # struct Movement;
+/// # #[derive(Component)]
+/// # #[require(Movement)]
+/// # struct Walking;
+/// fn system(states: SuperstateQuery<Movement>, q: Query<Entity>) {
+///     for e in &q {
+///         if states.is::<Walking>(e) {
+///             println!("{e} is walking");
+///         }
+///     }
+/// }
+/// ```
+#[derive(SystemParam)]
+pub struct SuperstateQuery<'w, 's, Super: Component> {
+    infos: Query<'w, 's, &'static SuperstateInfo<Super>>,
+    // Info is kept after `Super` is removed, so only entities with `Super` are iterated.
+    active: Query<'w, 's, (Entity, &'static SuperstateInfo<Super>), With<Super>>,
+    components: &'w Components,
+    registry: Option<Res<'w, SuperstateRegistry<Super>>>,
+    ids: Local<'s, StateTypeIds<Super>>,
+}
+
+impl<Super: Component> SuperstateQuery<'_, '_, Super> {
+    /// Returns `true` if `entity` is in `State`.
+    ///
+    /// Ids of states registered before the system is initialized are cached,
+    /// others are looked up in [`Components`].
+    pub fn is<State: Component>(&self, entity: Entity) -> bool {
+        let Some(id) = self
+            .ids
+            .get(TypeId::of::<State>())
+            .or_else(|| self.components.component_id::<State>())
+        else {
+            return false;
+        };
+        self.infos
+            .get(entity)
+            .is_ok_and(|info| info.active_states().contains(&id))
+    }
+
+    /// Returns the [`ComponentId`] of the active state of `entity`,
+    /// or `None` if it has no superstate.
+    pub fn current(&self, entity: Entity) -> Option<ComponentId> {
+        self.infos
+            .get(entity)
+            .ok()
+            .and_then(SuperstateInfo::current_state)
+    }
+
+    /// Returns [`SuperstateInfo`] of `entity`.
+    pub fn info(&self, entity: Entity) -> Option<&SuperstateInfo<Super>> {
+        self.infos.get(entity).ok()
+    }
+
+    /// Iterates over entities with `Super` and ids of their active states,
+    /// e.g. to count entities in each state.
+    /// The state is `None` only for superstates allowing empty.
+    pub fn iter(&self) -> impl Iterator<Item = (Entity, Option<ComponentId>)> {
+        self.active
+            .iter()
+            .map(|(entity, info)| (entity, info.current_state()))
+    }
+
+    /// Ids of all states registered for `Super`, in `States` order,
+    /// e.g. to list possible states in a debug UI.
+    /// Empty if `Super` is not registered.
+    pub fn all_states(&self) -> &[ComponentId] {
+        self.registry
+            .as_deref()
+            .map_or(&[], SuperstateRegistry::state_ids)
+    }
+
+    /// Same as [`SuperstateQuery::all_states`], but paired with type names of states.
+    pub fn all_state_names(&self) -> impl Iterator<Item = (ComponentId, &str)> {
+        self.all_states()
+            .iter()
+            .filter_map(|&id| self.components.get_info(id).map(|info| (id, info.name())))
+    }
+}
+
+/// Ids of registered states of `Super` by their types,
+/// cached by [`SuperstateQuery`] when the system is initialized.
+pub(crate) struct StateTypeIds<Super: Component> {
+    ids: Vec<(TypeId, ComponentId)>,
+    _p: PhantomData<Super>,
+}
+
+impl<Super: Component> StateTypeIds<Super> {
+    fn get(&self, type_id: TypeId) -> Option<ComponentId> {
+        self.ids
+            .iter()
+            .find_map(|(ty, id)| (*ty == type_id).then_some(*id))
+    }
+}
+
+impl<Super: Component> FromWorld for StateTypeIds<Super> {
+    fn from_world(world: &mut World) -> Self {
+        let components = world.components();
+        let ids = world
+            .get_resource::<SuperstateRegistry<Super>>()
+            .map(|registry| {
+                registry
+                    .state_ids()
+                    .iter()
+                    .filter_map(|&id| Some((components.get_info(id)?.type_id()?, id)))
+                    .collect()
+            })
+            .unwrap_or_default();
+        Self {
+            ids,
+            _p: PhantomData,
+        }
+    }
+}
+
+/// [`SystemParam`] for reading active states of `Super` as variants of `E`,
+/// so systems can `match` on them. `E` should be registered with
+/// [`crate::register_state_enum`] or [`crate::SuperstateBuilder::state_enum`],
+/// otherwise every state reads as `None`.
+///
+/// The mapping of `E` is looked up once per [`StateEnumQuery::iter`],
+/// and the active state of each entity is a single map lookup.
+///
+/// ```
+/// # use bevy_ecs::component::Component;
+/// # use superstate::{StateEnum, StateEnumBuilder, StateEnumQuery, SuperstateInfo};
+/// # #[derive(Default, Component)]
+/// # #[require(SuperstateInfo<Movement>)]
+/// # struct Movement;
+/// # #[derive(Component)]
+/// # #[require(Movement)]
+/// # struct Walking;
+/// # #[derive(Clone)]
+/// # enum MovementState {
+/// #     Walking,
+/// # }
+/// # impl StateEnum<Movement> for MovementState {
+/// #     fn map_states(builder: &mut StateEnumBuilder<Movement, Self>) {
+/// #         builder.state::<Walking>(MovementState::Walking);
+/// #     }
+/// # }
+/// fn system(states: StateEnumQuery<Movement, MovementState>) {
+///     for (entity, state) in states.iter() {
+///         match state {
+///             Some(MovementState::Walking) => println!("{entity} is walking"),
+///             None => {}
+///         }
+///     }
+/// }
+/// ```
+#[derive(SystemParam)]
+pub struct StateEnumQuery<'w, 's, Super: Component, E: StateEnum<Super>> {
+    infos: Query<'w, 's, &'static SuperstateInfo<Super>>,
+    active: Query<'w, 's, (Entity, &'static SuperstateInfo<Super>), With<Super>>,
+    registry: Option<Res<'w, SuperstateRegistry<Super>>>,
+    _p: PhantomData<E>,
+}
+
+impl<Super: Component, E: StateEnum<Super>> StateEnumQuery<'_, '_, Super, E> {
+    /// Returns the active state of `entity` as a variant of `E`,
+    /// or `None` if it has no active state or the state is not mapped.
+    pub fn get(&self, entity: Entity) -> Option<E> {
+        let id = self.infos.get(entity).ok()?.current_state()?;
+        self.variants()?.get(&id).cloned()
+    }
+
+    /// Iterates over entities with `Super` and their active states as variants of `E`.
+    pub fn iter(&self) -> impl Iterator<Item = (Entity, Option<E>)> {
+        let variants = self.variants();
+        self.active.iter().map(move |(entity, info)| {
+            let state = info
+                .current_state()
+                .and_then(|id| variants?.get(&id).cloned());
+            (entity, state)
+        })
+    }
+
+    fn variants(&self) -> Option<&HashMap<ComponentId, E>> {
+        self.registry.as_deref()?.state_enum::<E>()
+    }
+}
+
+/// [`SystemParam`] for pulling changes of the active state of `Super`
+/// since the last run of the system, an alternative to events
+/// for systems that do not run every frame.
+///
+/// Only the active state at the last run is compared, so
+/// a transition to another state and back between runs is not reported.
+///
+/// ```
+/// # use bevy_ecs::component::Component;
+/// # use superstate::{StateChanged, SuperstateInfo};
+/// # #[derive(Default, Component)]
+/// # #[require(SuperstateInfo<Movement>)]
+/// # struct Movement;
+/// fn system(mut changed: StateChanged<Movement>) {
+///     for (entity, from, to) in changed.iter() {
+///         println!("{entity} changed state from {from:?} to {to:?}");
+///     }
+/// }
+/// ```
+#[derive(SystemParam)]
+pub struct StateChanged<'w, 's, Super: Component> {
+    changed:
+        Query<'w, 's, (Entity, &'static SuperstateInfo<Super>), Changed<SuperstateInfo<Super>>>,
+    removed: RemovedComponents<'w, 's, SuperstateInfo<Super>>,
+    last: Local<'s, HashMap<Entity, ComponentId>>,
+}
+
+impl<Super: Component> StateChanged<'_, '_, Super> {
+    /// Returns `(entity, from, to)` for entities whose active state changed
+    /// since the last run, `None` if the entity had or has no superstate.
+    ///
+    /// The stored states are updated while iterating,
+    /// so changes skipped by not consuming the iterator are lost.
+    /// States of despawned entities or entities without the info are forgotten.
+    pub fn iter(
+        &mut self,
+    ) -> impl Iterator<Item = (Entity, Option<ComponentId>, Option<ComponentId>)> + '_ {
+        for entity in self.removed.read() {
+            self.last.remove(&entity);
+        }
+        self.changed.iter().filter_map(|(entity, info)| {
+            let to = info.current_state();
+            let from = match to {
+                Some(to) => self.last.insert(entity, to),
+                None => self.last.remove(&entity),
+            };
+            (from != to).then_some((entity, from, to))
+        })
+    }
+}
+
+/// Returns the id of the active state of `Super` of `entity`, or `None` if it has
+/// no active state, e.g. after `Super` was removed, without panicking on a missing
+/// entity or info, e.g. in hooks of downstream libraries.
+/// [`DeferredWorld`](bevy_ecs::world::DeferredWorld) of a hook derefs to [`World`].
+///
+/// Returns [`SuperstateError::NotRegistered`] if `Super` is not registered,
+/// [`SuperstateError::NoEntity`] if the entity does not exist,
+/// or [`SuperstateError::NoInfo`] if it never had `Super`.
+pub fn try_current_state<Super: Component>(
+    world: &World,
+    entity: Entity,
+) -> Result<Option<ComponentId>, SuperstateError> {
+    if !world.contains_resource::<SuperstateRegistry<Super>>() {
+        return Err(NotRegisteredError.into());
+    }
+    let entity_ref = world
+        .get_entity(entity)
+        .map_err(|_| SuperstateError::NoEntity(entity))?;
+    let info = entity_ref
+        .get::<SuperstateInfo<Super>>()
+        .ok_or(SuperstateError::NoInfo(entity))?;
+    Ok(info.current_state())
+}
+
+/// Returns all entities whose active state of `Super` is `state`.
+///
+/// Useful when the state is known only at runtime, e.g. loaded from assets,
+/// and a static `Query<Entity, With<State>>` cannot be used.
+pub fn entities_in_state<Super: Component>(world: &World, state: ComponentId) -> Vec<Entity> {
+    let Some(mut query) = world.try_query::<(Entity, &SuperstateInfo<Super>)>() else {
+        return Vec::new();
+    };
+    query
+        .iter(world)
+        .filter(|(_, info)| info.current_state() == Some(state))
+        .map(|(entity, _)| entity)
+        .collect()
+}
+
+/// Returns type names of `(superstate, state)` for every registered superstate
+/// that `entity` is in, in order of registration,
+/// e.g. for an inspector line with states of all machines of the entity.
+///
+/// Empty if the entity does not exist.
+pub fn dump_entity_states(world: &World, entity: Entity) -> Vec<(&str, &str)> {
+    let (Ok(entity), Some(superstates)) = (
+        world.get_entity(entity),
+        world.get_resource::<Superstates>(),
+    ) else {
+        return Vec::new();
+    };
+    let components = world.components();
+    let name = |id| components.get_info(id).map(|info| info.name());
+    superstates
+        .current_states(&entity)
+        .filter_map(|(superstate, state)| Some((name(superstate)?, name(state)?)))
+        .collect()
+}
@@ -0,0 +1,36 @@
+use std::any::type_name;
+
+use bevy_ecs::component::Component;
+use bevy_reflect::{TypePath, utility::GenericTypePathCell};
+
+use crate::SuperstateInfo;
+
+// Implemented by hand, because the derived one requires `S: TypePath`,
+// and superstate components are not required to be reflected.
+impl<S: Component> TypePath for SuperstateInfo<S> {
+    fn type_path() -> &'static str {
+        static CELL: GenericTypePathCell = GenericTypePathCell::new();
+        CELL.get_or_insert::<Self, _>(|| {
+            format!("superstate::SuperstateInfo<{}>", type_name::<S>())
+        })
+    }
+
+    fn short_type_path() -> &'static str {
+        static CELL: GenericTypePathCell = GenericTypePathCell::new();
+        CELL.get_or_insert::<Self, _>(|| {
+            format!("SuperstateInfo<{}>", disqualified::ShortName::of::<S>())
+        })
+    }
+
+    fn type_ident() -> Option<&'static str> {
+        Some("SuperstateInfo")
+    }
+
+    fn crate_name() -> Option<&'static str> {
+        Some("superstate")
+    }
+
+    fn module_path() -> Option<&'static str> {
+        Some("superstate")
+    }
+}
@@ -0,0 +1,231 @@
+use std::{
+    any::{Any, TypeId},
+    collections::HashMap,
+    fmt,
+    marker::PhantomData,
+    sync::Arc,
+    time::Duration,
+};
+
+use bevy_ecs::{
+    component::{Component, ComponentId},
+    resource::Resource,
+    world::{EntityRef, EntityWorldMut, World},
+};
+
+use log::Level;
+
+use crate::{ConflictPolicy, StateEnum, SuperstateInfo, constructor::StateConstructors};
+
+/// Inserts a default value of a state.
+pub(crate) type InsertFn = fn(&mut EntityWorldMut);
+
+/// Inserts the initial state built by the factory of
+/// [`crate::SuperstateBuilder::default_state_with`].
+#[derive(Clone)]
+pub(crate) struct InitialFn(pub(crate) Arc<dyn Fn(&mut EntityWorldMut) + Send + Sync>);
+
+impl fmt::Debug for InitialFn {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("InitialFn")
+    }
+}
+
+/// Resource with information about registered superstate `Super`,
+/// shared by all entities with this superstate.
+/// Inserted by [`crate::register_hooks`].
+#[derive(Resource, Debug, Clone)]
+pub struct SuperstateRegistry<Super: Component> {
+    super_id: ComponentId,
+    state_ids: Vec<ComponentId>,
+    // States registered by `States` bundle, the rest are dynamic.
+    static_len: usize,
+    pub(crate) policy: ConflictPolicy,
+    pub(crate) allow_empty: bool,
+    pub(crate) parallel: bool,
+    pub(crate) stack_depth: usize,
+    pub(crate) min_dwell: HashMap<ComponentId, Duration>,
+    #[cfg_attr(not(feature = "bevy_time"), allow(dead_code))]
+    pub(crate) log_min_dwell: bool,
+    pub(crate) log_level: Option<Level>,
+    #[cfg_attr(not(debug_assertions), allow(dead_code))]
+    pub(crate) strict: bool,
+    pub(crate) fallback: Option<InsertFn>,
+    pub(crate) initial: Option<InitialFn>,
+    pub(crate) remove_info: bool,
+    // Inserts `Super` into entity with a dynamic state, which cannot require it.
+    pub(crate) insert_super: Option<InsertFn>,
+    // Variants of every `StateEnum` by state ids, `HashMap<ComponentId, E>` by `TypeId` of `E`.
+    pub(crate) state_enums: HashMap<TypeId, Arc<dyn Any + Send + Sync>>,
+    _p: PhantomData<Super>,
+}
+
+impl<Super: Component> SuperstateRegistry<Super> {
+    pub(crate) fn new(super_id: ComponentId, state_ids: Box<[ComponentId]>) -> Self {
+        Self {
+            super_id,
+            static_len: state_ids.len(),
+            state_ids: state_ids.into_vec(),
+            policy: ConflictPolicy::default(),
+            allow_empty: false,
+            parallel: false,
+            stack_depth: 0,
+            min_dwell: HashMap::new(),
+            log_min_dwell: false,
+            log_level: None,
+            strict: false,
+            fallback: None,
+            initial: None,
+            remove_info: false,
+            insert_super: None,
+            state_enums: HashMap::new(),
+            _p: PhantomData,
+        }
+    }
+
+    /// Id of `Super` component.
+    pub fn super_id(&self) -> ComponentId {
+        self.super_id
+    }
+
+    /// Ids of all states of `Super`, in `States` order,
+    /// followed by states registered with [`crate::register_state`].
+    ///
+    /// Computed by [`crate::register_hooks`] from the component ids of `States`,
+    /// without registering `States` in the bundle registry of the world,
+    /// so tools can reuse them instead of registering the bundle themselves.
+    pub fn state_ids(&self) -> &[ComponentId] {
+        &self.state_ids
+    }
+
+    /// Ids of states registered with [`crate::register_state`].
+    pub fn dynamic_state_ids(&self) -> &[ComponentId] {
+        &self.state_ids[self.static_len..]
+    }
+
+    pub(crate) fn push_state(&mut self, id: ComponentId) {
+        self.state_ids.push(id);
+    }
+
+    /// Returns `true` if `entity` has any state of `Super`.
+    pub fn has_state(&self, entity: &EntityRef) -> bool {
+        self.state_ids.iter().any(|id| entity.contains_id(*id))
+    }
+
+    /// [`ConflictPolicy`] of `Super`.
+    pub fn policy(&self) -> ConflictPolicy {
+        self.policy
+    }
+
+    /// Returns `true` if `Super` stays on entity without states,
+    /// see [`crate::register_hooks_allowing_empty`].
+    pub fn allows_empty(&self) -> bool {
+        self.allow_empty
+    }
+
+    /// Returns `true` if states of `Super` are parallel regions,
+    /// see [`crate::register_parallel_hooks`].
+    pub fn is_parallel(&self) -> bool {
+        self.parallel
+    }
+
+    /// Maximum depth of the history stack of `Super`,
+    /// `0` if the stack is disabled. See [`crate::SuperstateBuilder::history_stack`].
+    pub fn stack_depth(&self) -> usize {
+        self.stack_depth
+    }
+
+    /// Variant of `E` for the state with `id`, or `None` if the state is not mapped
+    /// or `E` is not registered with [`crate::register_state_enum`].
+    pub fn state_as<E: StateEnum<Super>>(&self, id: ComponentId) -> Option<E> {
+        self.state_enum::<E>()?.get(&id).cloned()
+    }
+
+    /// Variants of `E` by state ids, or `None` if `E` is not registered.
+    pub(crate) fn state_enum<E: StateEnum<Super>>(&self) -> Option<&HashMap<ComponentId, E>> {
+        self.state_enums
+            .get(&TypeId::of::<E>())?
+            .downcast_ref::<HashMap<ComponentId, E>>()
+    }
+
+    /// Minimum time the state with `id` must be active before the entity can leave it,
+    /// see [`crate::SuperstateBuilder::min_dwell`].
+    pub fn min_dwell(&self, id: ComponentId) -> Option<Duration> {
+        self.min_dwell.get(&id).copied()
+    }
+}
+
+/// Id of `Super` component, or `None` if `Super` was never registered
+/// with [`crate::register_hooks`].
+pub fn super_component_id<Super: Component>(world: &World) -> Option<ComponentId> {
+    world
+        .get_resource::<SuperstateRegistry<Super>>()
+        .map(SuperstateRegistry::super_id)
+}
+
+/// Id of `State` component in `world`, or `None` if it was never registered there.
+///
+/// A [`ComponentId`] is assigned by each world separately, so an id from one world,
+/// e.g. the main world, means nothing in another one, e.g. the render world or a sub-app.
+/// Resolve ids of states in the world where they are used instead of passing them across,
+/// and carry states to another world by names, as `snapshot_entity_states`
+/// and serialized [`SuperstateInfo`] do.
+pub fn resolve_state_id<State: Component>(world: &World) -> Option<ComponentId> {
+    world.component_id::<State>()
+}
+
+type CurrentStateFn = fn(&EntityRef) -> Option<ComponentId>;
+type ConstructStateFn = fn(&mut EntityWorldMut, ComponentId) -> bool;
+
+/// Resource with all registered superstates,
+/// used to read and enter their states without knowing `Super` types.
+#[derive(Resource, Default)]
+pub(crate) struct Superstates {
+    current: Vec<(ComponentId, CurrentStateFn)>,
+    #[cfg_attr(not(feature = "bincode"), allow(dead_code))]
+    construct: HashMap<ComponentId, ConstructStateFn>,
+}
+
+impl Superstates {
+    pub(crate) fn insert<Super: Component>(world: &mut World) {
+        let id = world.register_component::<Super>();
+        let mut superstates = world.get_resource_or_init::<Self>();
+        superstates.current.push((id, current_state::<Super>));
+        superstates
+            .construct
+            .insert(id, StateConstructors::<Super>::insert);
+    }
+
+    pub(crate) fn remove<Super: Component>(world: &mut World) {
+        let Some(id) = world.component_id::<Super>() else {
+            return;
+        };
+        if let Some(mut superstates) = world.get_resource_mut::<Self>() {
+            superstates.current.retain(|(current, _)| *current != id);
+            superstates.construct.remove(&id);
+        }
+    }
+
+    /// Constructor of states of superstate with `superstate` id,
+    /// which returns `false` if there is no constructor for the state.
+    #[cfg(feature = "bincode")]
+    pub(crate) fn constructor(&self, superstate: ComponentId) -> Option<ConstructStateFn> {
+        self.construct.get(&superstate).copied()
+    }
+
+    /// Ids of registered superstates with the current state on `entity`.
+    pub(crate) fn current_states<'a>(
+        &'a self,
+        entity: &'a EntityRef,
+    ) -> impl Iterator<Item = (ComponentId, ComponentId)> + 'a {
+        self.current
+            .iter()
+            .filter_map(|(id, current)| Some((*id, current(entity)?)))
+    }
+}
+
+fn current_state<Super: Component>(entity: &EntityRef) -> Option<ComponentId> {
+    entity
+        .get::<SuperstateInfo<Super>>()
+        .and_then(SuperstateInfo::current_state)
+}
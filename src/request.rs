@@ -0,0 +1,96 @@
+//! Transitions requested by events and applied by a system at a known point of the schedule.
+
+use std::{any::type_name, marker::PhantomData};
+
+use bevy_ecs::{
+    change_detection::Mut,
+    component::{Component, ComponentId},
+    entity::Entity,
+    event::{Event, EventCursor, Events},
+    system::Local,
+    world::World,
+};
+use log::warn;
+
+use crate::{StateSet, SuperstateHooks, SuperstateRegistry, constructor::StateConstructors};
+
+/// Event requesting a transition of an entity to a state of `Super`,
+/// applied by [`process_transitions`].
+///
+/// Inserting a state applies the transition immediately by the hooks,
+/// while the request lands only when the system runs, so it can be ordered
+/// relative to other systems, see [`crate::SuperstateBuilder::process_transitions_in`].
+///
+/// The state is known only by its [`ComponentId`], so it is constructed
+/// by its constructor registered with [`crate::register_default_state`]
+/// or [`crate::register_state_constructor`].
+#[derive(Event)]
+pub struct TransitionRequest<Super: Component> {
+    /// The entity to transition.
+    pub entity: Entity,
+    /// Id of the state to enter.
+    pub state: ComponentId,
+    _p: PhantomData<Super>,
+}
+
+impl<Super: Component> TransitionRequest<Super> {
+    /// Requests transition of `entity` to the state with `state` id.
+    pub fn new(entity: Entity, state: ComponentId) -> Self {
+        Self {
+            entity,
+            state,
+            _p: PhantomData,
+        }
+    }
+}
+
+/// System applying [`TransitionRequest`]s of `Super` in the order they were sent.
+/// Commands queued by the hooks of each transition are applied before the next one,
+/// so later requests see the result of earlier ones.
+///
+/// Requests are read with a cursor, so other systems can read them too,
+/// and they are dropped by [`Events`] as usual.
+/// Requests for despawned entities are dropped,
+/// requests for unknown states or states without a constructor are logged.
+/// While the hooks are disabled by [`crate::deregister_hooks`], requests are ignored.
+pub fn process_transitions<Super: Component, States: StateSet>(
+    world: &mut World,
+    mut cursor: Local<EventCursor<TransitionRequest<Super>>>,
+) {
+    let enabled = SuperstateHooks::<Super, States>::enabled(world);
+    world.try_resource_scope(|world, events: Mut<Events<TransitionRequest<Super>>>| {
+        for request in cursor.read(&events) {
+            if enabled {
+                apply_transition::<Super>(world, request.entity, request.state);
+            }
+        }
+    });
+}
+
+/// Inserts the state with `state` id into `entity` by its constructor,
+/// and applies the commands queued by the hooks.
+///
+/// Despawned entities are skipped, unknown states or states without a constructor are logged.
+pub(crate) fn apply_transition<Super: Component>(
+    world: &mut World,
+    entity: Entity,
+    state: ComponentId,
+) {
+    if !world
+        .get_resource::<SuperstateRegistry<Super>>()
+        .is_some_and(|registry| registry.state_ids().contains(&state))
+    {
+        warn!(
+            "Requested State({state:?}) of {entity} is not a state of {}.",
+            type_name::<Super>()
+        );
+        return;
+    }
+    let Ok(mut entity_mut) = world.get_entity_mut(entity) else {
+        return;
+    };
+    if !StateConstructors::<Super>::insert(&mut entity_mut, state) {
+        warn!("Requested State({state:?}) of {entity} has no constructor.");
+    }
+    world.flush();
+}
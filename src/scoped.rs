@@ -0,0 +1,172 @@
+//! Despawning of child entities when their parent exits a state,
+//! per-entity analog of `bevy_state`'s `StateScoped`,
+//! and of helper entities when their host loses the superstate.
+
+use std::marker::PhantomData;
+
+use bevy_ecs::{
+    component::{Component, ComponentId, HookContext},
+    entity::Entity,
+    query::Without,
+    removal_detection::RemovedComponents,
+    system::{Commands, Query},
+    world::DeferredWorld,
+};
+#[cfg(feature = "bevy_app")]
+use bevy_ecs::{hierarchy::Children, resource::Resource, system::ResMut};
+
+/// Marker of a child entity, which is despawned when its parent exits `State` of `Super`,
+/// e.g. an UI panel of a menu state. Despawned in `PostUpdate` by a system added by
+/// [`SuperstateBuilder::build`](crate::SuperstateBuilder::build), after exit systems
+/// in `Update` could read the child. Without `bevy_app` feature the child is kept.
+///
+/// Transition to the same state and re-adding the state keep the child,
+/// as well as a rejected transition, as the entity never left `State`.
+#[derive(Component, Debug)]
+#[require(ScopedState<Super>)]
+#[component(on_add = on_add_state_scoped::<Super, State>)]
+pub struct StateScoped<Super: Component, State: Component> {
+    _p: PhantomData<(Super, State)>,
+}
+
+impl<Super: Component, State: Component> Default for StateScoped<Super, State> {
+    fn default() -> Self {
+        Self { _p: PhantomData }
+    }
+}
+
+/// Id of `State` of [`StateScoped`], so all scoped children of `Super`
+/// are found by one query whatever their state.
+#[derive(Component, Debug)]
+pub(crate) struct ScopedState<Super: Component> {
+    state: Option<ComponentId>,
+    _p: PhantomData<Super>,
+}
+
+impl<Super: Component> Default for ScopedState<Super> {
+    fn default() -> Self {
+        Self {
+            state: None,
+            _p: PhantomData,
+        }
+    }
+}
+
+fn on_add_state_scoped<Super: Component, State: Component>(
+    mut world: DeferredWorld,
+    ctx: HookContext,
+) {
+    let state = world.components().component_id::<State>();
+    if let Some(mut scoped) = world.get_mut::<ScopedState<Super>>(ctx.entity) {
+        scoped.state = state;
+    }
+}
+
+#[cfg(feature = "bevy_app")]
+/// Resource with states of `Super` exited since the last run of `despawn_state_scoped`,
+/// filled by the hooks only for states that were active.
+#[derive(Resource)]
+pub(crate) struct StateScopedExits<Super: Component> {
+    exits: Vec<(Entity, ComponentId)>,
+    _p: PhantomData<Super>,
+}
+
+#[cfg(feature = "bevy_app")]
+impl<Super: Component> Default for StateScopedExits<Super> {
+    fn default() -> Self {
+        Self {
+            exits: Vec::new(),
+            _p: PhantomData,
+        }
+    }
+}
+
+#[cfg(feature = "bevy_app")]
+impl<Super: Component> StateScopedExits<Super> {
+    pub(crate) fn push(&mut self, entity: Entity, state: ComponentId) {
+        self.exits.push((entity, state));
+    }
+}
+
+#[cfg(feature = "bevy_app")]
+/// System despawning children with [`StateScoped`] of `Super`
+/// of entities which exited the scoped state since the last run.
+pub(crate) fn despawn_state_scoped<Super: Component>(
+    mut commands: Commands,
+    exits: Option<ResMut<StateScopedExits<Super>>>,
+    parents: Query<&Children>,
+    scoped: Query<(Entity, &ScopedState<Super>)>,
+) {
+    let Some(mut exits) = exits else {
+        return;
+    };
+    for (entity, state) in exits.exits.drain(..) {
+        let Ok(children) = parents.get(entity) else {
+            continue;
+        };
+        for (child, scoped) in scoped.iter_many(children) {
+            if scoped.state == Some(state) {
+                commands.entity(child).despawn();
+            }
+        }
+    }
+}
+
+/// Relationship of a helper entity to a `host`, which despawns the helper
+/// when the host loses `Super`, e.g. an effect attached to a movement machine.
+/// Despawning the host despawns the helper too.
+///
+/// Cleaned by [`despawn_super_scoped`], which is added by
+/// [`SuperstateBuilder::build`](crate::SuperstateBuilder::build).
+#[derive(Component, Debug)]
+#[relationship(relationship_target = SuperScopedEntities<Super>)]
+pub struct SuperScoped<Super: Component> {
+    #[relationship]
+    host: Entity,
+    _p: PhantomData<Super>,
+}
+
+impl<Super: Component> SuperScoped<Super> {
+    /// Links the helper to the `host`.
+    pub fn new(host: Entity) -> Self {
+        Self {
+            host,
+            _p: PhantomData,
+        }
+    }
+
+    /// Returns the host entity.
+    pub fn host(&self) -> Entity {
+        self.host
+    }
+}
+
+/// Helper entities with [`SuperScoped<Super>`] of the host.
+#[derive(Component, Debug)]
+#[relationship_target(relationship = SuperScoped<Super>, linked_spawn)]
+pub struct SuperScopedEntities<Super: Component> {
+    #[relationship]
+    entities: Vec<Entity>,
+    _p: PhantomData<Super>,
+}
+
+impl<Super: Component> SuperScopedEntities<Super> {
+    /// Returns the helper entities.
+    pub fn entities(&self) -> &[Entity] {
+        &self.entities
+    }
+}
+
+/// System despawning helpers with [`SuperScoped<Super>`]
+/// of hosts which lost `Super`.
+pub fn despawn_super_scoped<Super: Component>(
+    mut commands: Commands,
+    mut removed: RemovedComponents<Super>,
+    hosts: Query<&SuperScopedEntities<Super>, Without<Super>>,
+) {
+    for helpers in hosts.iter_many(removed.read()) {
+        for &helper in helpers.entities() {
+            commands.entity(helper).despawn();
+        }
+    }
+}
@@ -0,0 +1,54 @@
+use bevy_ecs::{
+    component::{Component, ComponentId},
+    world::{EntityRef, World},
+};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::{SuperstateInfo, alias::StateAliases};
+
+/// Serialized form of [`SuperstateInfo`].
+/// [`ComponentId`] is not stable across runs, so the active state
+/// is stored by the type path of its component, or its alias, see [`crate::state_alias`].
+#[derive(Serialize, Deserialize)]
+struct SuperstateInfoData {
+    state: Option<String>,
+}
+
+/// Only the active state is persisted, not the internal bookkeeping.
+impl<S: Component> Serialize for SuperstateInfo<S> {
+    fn serialize<Ser: Serializer>(&self, serializer: Ser) -> Result<Ser::Ok, Ser::Error> {
+        SuperstateInfoData {
+            state: self.state_name.clone(),
+        }
+        .serialize(serializer)
+    }
+}
+
+/// Deserialized info is resolved to the [`ComponentId`]s of the current world
+/// on the next state hook invocation on the entity.
+impl<'de, S: Component> Deserialize<'de> for SuperstateInfo<S> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let data = SuperstateInfoData::deserialize(deserializer)?;
+        Ok(Self {
+            state_name: data.state,
+            ..Default::default()
+        })
+    }
+}
+
+impl<S: Component> SuperstateInfo<S> {
+    /// Returns the id of the deserialized active state
+    /// if it is not yet resolved and the entity has it.
+    pub(crate) fn restored_state(
+        &self,
+        world: &World,
+        state_ids: &[ComponentId],
+        entity: &EntityRef,
+    ) -> Option<ComponentId> {
+        if !self.states_on_entity.is_empty() {
+            return None;
+        }
+        let id = StateAliases::id(world, self.state_name.as_deref()?)?;
+        (state_ids.contains(&id) && entity.contains_id(id)).then_some(id)
+    }
+}
@@ -0,0 +1,24 @@
+//! System sets of the systems added for superstates.
+
+use bevy_ecs::schedule::SystemSet;
+
+/// System sets of all systems added by [`crate::SuperstateBuilder::build`]
+/// and [`crate::SuperstateAppExt`], to order other systems around them,
+/// e.g. `.after(SuperstateSet::ProcessTransitions)`.
+///
+/// The sets are shared by all superstates.
+#[derive(SystemSet, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SuperstateSet {
+    /// [`crate::process_transitions`] in `Update` schedule, applying requested transitions,
+    /// see [`crate::SuperstateBuilder::process_transitions_in`].
+    ProcessTransitions,
+    /// Systems copying the active state elsewhere: [`crate::CurrentState`]
+    /// and [`crate::StateLabel`] in `PostUpdate` schedule, and `StateMirror`
+    /// of `bevy_state` feature in `PreUpdate` schedule.
+    Sync,
+    /// Despawning of [`crate::StateScoped`] entities in `PostUpdate` schedule.
+    Cleanup,
+    /// Validation of `validate` feature in `PostUpdate` schedule,
+    /// and measuring of `diagnostic` feature in `Last` schedule.
+    Diagnostics,
+}
@@ -0,0 +1,82 @@
+//! Compact binary snapshot of the active states of an entity, e.g. for networking.
+
+use bevy_ecs::{entity::Entity, error::BevyError, world::World};
+use log::warn;
+
+use crate::{alias::StateAliases, registry::Superstates};
+
+/// Type names of `(superstate, state)` pairs, as component ids are not stable across runs.
+/// States are named by their aliases if any, see [`crate::state_alias`].
+type Snapshot = Vec<(String, String)>;
+
+fn config() -> impl bincode::config::Config {
+    bincode::config::standard()
+}
+
+/// Encodes the active state of every registered superstate of `entity`
+/// by type names of the superstate and the state, or the alias of the state.
+///
+/// Only active states are included, not the previous state,
+/// the history stack or the time in state.
+/// Names are resolved to ids by the restoring world, so a snapshot can be
+/// restored in another world, e.g. of a sub-app, see [`crate::resolve_state_id`].
+/// Empty snapshot if the entity does not exist.
+pub fn snapshot_entity_states(world: &World, entity: Entity) -> Vec<u8> {
+    let (Ok(entity), Some(superstates)) = (
+        world.get_entity(entity),
+        world.get_resource::<Superstates>(),
+    ) else {
+        return Vec::new();
+    };
+    let snapshot: Snapshot = superstates
+        .current_states(&entity)
+        .filter_map(|(superstate, state)| {
+            Some((
+                world.components().get_name(superstate)?.into_owned(),
+                StateAliases::name(world, state)?,
+            ))
+        })
+        .collect();
+    bincode::encode_to_vec(snapshot, config()).unwrap_or_default()
+}
+
+/// Restores states of `entity` from [`snapshot_entity_states`] bytes,
+/// in order of registration of superstates, so a parent state is entered
+/// before the states of its own superstate.
+///
+/// States are inserted by constructors registered with
+/// [`crate::register_default_state`] or [`crate::register_state_constructor`],
+/// triggering the usual transition hooks. States the entity is already in are skipped,
+/// states of unknown superstates or without a constructor are logged and skipped.
+///
+/// Returns an error if `bytes` are not a snapshot or the entity does not exist.
+pub fn restore_entity_states(
+    world: &mut World,
+    entity: Entity,
+    bytes: &[u8],
+) -> Result<(), BevyError> {
+    let (snapshot, _): (Snapshot, usize) = bincode::decode_from_slice(bytes, config())?;
+    let mut entity = world.get_entity_mut(entity)?;
+    for (superstate, state) in snapshot {
+        let ids = entity.world_scope(|world: &mut World| {
+            let id = |name: &str| StateAliases::id(world, name);
+            let constructor = id(&superstate).and_then(|superstate| {
+                world
+                    .get_resource::<Superstates>()
+                    .and_then(|superstates| superstates.constructor(superstate))
+            });
+            Some((constructor?, id(&state)?))
+        });
+        let Some((constructor, id)) = ids else {
+            warn!("State {state} of {superstate} is not registered to be restored.");
+            continue;
+        };
+        if !entity.contains_id(id) && !constructor(&mut entity, id) {
+            warn!(
+                "State {state} of {} has no constructor to be restored.",
+                entity.id()
+            );
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,130 @@
+//! Mapping of states to variants of a user enum, for `match`-based logic.
+
+use std::{
+    any::{Any, TypeId},
+    collections::HashMap,
+    marker::PhantomData,
+    sync::Arc,
+};
+
+use bevy_ecs::{
+    component::{Component, ComponentId},
+    error::BevyError,
+    world::World,
+};
+
+use crate::{
+    SuperstateRegistry,
+    hooks::{NotRegisteredError, UnknownStateError},
+};
+
+/// Enum with a variant for states of `Super`, converted from the id of a state
+/// registered with [`register_state_enum`] or [`crate::SuperstateBuilder::state_enum`].
+///
+/// ```
+/// # use bevy_ecs::{component::Component, world::World};
+/// # use superstate::{SuperstateInfo, SuperstateRegistry, StateEnum, StateEnumBuilder};
+/// # use superstate::{register_hooks, register_state_enum};
+/// # #[derive(Default, Component)]
+/// # #[require(SuperstateInfo<Movement>)]
+/// # struct Movement;
+/// # #[derive(Component)]
+/// # #[require(Movement)]
+/// # struct Walking;
+/// # #[derive(Component)]
+/// # #[require(Movement)]
+/// # struct Running;
+/// #[derive(Clone, Copy, PartialEq, Debug)]
+/// enum MovementState {
+///     Walking,
+///     Running,
+/// }
+///
+/// impl StateEnum<Movement> for MovementState {
+///     fn map_states(builder: &mut StateEnumBuilder<Movement, Self>) {
+///         builder
+///             .state::<Walking>(MovementState::Walking)
+///             .state::<Running>(MovementState::Running);
+///     }
+/// }
+///
+/// let mut world = World::new();
+/// register_hooks::<Movement, (Walking, Running)>(&mut world).unwrap();
+/// register_state_enum::<Movement, MovementState>(&mut world).unwrap();
+/// let e = world.spawn(Running).id();
+/// let info = world.get::<SuperstateInfo<Movement>>(e).unwrap();
+/// let registry = world.resource::<SuperstateRegistry<Movement>>();
+/// assert_eq!(info.current_as(registry), Some(MovementState::Running));
+/// ```
+pub trait StateEnum<Super: Component>: Clone + Send + Sync + 'static {
+    /// Maps state components to variants with [`StateEnumBuilder::state`].
+    fn map_states(builder: &mut StateEnumBuilder<Super, Self>);
+
+    /// Returns the variant of the state with `id`, or `None`
+    /// if the state is not mapped or the enum is not registered.
+    fn from_component_id(id: ComponentId, world: &World) -> Option<Self> {
+        world
+            .get_resource::<SuperstateRegistry<Super>>()?
+            .state_as::<Self>(id)
+    }
+}
+
+/// Builder of the mapping of [`StateEnum`], passed to [`StateEnum::map_states`].
+pub struct StateEnumBuilder<'w, Super: Component, E> {
+    world: &'w mut World,
+    variants: HashMap<ComponentId, E>,
+    _p: PhantomData<Super>,
+}
+
+impl<Super: Component, E> StateEnumBuilder<'_, Super, E> {
+    /// Maps `State` to `variant`.
+    pub fn state<State: Component>(&mut self, variant: E) -> &mut Self {
+        let id = self.world.register_component::<State>();
+        self.variants.insert(id, variant);
+        self
+    }
+}
+
+/// Builds the mapping of `E` and stores it in [`SuperstateRegistry<Super>`],
+/// so [`crate::SuperstateInfo::current_as`] can convert the active state to `E`.
+///
+/// Returns [`NotRegisteredError`] if `Super` is not registered,
+/// or [`UnknownStateError`] if a mapped component is not a state of `Super`.
+pub fn register_state_enum<Super: Component, E: StateEnum<Super>>(
+    world: &mut World,
+) -> Result<(), BevyError> {
+    let state_ids = world
+        .get_resource::<SuperstateRegistry<Super>>()
+        .ok_or(NotRegisteredError)?
+        .state_ids()
+        .to_vec();
+    let (type_id, variants) = map_state_enum::<Super, E>(world, &state_ids)?;
+    world
+        .resource_mut::<SuperstateRegistry<Super>>()
+        .state_enums
+        .insert(type_id, variants);
+    Ok(())
+}
+
+/// Mapping of [`StateEnum`] by its type, stored in [`SuperstateRegistry`].
+pub(crate) type StateEnumMap = (TypeId, Arc<dyn Any + Send + Sync>);
+
+/// Builds the mapping of `E` without storing it, so it can be checked before
+/// `Super` is registered. Returns [`UnknownStateError`] if a mapped component
+/// is not one of `state_ids`.
+pub(crate) fn map_state_enum<Super: Component, E: StateEnum<Super>>(
+    world: &mut World,
+    state_ids: &[ComponentId],
+) -> Result<StateEnumMap, UnknownStateError> {
+    let mut builder = StateEnumBuilder::<Super, E> {
+        world,
+        variants: HashMap::new(),
+        _p: PhantomData,
+    };
+    E::map_states(&mut builder);
+    let variants = builder.variants;
+    if let Some(id) = variants.keys().find(|id| !state_ids.contains(id)) {
+        return Err(UnknownStateError(*id));
+    }
+    Ok((TypeId::of::<E>(), Arc::new(variants)))
+}
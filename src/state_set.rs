@@ -0,0 +1,70 @@
+//! Compile-time check of the `States` set of a superstate.
+
+use bevy_ecs::{
+    bundle::Bundle,
+    component::Component,
+    query::{Or, QueryFilter, With},
+};
+
+mod sealed {
+    pub trait Sealed {}
+}
+
+/// Tuple of plain state components, e.g. `(Walking, Running)`,
+/// accepted as `States` of a superstate.
+///
+/// Unlike any [`Bundle`], it cannot contain nested bundles, so
+/// `((Walking, Running), Flying)` is a compile error instead of confusing ids.
+/// Implemented for tuples of up to 15 components: `bevy_ecs` implements [`Bundle`]
+/// and [`Or`] only for tuples of up to 15 elements, and a larger set cannot be
+/// nested. More states can be added with [`crate::register_state`].
+/// A single state is a 1-tuple: `(Walking,)`.
+///
+/// ```compile_fail
+/// # use bevy_ecs::{component::Component, world::World};
+/// # use superstate::{SuperstateInfo, register_hooks};
+/// # #[derive(Default, Component)]
+/// # #[require(SuperstateInfo<Movement>)]
+/// # struct Movement;
+/// # #[derive(Component)]
+/// # #[require(Movement)]
+/// # struct Walking;
+/// # #[derive(Component)]
+/// # #[require(Movement)]
+/// # struct Running;
+/// # #[derive(Component)]
+/// # #[require(Movement)]
+/// # struct Flying;
+/// let mut world = World::new();
+/// register_hooks::<Movement, ((Walking, Running), Flying)>(&mut world);
+/// ```
+///
+/// This trait is sealed and cannot be implemented outside of this crate.
+pub trait StateSet: Bundle + sealed::Sealed {
+    /// `Or<(With<S0>, With<S1>, ...)>` filter of entities with any of the states,
+    /// see [`crate::AnyOfStates`].
+    type AnyOf: QueryFilter;
+}
+
+macro_rules! impl_state_set {
+    ($($state:ident),*) => {
+        impl<$($state: Component),*> sealed::Sealed for ($($state,)*) {}
+        impl<$($state: Component),*> StateSet for ($($state,)*) {
+            type AnyOf = Or<($(With<$state>,)*)>;
+        }
+    };
+}
+
+macro_rules! impl_state_sets {
+    () => {
+        impl_state_set!();
+    };
+    ($head:ident $(, $tail:ident)*) => {
+        impl_state_set!($head $(, $tail)*);
+        impl_state_sets!($($tail),*);
+    };
+}
+
+impl_state_sets!(
+    S0, S1, S2, S3, S4, S5, S6, S7, S8, S9, S10, S11, S12, S13, S14
+);
@@ -0,0 +1,111 @@
+//! Helper for tests driving transitions of superstates.
+
+use std::any::type_name;
+
+use bevy_ecs::{
+    bundle::Bundle, component::Component, entity::Entity, system::Commands, world::World,
+};
+
+use crate::{StateSet, dump_entity_states, register_hooks};
+
+/// [`World`] wrapper for tests, which applies queued commands
+/// before every action and assertion, so hooks have always flushed.
+///
+/// ```
+/// # use bevy_ecs::component::Component;
+/// # use superstate::{SuperstateInfo, testing::SuperstateTestApp};
+/// # #[derive(Default, Component)]
+/// # #[require(SuperstateInfo<Movement>)]
+/// # struct Movement;
+/// # #[derive(Component)]
+/// # #[require(Movement)]
+/// # struct Walking;
+/// # #[derive(Component)]
+/// # #[require(Movement)]
+/// # struct Running;
+/// let mut app = SuperstateTestApp::new();
+/// app.register::<Movement, (Walking, Running)>();
+/// let e = app.spawn_in(Walking);
+/// app.insert(e, Running).assert_in::<Running>(e);
+/// app.remove::<Running>(e).assert_stateless(e);
+/// ```
+#[derive(Default)]
+pub struct SuperstateTestApp {
+    world: World,
+}
+
+impl SuperstateTestApp {
+    /// Creates an empty world.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers hooks of `Super` with `States`, see [`register_hooks`].
+    /// Panics if the registration fails.
+    pub fn register<Super: Component, States: StateSet>(&mut self) -> &mut Self {
+        register_hooks::<Super, States>(&mut self.world).unwrap();
+        self
+    }
+
+    /// Spawns an entity with `bundle`, usually a state.
+    pub fn spawn_in(&mut self, bundle: impl Bundle) -> Entity {
+        self.world.flush();
+        let entity = self.world.spawn(bundle).id();
+        self.world.flush();
+        entity
+    }
+
+    /// Inserts `bundle` into `entity`, e.g. to transition to another state.
+    pub fn insert(&mut self, entity: Entity, bundle: impl Bundle) -> &mut Self {
+        self.world.flush();
+        self.world.entity_mut(entity).insert(bundle);
+        self.world.flush();
+        self
+    }
+
+    /// Removes `B` from `entity`.
+    pub fn remove<B: Bundle>(&mut self, entity: Entity) -> &mut Self {
+        self.world.flush();
+        self.world.entity_mut(entity).remove::<B>();
+        self.world.flush();
+        self
+    }
+
+    /// Commands of the world, applied before the next action or assertion.
+    pub fn commands(&mut self) -> Commands<'_, '_> {
+        self.world.commands()
+    }
+
+    /// Panics if `entity` is not in `State`.
+    #[track_caller]
+    pub fn assert_in<State: Component>(&mut self, entity: Entity) -> &mut Self {
+        self.world.flush();
+        assert!(
+            self.world.get::<State>(entity).is_some(),
+            "{entity} is not in {}, its states: {:?}.",
+            type_name::<State>(),
+            dump_entity_states(&self.world, entity),
+        );
+        self
+    }
+
+    /// Panics if `entity` is in any state of any registered superstate.
+    #[track_caller]
+    pub fn assert_stateless(&mut self, entity: Entity) -> &mut Self {
+        self.world.flush();
+        let states = dump_entity_states(&self.world, entity);
+        assert!(states.is_empty(), "{entity} is in states {states:?}.");
+        self
+    }
+
+    /// The wrapped world.
+    pub fn world(&self) -> &World {
+        &self.world
+    }
+
+    /// The wrapped world, commands queued in it are applied
+    /// before the next action or assertion.
+    pub fn world_mut(&mut self) -> &mut World {
+        &mut self.world
+    }
+}
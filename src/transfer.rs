@@ -0,0 +1,77 @@
+//! Transfer of data from the old state to the new one on transition.
+
+use std::{collections::HashMap, marker::PhantomData};
+
+use bevy_ecs::{
+    component::{Component, ComponentId, Mutable},
+    entity::Entity,
+    query::QueryState,
+    resource::Resource,
+    world::{DeferredWorld, World},
+};
+
+/// State that passes its data to `Target` state when the entity transitions
+/// from it to `Target`, e.g. `Running` inherits `Walking`'s momentum.
+///
+/// Register with [`register_transfer`].
+pub trait TransferState<Target: Component>: Component {
+    /// Called when the entity transitions from `from` to `to`,
+    /// while both states are still on the entity.
+    fn transfer(from: &Self, to: &mut Target);
+}
+
+type TransferFn = fn(&mut DeferredWorld, Entity);
+
+/// Resource with registered transfers between states of `Super`,
+/// used by [`crate::hooks::on_add_hook_state`].
+#[derive(Resource)]
+pub(crate) struct StateTransfers<Super: Component> {
+    transfers: HashMap<(ComponentId, ComponentId), TransferFn>,
+    _p: PhantomData<Super>,
+}
+
+impl<Super: Component> Default for StateTransfers<Super> {
+    fn default() -> Self {
+        Self {
+            transfers: HashMap::new(),
+            _p: PhantomData,
+        }
+    }
+}
+
+impl<Super: Component> StateTransfers<Super> {
+    pub(crate) fn get(world: &World, from: ComponentId, to: ComponentId) -> Option<TransferFn> {
+        world
+            .get_resource::<Self>()
+            .and_then(|res| res.transfers.get(&(from, to)).copied())
+    }
+}
+
+/// Registers [`TransferState`] from `From` to `To` state of `Super`.
+/// Transitions without registered transfer do not cost anything.
+pub fn register_transfer<Super, From, To>(world: &mut World)
+where
+    Super: Component,
+    From: TransferState<To>,
+    To: Component<Mutability = Mutable>,
+{
+    let from = world.register_component::<From>();
+    let to = world.register_component::<To>();
+    world
+        .get_resource_or_init::<StateTransfers<Super>>()
+        .transfers
+        .insert((from, to), transfer::<From, To>);
+}
+
+fn transfer<From, To>(world: &mut DeferredWorld, entity: Entity)
+where
+    From: TransferState<To>,
+    To: Component<Mutability = Mutable>,
+{
+    let Some(mut state) = QueryState::<(&From, &mut To)>::try_new(world) else {
+        return;
+    };
+    if let Ok((from, mut to)) = world.query(&mut state).get_mut(entity) {
+        From::transfer(from, &mut to);
+    }
+}
@@ -0,0 +1,324 @@
+use std::{collections::HashSet, marker::PhantomData, time::Duration};
+
+use bevy_ecs::{
+    change_detection::DetectChangesMut,
+    component::{Component, ComponentId},
+    entity::Entity,
+    resource::Resource,
+    world::{DeferredWorld, EntityWorldMut, World},
+};
+#[cfg(feature = "bevy_time")]
+use bevy_time::Time;
+
+use crate::{SuperstateInfo, SuperstateRegistry, resolve_state_id};
+
+/// Resource with allowed transitions between states of `Super`.
+///
+/// When the resource exists, [`crate::hooks::on_add_hook_state`] checks
+/// every change of the active state against it. If the edge is not allowed,
+/// the newly added state is removed, the old one stays on the entity,
+/// and the rejected transition is logged with `warn!`.
+///
+/// Adding the first state (when there is no active state) and re-adding
+/// the active state are always allowed.
+///
+/// When the resource does not exist, any transition is allowed.
+#[derive(Resource, Debug, Clone)]
+pub struct TransitionTable<Super: Component> {
+    edges: HashSet<(ComponentId, ComponentId)>,
+    _p: PhantomData<Super>,
+}
+
+impl<Super: Component> Default for TransitionTable<Super> {
+    fn default() -> Self {
+        Self {
+            edges: HashSet::new(),
+            _p: PhantomData,
+        }
+    }
+}
+
+impl<Super: Component> TransitionTable<Super> {
+    /// Creates a [`TransitionTableBuilder`] which registers state components in the `world`.
+    pub fn builder(world: &mut World) -> TransitionTableBuilder<'_, Super> {
+        TransitionTableBuilder {
+            world,
+            table: Self::default(),
+        }
+    }
+
+    /// Allow transition from state with `from` id to state with `to` id.
+    pub fn allow_by_id(&mut self, from: ComponentId, to: ComponentId) -> &mut Self {
+        self.edges.insert((from, to));
+        self
+    }
+
+    /// Disallow previously allowed transition.
+    pub fn disallow_by_id(&mut self, from: ComponentId, to: ComponentId) -> &mut Self {
+        self.edges.remove(&(from, to));
+        self
+    }
+
+    /// Returns `true` if the transition is allowed.
+    /// Transition to the same state is always allowed.
+    pub fn is_allowed(&self, from: ComponentId, to: ComponentId) -> bool {
+        from == to || self.edges.contains(&(from, to))
+    }
+
+    /// Iterator over all allowed `(from, to)` edges.
+    pub fn edges(&self) -> impl Iterator<Item = (ComponentId, ComponentId)> + '_ {
+        self.edges.iter().copied()
+    }
+
+    /// Returns `true` if there is no [`TransitionTable`] resource
+    /// or the transition is allowed by it.
+    pub(crate) fn allows(world: &World, from: ComponentId, to: ComponentId) -> bool {
+        world
+            .get_resource::<Self>()
+            .is_none_or(|table| table.is_allowed(from, to))
+    }
+}
+
+/// Builder of [`TransitionTable`].
+///
+/// ```
+/// # use bevy_ecs::{component::Component, world::World};
+/// # use superstate::{register_hooks, SuperstateInfo, TransitionTable};
+/// # #[derive(Default, Component)]
+/// # #[require(SuperstateInfo<Movement>)]
+/// # struct Movement;
+/// # #[derive(Component)]
+/// # #[require(Movement)]
+/// # struct Walking;
+/// # #[derive(Component)]
+/// # #[require(Movement)]
+/// # struct Running;
+/// let mut world = World::new();
+/// register_hooks::<Movement, (Walking, Running)>(&mut world).unwrap();
+/// let table = TransitionTable::<Movement>::builder(&mut world)
+///     .allow::<Walking, Running>()
+///     .build();
+/// world.insert_resource(table);
+/// ```
+pub struct TransitionTableBuilder<'w, Super: Component> {
+    world: &'w mut World,
+    table: TransitionTable<Super>,
+}
+
+impl<Super: Component> TransitionTableBuilder<'_, Super> {
+    /// Allow transition from `From` state to `To` state.
+    pub fn allow<From: Component, To: Component>(mut self) -> Self {
+        let from = self.world.register_component::<From>();
+        let to = self.world.register_component::<To>();
+        self.table.allow_by_id(from, to);
+        self
+    }
+
+    /// Allow transitions in both directions between `A` and `B` states.
+    pub fn allow_both<A: Component, B: Component>(self) -> Self {
+        self.allow::<A, B>().allow::<B, A>()
+    }
+
+    /// Allow transition from state with `from` id to state with `to` id.
+    pub fn allow_by_id(mut self, from: ComponentId, to: ComponentId) -> Self {
+        self.table.allow_by_id(from, to);
+        self
+    }
+
+    /// Returns the built table. Insert it into the world as a resource.
+    pub fn build(self) -> TransitionTable<Super> {
+        self.table
+    }
+}
+
+type GuardFn = dyn Fn(&DeferredWorld, Entity, ComponentId, ComponentId) -> bool + Send + Sync;
+
+/// Resource with a predicate deciding whether an entity may change
+/// its state of `Super` from one state to another at the moment.
+/// Inserted by [`crate::register_hooks_with_guard`], and can be replaced
+/// at runtime by inserting a new one.
+///
+/// Like [`TransitionTable`], the guard is checked by [`crate::hooks::on_add_hook_state`]
+/// only when the active state changes. If it returns `false`, the newly added state
+/// is removed and the old one stays on the entity.
+///
+/// The guard runs inside a component hook, so it has only read access to the world:
+/// the entity already has the new state component, the old state is not yet removed,
+/// and commands queued by other hooks are not yet applied.
+/// It must not panic on missing components of the entity.
+#[derive(Resource)]
+pub struct TransitionGuard<Super: Component> {
+    guard: Box<GuardFn>,
+    _p: PhantomData<Super>,
+}
+
+impl<Super: Component> TransitionGuard<Super> {
+    /// Creates a guard from a predicate taking the entity,
+    /// the id of the current state and the id of the new one.
+    pub fn new(
+        guard: impl Fn(&DeferredWorld, Entity, ComponentId, ComponentId) -> bool + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            guard: Box::new(guard),
+            _p: PhantomData,
+        }
+    }
+
+    /// Returns `true` if there is no [`TransitionGuard`] resource
+    /// or the transition is allowed by it.
+    pub(crate) fn allows(
+        world: &DeferredWorld,
+        entity: Entity,
+        from: ComponentId,
+        to: ComponentId,
+    ) -> bool {
+        world
+            .get_resource::<Self>()
+            .is_none_or(|guard| (guard.guard)(world, entity, from, to))
+    }
+}
+
+/// Component which freezes the state of `Super` of the entity, e.g. during a cutscene.
+/// While it is present, any new state of `Super` is removed and the current one stays,
+/// as with a [`TransitionGuard`] returning `false`.
+///
+/// Dropped transitions trigger [`crate::event::TransitionRejected`]
+/// with [`RejectReason::Locked`], but are not logged.
+/// Entering the first state is not blocked.
+///
+/// Insert and remove with [`crate::SuperstateCommands::lock_state`]
+/// and [`crate::SuperstateCommands::unlock_state`].
+#[derive(Component, Debug)]
+pub struct StateLock<Super: Component> {
+    _p: PhantomData<Super>,
+}
+
+impl<Super: Component> Default for StateLock<Super> {
+    fn default() -> Self {
+        Self { _p: PhantomData }
+    }
+}
+
+/// Cause of a transition rejected by [`crate::hooks::on_add_hook_state`],
+/// see [`crate::event::TransitionRejected`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RejectReason {
+    /// The edge is not allowed by [`TransitionTable`].
+    Table,
+    /// [`TransitionGuard`] returned `false`.
+    Guard,
+    /// The current state has not been active for its minimum dwell time,
+    /// see [`crate::SuperstateBuilder::min_dwell`].
+    MinDwell(Duration),
+    /// The entity has [`StateLock`].
+    Locked,
+}
+
+/// Checks the transition of `entity` with `info` from `current` to `to` state
+/// against [`StateLock`], [`TransitionTable`], [`TransitionGuard`] and minimum dwell time.
+/// Shared by the hook and [`can_transition`], so they never diverge.
+pub(crate) fn check_transition<Super: Component>(
+    world: &DeferredWorld,
+    entity: Entity,
+    info: &SuperstateInfo<Super>,
+    current: ComponentId,
+    to: ComponentId,
+) -> Result<(), RejectReason> {
+    if current != to
+        && world
+            .get_entity(entity)
+            .is_ok_and(|entity| entity.contains::<StateLock<Super>>())
+    {
+        return Err(RejectReason::Locked);
+    }
+    if !TransitionTable::<Super>::allows(world, current, to) {
+        return Err(RejectReason::Table);
+    }
+    if current != to && !TransitionGuard::<Super>::allows(world, entity, current, to) {
+        return Err(RejectReason::Guard);
+    }
+    #[cfg(feature = "bevy_time")]
+    if current != to
+        && info.current_state() == Some(current)
+        && let Some(min_dwell) = world
+            .get_resource::<SuperstateRegistry<Super>>()
+            .and_then(|registry| registry.min_dwell(current))
+        && let Some(time) = world.get_resource::<Time>()
+        && info.time_in_state(time) < min_dwell
+    {
+        return Err(RejectReason::MinDwell(min_dwell));
+    }
+    #[cfg(not(feature = "bevy_time"))]
+    let _ = info;
+    Ok(())
+}
+
+/// Returns `true` if inserting `To` state of `Super` into `entity`
+/// would be accepted right now, e.g. to gray out unavailable actions in UI.
+/// Checks the same [`StateLock`], [`TransitionTable`], [`TransitionGuard`] and minimum dwell time
+/// as [`crate::hooks::on_add_hook_state`], without changing anything.
+///
+/// Entering the first state is always allowed, and so is entering a state
+/// of a machine registered with [`crate::register_parallel_hooks`].
+/// Returns `false` if the entity does not exist or `To` is not a state of `Super`.
+///
+/// Takes `&mut World` only because the guard reads a [`DeferredWorld`].
+pub fn can_transition<Super: Component, To: Component>(world: &mut World, entity: Entity) -> bool {
+    let Some(to) = resolve_state_id::<To>(world) else {
+        return false;
+    };
+    let world = DeferredWorld::from(world);
+    let Some(registry) = world.get_resource::<SuperstateRegistry<Super>>() else {
+        return false;
+    };
+    let registered = registry.state_ids().contains(&to);
+    let parallel = registry.parallel;
+    let Ok(entity_ref) = world.get_entity(entity) else {
+        return false;
+    };
+    let Some(info) = entity_ref.get::<SuperstateInfo<Super>>() else {
+        return registered;
+    };
+    // Parallel regions are entered without the transition checks.
+    if parallel {
+        return registered;
+    }
+    match info.current_state() {
+        Some(current) => registered && check_transition(&world, entity, info, current, to).is_ok(),
+        None => registered,
+    }
+}
+
+/// Inserts `state` of `Super` into `entity` skipping [`StateLock`], [`TransitionTable`],
+/// [`TransitionGuard`] and minimum dwell time, e.g. to restore a save or in a cheat console.
+///
+/// This intentionally violates the transition rules, so it is meant for tools and tests.
+/// Only this insert is forced: the old state is still removed,
+/// [`crate::TransferState`] and [`crate::StateEntered`] still run,
+/// and later transitions are checked as usual.
+/// Does nothing if the entity does not exist.
+/// See also [`crate::SuperstateCommands::force_state`].
+pub fn force_state<Super: Component, State: Component>(
+    world: &mut World,
+    entity: Entity,
+    state: State,
+) {
+    if let Ok(entity) = world.get_entity_mut(entity) {
+        force_insert::<Super, State>(entity, state);
+    }
+}
+
+pub(crate) fn force_insert<Super: Component, State: Component>(
+    mut entity: EntityWorldMut,
+    state: State,
+) {
+    let id = entity.world_scope(|world: &mut World| world.register_component::<State>());
+    // The flag is read by the hook during the insert, so it is set only around it.
+    if let Some(mut info) = entity.get_mut::<SuperstateInfo<Super>>() {
+        info.bypass_change_detection().forced = Some(id);
+    }
+    entity.insert(state);
+    if let Some(mut info) = entity.get_mut::<SuperstateInfo<Super>>() {
+        info.bypass_change_detection().forced = None;
+    }
+}
@@ -0,0 +1,88 @@
+//! Checks of the invariants kept by the hooks, for development builds.
+
+use bevy_ecs::{
+    component::Component,
+    query::With,
+    system::Query,
+    world::{EntityRef, World},
+};
+
+use crate::{StateSet, SuperstateHooks, SuperstateInfo, SuperstateRegistry};
+
+/// System panicking if an entity with `Super` has several states of `Super`,
+/// or its state does not match [`SuperstateInfo::current_state`],
+/// naming the entity and its states.
+/// For [`crate::register_parallel_hooks`] checks that the states
+/// match [`SuperstateInfo::active_states`] instead.
+///
+/// Added by [`SuperstateBuilder::build`](crate::SuperstateBuilder::build)
+/// to `PostUpdate` schedule when `debug_assertions` are on,
+/// so release builds do not pay for it. Skipped while the hooks are disabled.
+pub fn validate_invariants<Super: Component, States: StateSet>(
+    world: &World,
+    entities: Query<EntityRef, With<Super>>,
+) {
+    if !SuperstateHooks::<Super, States>::enabled(world) {
+        return;
+    }
+    let Some(registry) = world.get_resource::<SuperstateRegistry<Super>>() else {
+        return;
+    };
+    let components = world.components();
+    let name = |id| components.get_name(id).unwrap_or_default();
+    for entity in &entities {
+        let states = registry
+            .state_ids()
+            .iter()
+            .copied()
+            .filter(|id| entity.contains_id(*id))
+            .collect::<Vec<_>>();
+        if registry.is_parallel() {
+            let active = entity
+                .get::<SuperstateInfo<Super>>()
+                .map_or(&[][..], SuperstateInfo::active_states);
+            if states.len() != active.len() || states.iter().any(|id| !active.contains(id)) {
+                panic!(
+                    "{} is in states {} of {}, but its info has {}.",
+                    entity.id(),
+                    states
+                        .iter()
+                        .map(|id| name(*id))
+                        .collect::<Vec<_>>()
+                        .join(", "),
+                    name(registry.super_id()),
+                    active
+                        .iter()
+                        .map(|id| name(*id))
+                        .collect::<Vec<_>>()
+                        .join(", "),
+                );
+            }
+            continue;
+        }
+        if states.len() > 1 {
+            panic!(
+                "{} has several states of {}: {}.",
+                entity.id(),
+                name(registry.super_id()),
+                states
+                    .iter()
+                    .map(|id| name(*id))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            );
+        }
+        let current = entity
+            .get::<SuperstateInfo<Super>>()
+            .and_then(SuperstateInfo::current_state);
+        if states.first().copied() != current {
+            panic!(
+                "{} is in state {} of {}, but its info has {}.",
+                entity.id(),
+                states.first().map_or("None".into(), |id| name(*id)),
+                name(registry.super_id()),
+                current.map_or("None".into(), name),
+            );
+        }
+    }
+}
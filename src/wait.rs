@@ -0,0 +1,216 @@
+//! Futures resolving when an entity enters a state, e.g. for cutscene scripts.
+//! Requires `async` feature.
+
+use std::{
+    collections::HashMap,
+    error::Error,
+    fmt::Display,
+    future::Future,
+    marker::PhantomData,
+    pin::Pin,
+    sync::{Arc, Mutex},
+    task::{Context, Poll, Waker},
+};
+
+use bevy_ecs::{
+    component::{Component, ComponentId},
+    entity::Entity,
+    error::BevyError,
+    resource::Resource,
+    world::{DeferredWorld, World},
+};
+
+use crate::{
+    SuperstateInfo, SuperstateRegistry,
+    hooks::{NotRegisteredError, UnknownStateError},
+};
+
+/// Returned by [`WaitForState`] when the state can no longer be entered:
+/// `Super` was removed from the entity, or the entity was despawned.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WaitCancelled(pub Entity);
+
+impl Display for WaitCancelled {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} left its superstate before entering the state.",
+            self.0
+        )
+    }
+}
+
+impl Error for WaitCancelled {}
+
+#[derive(Default)]
+struct Slot {
+    result: Option<Result<(), WaitCancelled>>,
+    waker: Option<Waker>,
+}
+
+/// Future returned by [`wait_for_state`].
+pub struct WaitForState {
+    slot: Arc<Mutex<Slot>>,
+}
+
+impl Future for WaitForState {
+    type Output = Result<(), WaitCancelled>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let mut slot = self.slot.lock().unwrap_or_else(|e| e.into_inner());
+        match slot.result.take() {
+            Some(result) => Poll::Ready(result),
+            None => {
+                slot.waker = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+}
+
+/// Sending half of [`WaitForState`], kept by [`StateWaiters`].
+struct Waiter {
+    state: ComponentId,
+    slot: Arc<Mutex<Slot>>,
+}
+
+impl Waiter {
+    fn resolve(self, result: Result<(), WaitCancelled>) {
+        let mut slot = self.slot.lock().unwrap_or_else(|e| e.into_inner());
+        slot.result = Some(result);
+        if let Some(waker) = slot.waker.take() {
+            waker.wake();
+        }
+    }
+
+    /// Returns `true` if the future was dropped, so nobody awaits the result.
+    fn is_dropped(&self) -> bool {
+        Arc::strong_count(&self.slot) == 1
+    }
+}
+
+/// Resource with pending [`WaitForState`] futures of `Super`,
+/// resolved by the hooks.
+#[derive(Resource)]
+pub(crate) struct StateWaiters<Super: Component> {
+    waiters: HashMap<Entity, Vec<Waiter>>,
+    _p: PhantomData<Super>,
+}
+
+impl<Super: Component> Default for StateWaiters<Super> {
+    fn default() -> Self {
+        Self {
+            waiters: HashMap::new(),
+            _p: PhantomData,
+        }
+    }
+}
+
+impl<Super: Component> StateWaiters<Super> {
+    /// Resolves futures waiting for `entity` to enter the state with `id`.
+    pub(crate) fn entered(world: &mut DeferredWorld, entity: Entity, id: ComponentId) {
+        let Some(mut waiters) = world.get_resource_mut::<Self>() else {
+            return;
+        };
+        let Some(pending) = waiters.waiters.get_mut(&entity) else {
+            return;
+        };
+        let (ready, rest) = pending
+            .drain(..)
+            .filter(|waiter| !waiter.is_dropped())
+            .partition::<Vec<_>, _>(|waiter| waiter.state == id);
+        if rest.is_empty() {
+            waiters.waiters.remove(&entity);
+        } else {
+            *pending = rest;
+        }
+        for waiter in ready {
+            waiter.resolve(Ok(()));
+        }
+    }
+
+    /// Cancels all futures waiting for `entity`.
+    pub(crate) fn cancel(world: &mut DeferredWorld, entity: Entity) {
+        let Some(pending) = world
+            .get_resource_mut::<Self>()
+            .and_then(|mut waiters| waiters.waiters.remove(&entity))
+        else {
+            return;
+        };
+        for waiter in pending {
+            waiter.resolve(Err(WaitCancelled(entity)));
+        }
+    }
+}
+
+/// Returns a future resolving when `entity` enters `State` of `Super`,
+/// to `.await` it in async tasks, e.g. scripted sequences.
+/// Resolves immediately if the entity is already in `State`.
+///
+/// Resolved by [`crate::hooks::on_add_hook_state`] on the transition, so it is ready
+/// right after the command flush that entered the state, and can be polled
+/// by any executor, e.g. of `bevy_tasks`.
+///
+/// Cancellation: if `Super` is removed from the entity, including when the entity
+/// is despawned, the future resolves with [`WaitCancelled`] instead of waiting forever.
+/// Dropping the future stops waiting, its entry is cleaned up by the next transition.
+///
+/// Returns [`NotRegisteredError`] if `Super` is not registered,
+/// or [`UnknownStateError`] if `State` is not one of its states.
+///
+/// ```
+/// # use std::{pin::pin, task::{Context, Poll, Waker}};
+/// # use bevy_ecs::{component::Component, world::World};
+/// # use superstate::{SuperstateInfo, register_hooks, wait_for_state};
+/// # #[derive(Default, Component)]
+/// # #[require(SuperstateInfo<Movement>)]
+/// # struct Movement;
+/// # #[derive(Component)]
+/// # #[require(Movement)]
+/// # struct Walking;
+/// # #[derive(Component)]
+/// # #[require(Movement)]
+/// # struct Running;
+/// let mut world = World::new();
+/// register_hooks::<Movement, (Walking, Running)>(&mut world).unwrap();
+/// let e = world.spawn(Walking).id();
+/// let mut running = pin!(wait_for_state::<Movement, Running>(&mut world, e).unwrap());
+/// let mut cx = Context::from_waker(Waker::noop());
+/// assert!(running.as_mut().poll(&mut cx).is_pending());
+/// world.entity_mut(e).insert(Running);
+/// assert_eq!(running.as_mut().poll(&mut cx), Poll::Ready(Ok(())));
+/// ```
+pub fn wait_for_state<Super: Component, State: Component>(
+    world: &mut World,
+    entity: Entity,
+) -> Result<WaitForState, BevyError> {
+    let id = world.register_component::<State>();
+    let Some(registry) = world.get_resource::<SuperstateRegistry<Super>>() else {
+        return Err(NotRegisteredError.into());
+    };
+    if !registry.state_ids().contains(&id) {
+        return Err(UnknownStateError(id).into());
+    }
+    let slot = Arc::new(Mutex::new(Slot::default()));
+    let waiter = Waiter {
+        state: id,
+        slot: slot.clone(),
+    };
+    match world.get_entity(entity) {
+        Err(_) => waiter.resolve(Err(WaitCancelled(entity))),
+        Ok(entity)
+            if entity
+                .get::<SuperstateInfo<Super>>()
+                .is_some_and(|info| info.active_states().contains(&id)) =>
+        {
+            waiter.resolve(Ok(()));
+        }
+        Ok(_) => world
+            .get_resource_or_init::<StateWaiters<Super>>()
+            .waiters
+            .entry(entity)
+            .or_default()
+            .push(waiter),
+    }
+    Ok(WaitForState { slot })
+}
@@ -0,0 +1,881 @@
+#![cfg(feature = "bevy_app")]
+
+use bevy_app::{App, PostUpdate, Update};
+use bevy_ecs::{
+    change_detection::DetectChangesMut,
+    component::{Component, HookContext},
+    entity::Entity,
+    event::{Event, EventReader},
+    hierarchy::ChildOf,
+    observer::Trigger,
+    query::With,
+    resource::Resource,
+    schedule::{IntoScheduleConfigs, SystemSet},
+    system::{Query, ResMut},
+    world::DeferredWorld,
+};
+use superstate::{
+    ConflictPolicy, CurrentState, HookOrder, StateEntered, StateEnum, StateEnumBuilder,
+    StateExited, StateInput, StateLabel, StateLock, StateScoped, SuperScoped, SuperScopedEntities,
+    SuperstateAppExt, SuperstateBuilder, SuperstateCommands, SuperstateEntered, SuperstateExited,
+    SuperstateInfo, SuperstatePlugin, SuperstateRegistry, SuperstateSet, TransitionRequest,
+    condition::in_any_entity_state, deregister_hooks, dump_entity_states, lazy_superstate_plugin,
+    register_hooks_with_order, register_state_constructor, superstate_plugin, superstate_plugins,
+};
+
+#[derive(Default, Component)]
+#[require(SuperstateInfo<Movement>)]
+struct Movement;
+
+#[derive(Component)]
+#[require(Movement)]
+struct Walking;
+
+#[derive(Component)]
+#[require(Movement)]
+struct Running;
+
+#[derive(Resource, Default)]
+struct Runs {
+    entered: u32,
+    exited: u32,
+}
+
+#[test]
+fn enter_exit() {
+    let mut app = App::new();
+    app.init_resource::<Runs>()
+        .add_plugins(superstate_plugin::<Movement, (Walking, Running)>)
+        .on_enter_state::<Movement, Walking, _>(|mut runs: ResMut<Runs>| runs.entered += 1)
+        .on_exit_state::<Movement, Walking, _>(|mut runs: ResMut<Runs>| runs.exited += 1);
+    let e = app.world_mut().spawn(Walking).id();
+    app.update();
+    app.update();
+    assert_eq!(app.world().resource::<Runs>().entered, 1);
+    assert_eq!(app.world().resource::<Runs>().exited, 0);
+    app.world_mut().entity_mut(e).insert(Running);
+    app.update();
+    app.update();
+    assert_eq!(app.world().resource::<Runs>().entered, 1);
+    assert_eq!(app.world().resource::<Runs>().exited, 1);
+    // Other changes of the info are not exits.
+    app.world_mut()
+        .get_mut::<SuperstateInfo<Movement>>(e)
+        .unwrap()
+        .set_changed();
+    app.update();
+    assert_eq!(app.world().resource::<Runs>().exited, 1);
+}
+
+#[test]
+fn enter_parallel() {
+    let mut app = App::new();
+    SuperstateBuilder::<Movement, (Walking, Running)>::new()
+        .parallel()
+        .build(&mut app)
+        .unwrap();
+    app.init_resource::<Runs>()
+        .on_enter_state::<Movement, Walking, _>(|mut runs: ResMut<Runs>| runs.entered += 1);
+    let e = app.world_mut().spawn(Running).id();
+    app.update();
+    app.world_mut().entity_mut(e).insert(Walking);
+    app.update();
+    assert_eq!(app.world().resource::<Runs>().entered, 1);
+    // Walking stays active while the other region leaves, so it is not entered again.
+    app.world_mut().entity_mut(e).remove::<Running>();
+    app.update();
+    assert!(app.world().entity(e).contains::<Walking>());
+    assert_eq!(app.world().resource::<Runs>().entered, 1);
+    app.world_mut().entity_mut(e).insert(Running);
+    app.update();
+    assert_eq!(app.world().resource::<Runs>().entered, 1);
+}
+
+#[test]
+fn exit_allowing_empty() {
+    #[derive(Resource, Default)]
+    struct Exits(Vec<&'static str>);
+
+    let mut app = App::new();
+    SuperstateBuilder::<Movement, (Walking, Running)>::new()
+        .allow_empty()
+        .build(&mut app)
+        .unwrap();
+    app.init_resource::<Exits>()
+        .on_exit_state::<Movement, Walking, _>(|mut exits: ResMut<Exits>| exits.0.push("walking"))
+        .on_exit_state::<Movement, Running, _>(|mut exits: ResMut<Exits>| exits.0.push("running"));
+    let e = app.world_mut().spawn(Walking).id();
+    app.update();
+    app.world_mut().entity_mut(e).insert(Running);
+    app.update();
+    assert_eq!(app.world().resource::<Exits>().0, ["walking"]);
+    app.world_mut().entity_mut(e).remove::<Running>();
+    app.update();
+    assert!(app.world().entity(e).contains::<Movement>());
+    assert_eq!(app.world().resource::<Exits>().0, ["walking", "running"]);
+    app.world_mut().entity_mut(e).insert(Walking);
+    app.update();
+    assert_eq!(app.world().resource::<Exits>().0, ["walking", "running"]);
+    app.world_mut().entity_mut(e).remove::<Movement>();
+    app.update();
+    assert_eq!(
+        app.world().resource::<Exits>().0,
+        ["walking", "running", "walking"]
+    );
+}
+
+#[test]
+fn on_transition() {
+    let mut app = App::new();
+    app.init_resource::<Runs>()
+        .add_plugins(superstate_plugin::<Movement, (Walking, Running)>)
+        .on_transition::<Movement, Walking, Running, _>(|mut runs: ResMut<Runs>| runs.entered += 1);
+    let e = app.world_mut().spawn(Running).id();
+    app.update();
+    app.world_mut().entity_mut(e).insert(Walking);
+    app.update();
+    assert_eq!(app.world().resource::<Runs>().entered, 0);
+    app.world_mut().entity_mut(e).insert(Running);
+    app.update();
+    app.update();
+    assert_eq!(app.world().resource::<Runs>().entered, 1);
+    // Other changes of the info are not transitions.
+    app.world_mut()
+        .get_mut::<SuperstateInfo<Movement>>(e)
+        .unwrap()
+        .set_changed();
+    app.update();
+    assert_eq!(app.world().resource::<Runs>().entered, 1);
+}
+
+#[test]
+fn current_state_component() {
+    let mut app = App::new();
+    SuperstateBuilder::<Movement, (Walking, Running)>::new()
+        .current_state_component()
+        .build(&mut app)
+        .unwrap();
+    let world = app.world_mut();
+    let walking = world.component_id::<Walking>().unwrap();
+    let running = world.component_id::<Running>().unwrap();
+    let e = world.spawn(Walking).id();
+    assert!(world.get::<CurrentState<Movement>>(e).is_none());
+    app.update();
+    let current = app.world().get::<CurrentState<Movement>>(e).unwrap();
+    assert_eq!(current.id(), walking);
+    app.world_mut().entity_mut(e).insert(Running);
+    app.update();
+    let current = app.world().get::<CurrentState<Movement>>(e).unwrap();
+    assert_eq!(current.id(), running);
+    app.world_mut().entity_mut(e).remove::<Running>();
+    app.update();
+    assert!(app.world().get::<CurrentState<Movement>>(e).is_none());
+}
+
+#[test]
+fn state_label() {
+    let mut app = App::new();
+    SuperstateBuilder::<Movement, (Walking, Running)>::new()
+        .state_label()
+        .build(&mut app)
+        .unwrap();
+    let e = app.world_mut().spawn(Walking).id();
+    app.update();
+    let label = app.world().get::<StateLabel<Movement>>(e).unwrap();
+    assert_eq!(label.as_str(), "Movement::Walking");
+    app.world_mut().entity_mut(e).insert(Running);
+    app.update();
+    let label = app.world().get::<StateLabel<Movement>>(e).unwrap();
+    assert_eq!(label.to_string(), "Movement::Running");
+    app.world_mut().entity_mut(e).remove::<Running>();
+    app.update();
+    assert!(app.world().get::<StateLabel<Movement>>(e).is_none());
+}
+
+#[test]
+fn require_superstate() {
+    let mut app = App::new();
+    app.add_plugins(superstate_plugin::<Movement, (Walking, Running)>)
+        .require_superstate::<Movement, (Walking, Running)>();
+}
+
+#[test]
+#[should_panic(expected = "is required, but not registered")]
+fn require_missing_superstate() {
+    App::new().require_superstate::<Movement, (Walking, Running)>();
+}
+
+#[test]
+fn register_twice() {
+    let mut app = App::new();
+    SuperstateBuilder::<Movement, (Walking, Running)>::new()
+        .conflict_policy(ConflictPolicy::KeepFirst)
+        .current_state_component()
+        .build(&mut app)
+        .unwrap();
+    fn first_plugin(app: &mut App) {
+        superstate_plugin::<Movement, (Walking, Running)>(app);
+    }
+    fn second_plugin(app: &mut App) {
+        superstate_plugin::<Movement, (Walking, Running)>(app);
+    }
+    app.add_plugins((first_plugin, second_plugin));
+    let e = app.world_mut().spawn((Walking, Running)).id();
+    app.update();
+    assert!(app.world().entity(e).contains::<Walking>());
+    assert!(!app.world().entity(e).contains::<Running>());
+    assert!(app.world().entity(e).contains::<CurrentState<Movement>>());
+}
+
+#[test]
+fn rebuild_after_deregister() {
+    let mut app = App::new();
+    superstate_plugin::<Movement, (Walking, Running)>(&mut app);
+    deregister_hooks::<Movement, (Walking, Running)>(app.world_mut()).unwrap();
+    SuperstateBuilder::<Movement, (Walking, Running)>::new()
+        .current_state_component()
+        .state_label()
+        .build(&mut app)
+        .unwrap();
+    let e = app.world_mut().spawn(Walking).id();
+    app.update();
+    assert!(app.world().entity(e).contains::<CurrentState<Movement>>());
+    assert!(app.world().entity(e).contains::<StateLabel<Movement>>());
+}
+
+#[derive(Default, Component)]
+#[require(SuperstateInfo<Mode>)]
+struct Mode;
+
+#[derive(Component)]
+#[require(Mode)]
+struct Explore;
+
+#[derive(Default, Component)]
+#[require(Mode, SuperstateInfo<Combat>)]
+struct Combat;
+
+#[derive(Component)]
+#[require(Combat)]
+struct Melee;
+
+#[derive(Component)]
+#[require(Combat)]
+struct Ranged;
+
+#[test]
+fn in_any_entity_state_condition() {
+    let mut app = App::new();
+    app.init_resource::<Runs>()
+        .add_plugins(superstate_plugin::<Movement, (Walking, Running)>)
+        .add_systems(
+            Update,
+            (|mut runs: ResMut<Runs>| runs.entered += 1)
+                .run_if(in_any_entity_state::<Movement, Running>),
+        );
+    app.update();
+    let e = app.world_mut().spawn(Walking).id();
+    app.update();
+    assert_eq!(app.world().resource::<Runs>().entered, 0);
+    app.world_mut().spawn(Running);
+    app.world_mut().entity_mut(e).insert(Running);
+    app.update();
+    app.update();
+    assert_eq!(app.world().resource::<Runs>().entered, 2);
+}
+
+#[test]
+fn state_scoped() {
+    let mut app = App::new();
+    app.init_resource::<Runs>()
+        .add_plugins(superstate_plugin::<Movement, (Walking, Running)>)
+        .on_exit_state::<Movement, Walking, _>(
+            |scoped: Query<(), With<StateScoped<Movement, Walking>>>, mut runs: ResMut<Runs>| {
+                assert!(!scoped.is_empty());
+                runs.exited += 1;
+            },
+        );
+    let world = app.world_mut();
+    let e = world.spawn(Walking).id();
+    let scoped = world
+        .spawn((StateScoped::<Movement, Walking>::default(), ChildOf(e)))
+        .id();
+    let other = world
+        .spawn((StateScoped::<Movement, Running>::default(), ChildOf(e)))
+        .id();
+    let child = world.spawn(ChildOf(e)).id();
+    app.update();
+    app.world_mut().entity_mut(e).insert(Walking);
+    app.update();
+    assert!(app.world().get_entity(scoped).is_ok());
+    // Running is inserted and removed again, the entity never leaves Walking.
+    app.world_mut()
+        .entity_mut(e)
+        .insert(StateLock::<Movement>::default());
+    app.world_mut().entity_mut(e).insert(Running);
+    app.update();
+    assert!(app.world().entity(e).contains::<Walking>());
+    assert!(app.world().get_entity(other).is_ok());
+    app.world_mut()
+        .entity_mut(e)
+        .remove::<StateLock<Movement>>();
+    app.world_mut().entity_mut(e).insert(Running);
+    app.update();
+    assert_eq!(app.world().resource::<Runs>().exited, 1);
+    assert!(app.world().get_entity(scoped).is_err());
+    assert!(app.world().get_entity(other).is_ok());
+    assert!(app.world().get_entity(child).is_ok());
+}
+
+#[test]
+fn super_scoped() {
+    let mut app = App::new();
+    app.add_plugins(superstate_plugin::<Movement, (Walking, Running)>);
+    let world = app.world_mut();
+    let e = world.spawn(Walking).id();
+    let helper = world.spawn(SuperScoped::<Movement>::new(e)).id();
+    assert_eq!(
+        world.get::<SuperScoped<Movement>>(helper).unwrap().host(),
+        e
+    );
+    assert_eq!(
+        world
+            .get::<SuperScopedEntities<Movement>>(e)
+            .unwrap()
+            .entities(),
+        [helper]
+    );
+    app.update();
+    app.world_mut().entity_mut(e).insert(Running);
+    app.update();
+    assert!(app.world().get_entity(helper).is_ok());
+    app.world_mut().entity_mut(e).remove::<Movement>();
+    app.update();
+    assert!(app.world().get_entity(helper).is_err());
+    assert!(app.world().get_entity(e).is_ok());
+
+    let world = app.world_mut();
+    let e = world.spawn(Walking).id();
+    let helper = world.spawn(SuperScoped::<Movement>::new(e)).id();
+    world.despawn(e);
+    assert!(world.get_entity(helper).is_err());
+}
+
+#[test]
+fn hierarchical() {
+    let mut app = App::new();
+    app.add_plugins(superstate_plugin::<Mode, (Explore, Combat)>)
+        .add_plugins(superstate_plugin::<Combat, (Melee, Ranged)>);
+    let world = app.world_mut();
+    let e = world.spawn(Melee).id();
+    assert!(world.entity(e).contains::<Mode>());
+    assert!(world.entity(e).contains::<Combat>());
+    world.entity_mut(e).insert(Ranged);
+    assert!(!world.entity(e).contains::<Melee>());
+    assert!(world.entity(e).contains::<Combat>());
+    world.entity_mut(e).insert(Explore);
+    assert!(world.entity(e).contains::<Mode>());
+    assert!(!world.entity(e).contains::<Combat>());
+    assert!(!world.entity(e).contains::<Ranged>());
+    world.entity_mut(e).insert(Melee);
+    assert!(!world.entity(e).contains::<Explore>());
+    assert!(world.entity(e).contains::<Combat>());
+}
+
+#[test]
+fn dump_states() {
+    let mut app = App::new();
+    app.add_plugins(superstate_plugins!(
+        (Movement, (Walking, Running)),
+        (Mode, (Explore, Combat)),
+        (Combat, (Melee, Ranged))
+    ));
+    let world = app.world_mut();
+    let e = world.spawn((Walking, Melee)).id();
+    let short = |name: &str| name.rsplit("::").next().unwrap().to_owned();
+    let dump = |world: &bevy_ecs::world::World| {
+        dump_entity_states(world, e)
+            .into_iter()
+            .map(|(superstate, state)| (short(superstate), short(state)))
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(
+        dump(world),
+        [
+            ("Movement".to_owned(), "Walking".to_owned()),
+            ("Mode".to_owned(), "Combat".to_owned()),
+            ("Combat".to_owned(), "Melee".to_owned()),
+        ]
+    );
+    world.entity_mut(e).insert(Explore);
+    assert_eq!(
+        dump(world),
+        [
+            ("Movement".to_owned(), "Walking".to_owned()),
+            ("Mode".to_owned(), "Explore".to_owned()),
+        ]
+    );
+}
+
+#[derive(Resource, Default)]
+struct HookRuns(u32);
+
+#[derive(Component)]
+#[require(Movement)]
+#[component(on_add = count_hook, on_remove = count_hook)]
+struct Flying;
+
+fn count_hook(mut world: DeferredWorld, _: HookContext) {
+    world.resource_mut::<HookRuns>().0 += 1;
+}
+
+#[test]
+fn foreign_hooks() {
+    let mut app = App::new();
+    app.init_resource::<HookRuns>();
+    register_hooks_with_order::<Movement, (Walking, Running, Flying)>(
+        app.world_mut(),
+        HookOrder::SuperstateFirst,
+    )
+    .unwrap();
+    let world = app.world_mut();
+    let e = world.spawn(Walking).id();
+    world.entity_mut(e).insert(Flying);
+    assert!(!world.entity(e).contains::<Walking>());
+    world.entity_mut(e).insert(Running);
+    assert!(!world.entity(e).contains::<Flying>());
+    assert!(world.entity(e).contains::<Running>());
+    assert_eq!(world.resource::<HookRuns>().0, 2);
+}
+
+#[test]
+fn many_plugins() {
+    let mut app = App::new();
+    app.add_plugins(superstate_plugins!(
+        (Mode, (Explore, Combat)),
+        (Combat, (Melee, Ranged))
+    ));
+    let world = app.world_mut();
+    let e = world.spawn(Melee).id();
+    world.entity_mut(e).insert(Explore);
+    assert!(!world.entity(e).contains::<Combat>());
+    assert!(!world.entity(e).contains::<Melee>());
+}
+
+#[derive(Resource, Default)]
+struct Entered(Vec<(Entity, bool)>);
+
+#[test]
+fn state_entered_observer() {
+    let mut app = App::new();
+    app.init_resource::<Entered>()
+        .add_plugins(superstate_plugin::<Movement, (Walking, Running)>)
+        .on_state_entered::<Movement, Running, _, _>(
+            |trigger: Trigger<StateEntered<Running>>,
+             walking: Query<(), With<Walking>>,
+             mut entered: ResMut<Entered>| {
+                let e = trigger.target();
+                assert!(trigger.previous.is_some());
+                entered.0.push((e, walking.contains(e)));
+            },
+        );
+    let world = app.world_mut();
+    let e = world.spawn(Walking).id();
+    world.entity_mut(e).insert(Running);
+    world.entity_mut(e).insert(Running);
+    assert_eq!(world.resource::<Entered>().0, [(e, false)]);
+}
+
+#[test]
+fn superstate_entered_exited() {
+    #[derive(Resource, Default)]
+    struct Boundary(Vec<(&'static str, Entity)>);
+
+    let mut app = App::new();
+    app.init_resource::<Boundary>()
+        .add_plugins(superstate_plugin::<Movement, (Walking, Running)>)
+        .on_super_entered::<Movement, _, _>(
+            |trigger: Trigger<SuperstateEntered<Movement>>, mut boundary: ResMut<Boundary>| {
+                boundary.0.push(("entered", trigger.target()));
+            },
+        )
+        .on_super_exited::<Movement, _, _>(
+            |trigger: Trigger<SuperstateExited<Movement>>,
+             walking: Query<(), With<Walking>>,
+             mut boundary: ResMut<Boundary>| {
+                assert!(!walking.contains(trigger.target()));
+                boundary.0.push(("exited", trigger.target()));
+            },
+        );
+    let world = app.world_mut();
+    let a = world.spawn(Walking).id();
+    world.entity_mut(a).insert(Running);
+    let b = world.spawn(Walking).id();
+    // Without a state `Movement` does not stay, so the machine is not entered.
+    world.spawn(Movement);
+    world.entity_mut(a).remove::<Movement>();
+    world.despawn(b);
+    world.flush();
+    assert_eq!(
+        world.resource::<Boundary>().0,
+        [("entered", a), ("entered", b), ("exited", a), ("exited", b)]
+    );
+}
+
+#[test]
+fn state_entered_bubbles() {
+    #[derive(Resource, Default)]
+    struct Bubbled(Vec<(Entity, Entity)>);
+
+    let mut app = App::new();
+    app.init_resource::<Entered>()
+        .init_resource::<Bubbled>()
+        .add_plugins(superstate_plugin::<Movement, (Walking, Running)>)
+        .on_state_entered::<Movement, Running, _, _>(
+            |trigger: Trigger<StateEntered<Running>>, mut entered: ResMut<Entered>| {
+                entered.0.push((trigger.target(), true));
+            },
+        );
+    let world = app.world_mut();
+    let root = world.spawn_empty().id();
+    let parent = world.spawn(ChildOf(root)).id();
+    let child = world.spawn((Walking, ChildOf(parent))).id();
+    let record = |trigger: Trigger<StateEntered<Running>>, mut bubbled: ResMut<Bubbled>| {
+        bubbled.0.push((trigger.target(), trigger.entity));
+    };
+    world.entity_mut(root).observe(record);
+    world.entity_mut(parent).observe(record);
+    world.entity_mut(child).insert(Running);
+    assert_eq!(
+        world.resource::<Bubbled>().0,
+        [(parent, child), (root, child)]
+    );
+    assert_eq!(
+        world.resource::<Entered>().0,
+        [(child, true), (parent, true), (root, true)]
+    );
+
+    world.resource_mut::<Bubbled>().0.clear();
+    world
+        .entity_mut(parent)
+        .observe(|mut trigger: Trigger<StateEntered<Running>>| trigger.propagate(false));
+    world.entity_mut(child).insert(Walking);
+    world.entity_mut(child).insert(Running);
+    assert_eq!(world.resource::<Bubbled>().0, [(parent, child)]);
+}
+
+#[test]
+fn state_exited_bubbles() {
+    #[derive(Resource, Default)]
+    struct Bubbled(Vec<(Entity, Entity)>);
+
+    let mut app = App::new();
+    app.init_resource::<Bubbled>()
+        .add_plugins(superstate_plugin::<Movement, (Walking, Running)>)
+        .on_state_exited::<Movement, Walking, _, _>(|_: Trigger<StateExited<Walking>>| {});
+    let world = app.world_mut();
+    let root = world.spawn_empty().id();
+    let parent = world.spawn(ChildOf(root)).id();
+    let child = world.spawn((Walking, ChildOf(parent))).id();
+    let record = |trigger: Trigger<StateExited<Walking>>, mut bubbled: ResMut<Bubbled>| {
+        bubbled.0.push((trigger.target(), trigger.entity));
+    };
+    world.entity_mut(root).observe(record);
+    world.entity_mut(parent).observe(record);
+    world.entity_mut(child).insert(Running);
+    assert_eq!(
+        world.resource::<Bubbled>().0,
+        [(parent, child), (root, child)]
+    );
+
+    world.resource_mut::<Bubbled>().0.clear();
+    world
+        .entity_mut(parent)
+        .observe(|mut trigger: Trigger<StateExited<Walking>>| trigger.propagate(false));
+    world.entity_mut(child).insert(Walking);
+    world.entity_mut(child).remove::<Movement>();
+    world.flush();
+    assert_eq!(world.resource::<Bubbled>().0, [(parent, child)]);
+}
+
+#[test]
+fn failed_build() {
+    #[derive(Clone)]
+    struct Unknown;
+
+    impl StateEnum<Movement> for Unknown {
+        fn map_states(builder: &mut StateEnumBuilder<Movement, Self>) {
+            builder.state::<Flying>(Unknown);
+        }
+    }
+
+    let mut app = App::new();
+    let systems = |app: &App| {
+        app.get_schedule(PostUpdate)
+            .map_or(0, |schedule| schedule.systems_len())
+    };
+    let before = systems(&app);
+    let result = SuperstateBuilder::<Movement, (Walking, Running)>::new()
+        .current_state_component()
+        .state_enum::<Unknown>()
+        .build(&mut app);
+    assert!(result.is_err());
+    assert_eq!(systems(&app), before);
+    assert!(
+        !app.world()
+            .contains_resource::<SuperstateRegistry<Movement>>()
+    );
+
+    SuperstateBuilder::<Movement, (Walking, Running)>::new()
+        .current_state_component()
+        .build(&mut app)
+        .unwrap();
+    let world = app.world_mut();
+    let e = world.spawn((Walking, Running)).id();
+    assert!(!world.entity(e).contains::<Walking>());
+    app.update();
+    assert!(app.world().entity(e).contains::<CurrentState<Movement>>());
+}
+
+#[test]
+fn builder() {
+    let mut app = App::new();
+    SuperstateBuilder::<Movement, (Walking, Running)>::new()
+        .allow_empty()
+        .guard(|_, _, _, _| false)
+        .build(&mut app)
+        .unwrap();
+    let world = app.world_mut();
+    let e = world.spawn(Movement).id();
+    assert!(world.entity(e).contains::<Movement>());
+    world.entity_mut(e).insert(Walking);
+    world.entity_mut(e).insert(Running);
+    assert!(world.entity(e).contains::<Walking>());
+    assert!(!world.entity(e).contains::<Running>());
+    world.entity_mut(e).remove::<Walking>();
+    assert!(world.entity(e).contains::<Movement>());
+}
+
+#[test]
+fn plugin_struct() {
+    let mut app = App::new();
+    app.add_plugins(
+        SuperstatePlugin::<Movement, (Walking, Running)>::default()
+            .with_policy(ConflictPolicy::KeepFirst)
+            .with_logging()
+            .allowing_empty(),
+    );
+    let world = app.world_mut();
+    let e = world.spawn((Walking, Running)).id();
+    assert!(world.entity(e).contains::<Walking>());
+    assert!(!world.entity(e).contains::<Running>());
+    world.entity_mut(e).remove::<Walking>();
+    assert!(world.entity(e).contains::<Movement>());
+}
+
+#[test]
+fn remove_auxiliary() {
+    let mut app = App::new();
+    SuperstateBuilder::<Movement, (Walking, Running)>::new()
+        .current_state_component()
+        .history_stack(4)
+        .build(&mut app)
+        .unwrap();
+    let e = app.world_mut().spawn(Walking).id();
+    app.world_mut()
+        .commands()
+        .entity(e)
+        .push_state::<Movement, _>(Running)
+        .lock_state::<Movement>();
+    app.update();
+    assert!(app.world().entity(e).contains::<CurrentState<Movement>>());
+    assert!(app.world().entity(e).contains::<StateLock<Movement>>());
+    let world = app.world_mut();
+    world.entity_mut(e).remove::<Movement>();
+    world.flush();
+    let entity = world.entity(e);
+    assert!(!entity.contains::<Running>());
+    assert!(!entity.contains::<CurrentState<Movement>>());
+    assert!(!entity.contains::<StateLock<Movement>>());
+    let info = entity.get::<SuperstateInfo<Movement>>().unwrap();
+    assert_eq!(info.current_state(), None);
+    assert_eq!(info.previous_state(), None);
+    assert!(info.stack().is_empty());
+}
+
+#[test]
+fn superstate_sets() {
+    #[derive(Resource, Default)]
+    struct Seen(Vec<&'static str>);
+
+    let mut app = App::new();
+    SuperstateBuilder::<Movement, (Walking, Running)>::new()
+        .process_transitions_in(SuperstateSet::ProcessTransitions)
+        .current_state_component()
+        .build(&mut app)
+        .unwrap();
+    register_state_constructor::<Movement, Running>(app.world_mut(), || Running);
+    app.init_resource::<Seen>()
+        .add_systems(
+            Update,
+            (|query: Query<(), With<Running>>, mut seen: ResMut<Seen>| {
+                if !query.is_empty() {
+                    seen.0.push("running");
+                }
+            })
+            .after(SuperstateSet::ProcessTransitions),
+        )
+        .add_systems(
+            PostUpdate,
+            (|query: Query<(), With<CurrentState<Movement>>>, mut seen: ResMut<Seen>| {
+                if !query.is_empty() {
+                    seen.0.push("current");
+                }
+            })
+            .after(SuperstateSet::Sync),
+        );
+    let running = app.world().component_id::<Running>().unwrap();
+    let e = app.world_mut().spawn(Walking).id();
+    app.world_mut()
+        .send_event(TransitionRequest::<Movement>::new(e, running))
+        .unwrap();
+    app.update();
+    assert_eq!(app.world().resource::<Seen>().0, ["running", "current"]);
+}
+
+#[test]
+fn transition_requests() {
+    #[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+    struct Transitions;
+
+    #[derive(Resource, Default)]
+    struct Seen(Vec<(&'static str, bool)>);
+
+    #[derive(Resource, Default)]
+    struct Read(usize);
+
+    let mut app = App::new();
+    SuperstateBuilder::<Movement, (Walking, Running)>::new()
+        .process_transitions_in(Transitions)
+        .build(&mut app)
+        .unwrap();
+    register_state_constructor::<Movement, Walking>(app.world_mut(), || Walking);
+    register_state_constructor::<Movement, Running>(app.world_mut(), || Running);
+    app.init_resource::<Seen>().add_systems(
+        Update,
+        (
+            (|query: Query<(), With<Running>>, mut seen: ResMut<Seen>| {
+                seen.0.push(("before", !query.is_empty()));
+            })
+            .before(Transitions),
+            (|query: Query<(), With<Running>>, mut seen: ResMut<Seen>| {
+                seen.0.push(("after", !query.is_empty()));
+            })
+            .after(Transitions),
+            (|mut requests: EventReader<TransitionRequest<Movement>>, mut read: ResMut<Read>| {
+                read.0 += requests.read().count();
+            })
+            .after(Transitions),
+        ),
+    );
+    app.init_resource::<Read>();
+    let walking = app.world().component_id::<Walking>().unwrap();
+    let running = app.world().component_id::<Running>().unwrap();
+    let e = app.world_mut().spawn(Walking).id();
+    app.world_mut()
+        .send_event_batch([
+            TransitionRequest::<Movement>::new(e, running),
+            TransitionRequest::new(e, walking),
+            TransitionRequest::new(e, running),
+        ])
+        .unwrap();
+    app.update();
+    assert!(app.world().entity(e).contains::<Running>());
+    let info = app.world().get::<SuperstateInfo<Movement>>(e).unwrap();
+    assert_eq!(info.previous_state(), Some(walking));
+    assert_eq!(
+        app.world().resource::<Seen>().0,
+        [("before", false), ("after", true)]
+    );
+    // Requests are not consumed by the system.
+    assert_eq!(app.world().resource::<Read>().0, 3);
+}
+
+#[test]
+fn input_map() {
+    #[derive(Clone, Copy)]
+    enum Input {
+        Run,
+        Stop,
+        Jump,
+    }
+
+    #[derive(Event)]
+    struct MovementInput(Entity, Input);
+
+    impl StateInput for MovementInput {
+        fn entity(&self) -> Entity {
+            self.0
+        }
+    }
+
+    let mut app = App::new();
+    app.add_plugins(superstate_plugin::<Movement, (Walking, Running)>);
+    register_state_constructor::<Movement, Walking>(app.world_mut(), || Walking);
+    register_state_constructor::<Movement, Running>(app.world_mut(), || Running);
+    let walking = app.world().component_id::<Walking>().unwrap();
+    let running = app.world().component_id::<Running>().unwrap();
+    app.register_input_map::<Movement, MovementInput>(move |input, current| match input.1 {
+        Input::Run if current == Some(walking) => Some(running),
+        Input::Stop if current.is_some() => Some(walking),
+        Input::Run | Input::Stop | Input::Jump => None,
+    });
+    let e = app.world_mut().spawn(Walking).id();
+    let other = app.world_mut().spawn_empty().id();
+    app.world_mut()
+        .send_event_batch([
+            MovementInput(e, Input::Run),
+            MovementInput(e, Input::Stop),
+            MovementInput(e, Input::Jump),
+            MovementInput(e, Input::Run),
+            MovementInput(other, Input::Stop),
+        ])
+        .unwrap();
+    app.update();
+    assert!(app.world().entity(e).contains::<Running>());
+    let info = app.world().get::<SuperstateInfo<Movement>>(e).unwrap();
+    assert_eq!(info.previous_state(), Some(walking));
+    assert!(!app.world().entity(other).contains::<Movement>());
+
+    app.world_mut().send_event(MovementInput(e, Input::Run));
+    app.update();
+    assert!(app.world().entity(e).contains::<Running>());
+}
+
+#[test]
+fn lazy_plugin() {
+    let mut app = App::new();
+    app.add_plugins(lazy_superstate_plugin::<Movement, (Walking, Running)>);
+    app.update();
+    assert!(
+        app.world()
+            .get_resource::<SuperstateRegistry<Movement>>()
+            .is_none()
+    );
+    let world = app.world_mut();
+    let a = world.commands().spawn((Walking, Running)).id();
+    let b = world.commands().spawn(Walking).id();
+    world.flush();
+    assert!(world.contains_resource::<SuperstateRegistry<Movement>>());
+    assert!(!world.entity(a).contains::<Walking>());
+    assert!(world.entity(a).contains::<Running>());
+    world.entity_mut(b).insert(Running);
+    world.flush();
+    assert!(!world.entity(b).contains::<Walking>());
+    let running = world.component_id::<Running>();
+    let info = world.get::<SuperstateInfo<Movement>>(b).unwrap();
+    assert_eq!(info.current_state(), running);
+    world.entity_mut(b).remove::<Running>();
+    world.flush();
+    assert!(!world.entity(b).contains::<Movement>());
+}
@@ -0,0 +1,155 @@
+#![cfg(feature = "asset")]
+
+use bevy_app::{App, TaskPoolPlugin};
+use bevy_asset::{AssetPlugin, AssetServer, Assets};
+use bevy_ecs::component::Component;
+use superstate::{
+    SuperstateAppExt, SuperstateInfo, TransitionGraph, TransitionTable, state_alias,
+    superstate_plugin,
+};
+
+#[derive(Default, Component)]
+#[require(SuperstateInfo<Movement>)]
+struct Movement;
+
+#[derive(Component)]
+#[require(Movement)]
+struct Walking;
+
+#[derive(Component)]
+#[require(Movement)]
+struct Running;
+
+#[derive(Component)]
+#[require(Movement)]
+struct Crawling;
+
+#[test]
+fn transition_graph() {
+    let mut app = App::new();
+    app.add_plugins((
+        TaskPoolPlugin::default(),
+        AssetPlugin {
+            file_path: "tests/assets".into(),
+            ..Default::default()
+        },
+    ))
+    .add_plugins(superstate_plugin::<Movement, (Walking, Running, Crawling)>)
+    .transition_graph::<Movement>("movement.graph.ron");
+    for _ in 0..1000 {
+        app.update();
+        if app.world().contains_resource::<TransitionTable<Movement>>() {
+            break;
+        }
+        std::thread::sleep(std::time::Duration::from_millis(1));
+    }
+    let world = app.world_mut();
+    let [walking, running, crawling] = [
+        world.register_component::<Walking>(),
+        world.register_component::<Running>(),
+        world.register_component::<Crawling>(),
+    ];
+    let table = world.resource::<TransitionTable<Movement>>();
+    assert!(table.is_allowed(walking, running));
+    assert!(table.is_allowed(running, walking));
+    assert!(!table.is_allowed(walking, crawling));
+    assert_eq!(table.edges().count(), 2);
+
+    let e = world.spawn(Walking).id();
+    world.entity_mut(e).insert(Crawling);
+    assert!(world.entity(e).contains::<Walking>());
+
+    // Modifying the asset, as hot-reloading does, replaces the table.
+    state_alias::<Crawling>(world, "crawl");
+    let graph = world
+        .resource::<AssetServer>()
+        .get_handle::<TransitionGraph>("movement.graph.ron")
+        .unwrap();
+    world
+        .resource_mut::<Assets<TransitionGraph>>()
+        .get_mut(&graph)
+        .unwrap()
+        .edges
+        .insert("Walking".into(), vec!["crawl".into()]);
+    app.update();
+    let world = app.world_mut();
+    let table = world.resource::<TransitionTable<Movement>>();
+    assert!(table.is_allowed(walking, crawling));
+    assert!(!table.is_allowed(walking, running));
+    world.entity_mut(e).insert(Crawling);
+    assert!(world.entity(e).contains::<Crawling>());
+}
+
+mod a {
+    use super::*;
+
+    #[derive(Component)]
+    #[require(Movement)]
+    pub struct Idle;
+}
+
+mod b {
+    use super::*;
+
+    #[derive(Component)]
+    #[require(Movement)]
+    pub struct Idle;
+}
+
+#[test]
+fn transition_graph_ambiguous_name() {
+    let mut app = App::new();
+    app.add_plugins((
+        TaskPoolPlugin::default(),
+        AssetPlugin {
+            file_path: "tests/assets".into(),
+            ..Default::default()
+        },
+    ))
+    .add_plugins(superstate_plugin::<Movement, (Walking, a::Idle, b::Idle)>)
+    .transition_graph::<Movement>("ambiguous.graph.ron");
+    let graph = app
+        .world()
+        .resource::<AssetServer>()
+        .get_handle::<TransitionGraph>("ambiguous.graph.ron")
+        .unwrap();
+    for _ in 0..1000 {
+        app.update();
+        if app.world().resource::<AssetServer>().is_loaded(&graph) {
+            break;
+        }
+        std::thread::sleep(std::time::Duration::from_millis(1));
+    }
+    app.update();
+    // `Idle` may be either state, so the graph is not applied.
+    assert!(!app.world().contains_resource::<TransitionTable<Movement>>());
+
+    let world = app.world_mut();
+    let [walking, idle] = [
+        world.register_component::<Walking>(),
+        world.register_component::<a::Idle>(),
+    ];
+    let name = std::any::type_name::<a::Idle>().to_owned();
+    let edges = &mut world
+        .resource_mut::<Assets<TransitionGraph>>()
+        .into_inner()
+        .get_mut(&graph)
+        .unwrap()
+        .edges;
+    edges.clear();
+    edges.insert(name.clone(), vec!["Walking".into()]);
+    edges.insert("Walking".into(), vec![name]);
+    app.update();
+    app.update();
+    let table = app.world().resource::<TransitionTable<Movement>>();
+    assert!(table.is_allowed(idle, walking));
+    assert!(table.is_allowed(walking, idle));
+    assert_eq!(table.edges().count(), 2);
+}
+
+#[test]
+fn transition_graph_from_ron() {
+    let graph = TransitionGraph::from_ron(r#"(edges: {"Walking": ["Running"]})"#).unwrap();
+    assert_eq!(graph.edges["Walking"], ["Running"]);
+    assert!(TransitionGraph::from_ron("(edges: [])").is_err());
+}
@@ -0,0 +1,36 @@
+#![cfg(feature = "diagnostic")]
+
+use bevy_app::App;
+use bevy_diagnostic::DiagnosticsStore;
+use bevy_ecs::component::Component;
+use superstate::{SuperstateDiagnostics, SuperstateInfo, superstate_plugin};
+
+#[derive(Default, Component)]
+#[require(SuperstateInfo<Movement>)]
+struct Movement;
+
+#[derive(Component)]
+#[require(Movement)]
+struct Walking;
+
+#[derive(Component)]
+#[require(Movement)]
+struct Running;
+
+#[test]
+fn transitions() {
+    let mut app = App::new();
+    app.add_plugins(superstate_plugin::<Movement, (Walking, Running)>);
+    let e = app.world_mut().spawn(Walking).id();
+    app.world_mut().entity_mut(e).insert(Running);
+    app.world_mut().entity_mut(e).insert(Walking);
+    let diagnostics = app.world().resource::<SuperstateDiagnostics<Movement>>();
+    assert_eq!(diagnostics.transitions(), 3);
+    let path = diagnostics.path().clone();
+    app.update();
+    let diagnostics = app.world().resource::<SuperstateDiagnostics<Movement>>();
+    assert_eq!(diagnostics.transitions(), 0);
+    assert_eq!(diagnostics.total(), 3);
+    let store = app.world().resource::<DiagnosticsStore>();
+    assert_eq!(store.get(&path).and_then(|d| d.value()), Some(3.0));
+}
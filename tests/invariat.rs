@@ -2,18 +2,41 @@
 mod invariant_test {
     use bevy_ecs::bundle::Bundle;
     use bevy_ecs::entity::Entity;
+    #[cfg(feature = "bevy_time")]
+    use std::time::Duration;
+
+    use bevy_ecs::ptr::OwningPtr;
     use bevy_ecs::query::{Or, With};
     use bevy_ecs::system::Query;
     use bevy_ecs::world::unsafe_world_cell;
-    use bevy_ecs::{component::Component, world::World};
-    use superstate::register_hooks;
-    use superstate::SuperstateInfo;
+    use bevy_ecs::{
+        component::{Component, ComponentId, HookContext},
+        observer::Trigger,
+        resource::Resource,
+        system::ResMut,
+        world::{DeferredWorld, OnRemove, World},
+    };
+    #[cfg(feature = "bevy_time")]
+    use bevy_time::Time;
+    use superstate::hooks::{StateCollisionError, SuperstateError};
+    use superstate::{
+        AnyOfStates, ConflictPolicy, RejectReason, StateChanged, StateEnum, StateEnumBuilder,
+        StateEnumQuery, StateJustChanged, StateLock, StateOccupancy, StatePriorities,
+        StatePriority, StateSnapshot, SuperstateBuilder, SuperstateCommands, SuperstateInfo,
+        SuperstateQuery, SuperstateRegistry, TransferState, TransitionGuard, TransitionLog,
+        TransitionRejected, TransitionTable, deregister_hooks, entities_in_state, export_dot,
+        on_exit_with, register_default_state, register_hooks, register_hooks_allowing_empty,
+        register_hooks_with_default, register_hooks_with_fallback, register_hooks_with_guard,
+        register_hooks_with_policy, register_state, register_state_constructor,
+        register_state_enum, register_transfer, resolve_state_id, super_component_id,
+        try_current_state,
+    };
 
     #[derive(Default, Component)]
     #[require(SuperstateInfo<Movement>)]
     struct Movement;
 
-    #[derive(Component)]
+    #[derive(Component, Default)]
     #[require(Movement)]
     struct Walking(u32);
 
@@ -25,6 +48,43 @@ mod invariant_test {
     #[require(Movement)]
     struct Flying(u32);
 
+    impl TransferState<Running> for Walking {
+        fn transfer(from: &Self, to: &mut Running) {
+            to.0 += from.0;
+        }
+    }
+
+    #[derive(Clone, Copy, PartialEq, Debug)]
+    enum MovementState {
+        Walking,
+        Running,
+    }
+
+    impl StateEnum<Movement> for MovementState {
+        fn map_states(builder: &mut StateEnumBuilder<Movement, Self>) {
+            builder
+                .state::<Walking>(MovementState::Walking)
+                .state::<Running>(MovementState::Running);
+        }
+    }
+
+    #[derive(Clone)]
+    struct Unknown;
+
+    impl StateEnum<Movement> for Unknown {
+        fn map_states(builder: &mut StateEnumBuilder<Movement, Self>) {
+            builder.state::<Movement>(Unknown);
+        }
+    }
+
+    impl StatePriority for Walking {
+        const PRIORITY: u32 = 1;
+    }
+
+    impl StatePriority for Flying {
+        const PRIORITY: u32 = 2;
+    }
+
     #[test]
     fn main() {
         let mut world = World::new();
@@ -54,6 +114,1653 @@ mod invariant_test {
         world.run_system(no_states).unwrap();
     }
 
+    #[test]
+    fn current_state() {
+        let mut world = World::new();
+        register_hooks::<Movement, (Walking, Running, Flying)>(&mut world).unwrap();
+        let walking = world.register_component::<Walking>();
+        let running = world.register_component::<Running>();
+        let e = world.spawn(Walking(1)).id();
+        let info = world.get::<SuperstateInfo<Movement>>(e).unwrap();
+        assert_eq!(info.current_state(), Some(walking));
+        world.entity_mut(e).insert(Running(2));
+        let info = world.get::<SuperstateInfo<Movement>>(e).unwrap();
+        assert_eq!(info.current_state(), Some(running));
+        assert_eq!(info.previous_state(), Some(walking));
+        world.entity_mut(e).remove::<Movement>();
+        let info = world.get::<SuperstateInfo<Movement>>(e).unwrap();
+        assert_eq!(info.current_state(), None);
+        assert_eq!(info.previous_state(), None);
+    }
+
+    #[test]
+    fn is_in_state() {
+        #[derive(Component)]
+        struct Unregistered;
+
+        let mut world = World::new();
+        register_hooks::<Movement, (Walking, Running, Flying)>(&mut world).unwrap();
+        let e = world.spawn(Walking(1)).id();
+        let info = world.get::<SuperstateInfo<Movement>>(e).unwrap();
+        assert!(info.is_in_state::<Walking>(world.components()));
+        assert!(!info.is_in_state::<Running>(world.components()));
+        assert!(!info.is_in_state::<Unregistered>(world.components()));
+    }
+
+    #[test]
+    fn current_state_name() {
+        let mut world = World::new();
+        register_hooks::<Movement, (Walking, Running, Flying)>(&mut world).unwrap();
+        let e = world.spawn(Walking(1)).id();
+        let info = world.get::<SuperstateInfo<Movement>>(e).unwrap();
+        assert!(
+            info.current_state_name(world.components())
+                .is_some_and(|name| name.ends_with("Walking"))
+        );
+        world.entity_mut(e).remove::<Movement>();
+        let info = world.get::<SuperstateInfo<Movement>>(e).unwrap();
+        assert_eq!(info.current_state_name(world.components()), None);
+    }
+
+    #[test]
+    fn readd_same_state() {
+        let mut world = World::new();
+        register_hooks::<Movement, (Walking, Running, Flying)>(&mut world).unwrap();
+        let e = world.spawn_empty().id();
+        world.commands().entity(e).insert(Walking(1));
+        world.commands().entity(e).insert(Walking(2));
+        world.flush();
+        let walking = world.component_id::<Walking>();
+        let info = world.get::<SuperstateInfo<Movement>>(e).unwrap();
+        assert_eq!(info.current_state(), walking);
+        // With a stale duplicate id `Super` would stay without states.
+        world.entity_mut(e).remove::<Walking>();
+        assert!(!world.entity(e).contains::<Movement>());
+    }
+
+    #[test]
+    fn missing_require() {
+        #[derive(Component)]
+        struct Swimming;
+
+        #[derive(Default, Component)]
+        struct Health;
+
+        #[derive(Component)]
+        #[require(Health)]
+        struct Alive;
+
+        let mut world = World::new();
+        let err = register_hooks::<Movement, (Walking, Swimming)>(&mut world).unwrap_err();
+        assert!(err.to_string().contains("Swimming"));
+        assert!(register_hooks::<Health, (Alive,)>(&mut world).is_err());
+    }
+
+    #[test]
+    fn state_on_empty_entity() {
+        let mut world = World::new();
+        register_hooks::<Movement, (Walking, Running, Flying)>(&mut world).unwrap();
+        let running = world.component_id::<Running>();
+        let e = world.spawn_empty().id();
+        world.entity_mut(e).insert(Running(1));
+        let info = world.get::<SuperstateInfo<Movement>>(e).unwrap();
+        assert_eq!(info.current_state(), running);
+        let e = world.spawn_empty().id();
+        world.commands().entity(e).insert(Running(1));
+        world.flush();
+        assert!(world.entity(e).contains::<Movement>());
+        let info = world.get::<SuperstateInfo<Movement>>(e).unwrap();
+        assert_eq!(info.current_state(), running);
+    }
+
+    #[test]
+    fn collisions() {
+        let mut world = World::new();
+        let err = register_hooks::<Movement, (Walking, Movement)>(&mut world).unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<StateCollisionError>(),
+            Some(StateCollisionError::SuperIsState { .. })
+        ));
+        let err = register_hooks::<Movement, (Walking, Running, Walking)>(&mut world).unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<StateCollisionError>(),
+            Some(StateCollisionError::DuplicateState { name, .. }) if name.contains("Walking")
+        ));
+        register_hooks::<Movement, (Walking, Running)>(&mut world).unwrap();
+    }
+
+    #[test]
+    fn spawn_in_state() {
+        #[derive(Component)]
+        struct Tag;
+
+        let mut world = World::new();
+        register_hooks_with_policy::<Movement, (Walking, Running, Flying)>(
+            &mut world,
+            ConflictPolicy::KeepFirst,
+        )
+        .unwrap();
+        let e =
+            superstate::spawn_in_state::<Movement, _>(&mut world.commands(), Running(1), Tag).id();
+        world.flush();
+        assert!(world.entity(e).contains::<Tag>());
+        assert!(world.entity(e).contains::<Running>());
+        let info = world.get::<SuperstateInfo<Movement>>(e).unwrap();
+        assert_eq!(info.previous_state(), None);
+        let e = superstate::spawn_in_state::<Movement, _>(
+            &mut world.commands(),
+            Running(1),
+            (Tag, Walking(1), Flying(1)),
+        )
+        .id();
+        world.flush();
+        assert!(world.entity(e).contains::<Running>());
+        assert!(!world.entity(e).contains::<Walking>());
+        assert!(!world.entity(e).contains::<Flying>());
+    }
+
+    #[test]
+    fn despawn_with_state_insert() {
+        let mut world = World::new();
+        register_hooks::<Movement, (Walking, Running, Flying)>(&mut world).unwrap();
+        let e = world.spawn(Walking(1)).id();
+        world.commands().entity(e).insert(Running(1));
+        world.commands().entity(e).despawn();
+        world.flush();
+        let e = world.spawn(Walking(1)).id();
+        world.commands().entity(e).despawn();
+        world.commands().entity(e).remove::<Walking>();
+        world.flush();
+        let e = world.spawn(Walking(1)).id();
+        world.entity_mut(e).remove::<SuperstateInfo<Movement>>();
+        world.entity_mut(e).remove::<Walking>();
+        world.entity_mut(e).remove::<Movement>();
+        assert!(world.get_entity(e).is_ok());
+    }
+
+    #[test]
+    fn transition_to_previous() {
+        let mut world = World::new();
+        register_hooks::<Movement, (Walking, Running, Flying)>(&mut world).unwrap();
+        register_default_state::<Movement, Walking>(&mut world);
+        let e = world.spawn(Running(1)).id();
+        world
+            .commands()
+            .entity(e)
+            .transition_to_previous::<Movement>();
+        world.flush();
+        assert!(world.entity(e).contains::<Running>());
+        world.entity_mut(e).insert(Walking(5));
+        world.entity_mut(e).insert(Flying(1));
+        world
+            .commands()
+            .entity(e)
+            .transition_to_previous::<Movement>();
+        world.flush();
+        assert_eq!(world.get::<Walking>(e).unwrap().0, 0);
+        assert!(!world.entity(e).contains::<Flying>());
+        // `Flying` has no constructor.
+        world
+            .commands()
+            .entity(e)
+            .transition_to_previous::<Movement>();
+        world.flush();
+        assert!(world.entity(e).contains::<Walking>());
+    }
+
+    #[test]
+    fn history_stack() {
+        let mut world = World::new();
+        SuperstateBuilder::<Movement, (Walking, Running, Flying)>::new()
+            .history_stack(2)
+            .build_world(&mut world)
+            .unwrap();
+        register_default_state::<Movement, Walking>(&mut world);
+        register_state_constructor::<Movement, Running>(&mut world, || Running(7));
+        let walking = world.component_id::<Walking>().unwrap();
+        let running = world.component_id::<Running>().unwrap();
+        let e = world.spawn(Flying(1)).id();
+        world
+            .commands()
+            .entity(e)
+            .push_state::<Movement, _>(Walking(1))
+            .push_state::<Movement, _>(Running(1))
+            .push_state::<Movement, _>(Flying(1));
+        world.flush();
+        let info = world.get::<SuperstateInfo<Movement>>(e).unwrap();
+        assert_eq!(info.stack(), [walking, running]);
+        world.commands().entity(e).pop_state::<Movement>();
+        world.flush();
+        assert_eq!(world.get::<Running>(e).unwrap().0, 7);
+        world.commands().entity(e).pop_state::<Movement>();
+        world.flush();
+        assert!(world.entity(e).contains::<Walking>());
+        world.commands().entity(e).pop_state::<Movement>();
+        world.flush();
+        assert!(world.entity(e).contains::<Walking>());
+    }
+
+    #[test]
+    fn history_stack_rejected() {
+        let mut world = World::new();
+        SuperstateBuilder::<Movement, (Walking, Running, Flying)>::new()
+            .history_stack(2)
+            .build_world(&mut world)
+            .unwrap();
+        register_default_state::<Movement, Walking>(&mut world);
+        let table = TransitionTable::<Movement>::builder(&mut world)
+            .allow::<Flying, Walking>()
+            .allow::<Walking, Flying>()
+            .build();
+        world.insert_resource(table);
+        let walking = world.component_id::<Walking>().unwrap();
+        let e = world.spawn(Flying(1)).id();
+        // Flying -> Running is rejected by the table.
+        world
+            .commands()
+            .entity(e)
+            .push_state::<Movement, _>(Running(1));
+        world.flush();
+        let info = world.get::<SuperstateInfo<Movement>>(e).unwrap();
+        assert!(info.stack().is_empty());
+        // Rejected by the lock.
+        world.entity_mut(e).insert(StateLock::<Movement>::default());
+        world
+            .commands()
+            .entity(e)
+            .push_state::<Movement, _>(Walking(1));
+        world.flush();
+        let info = world.get::<SuperstateInfo<Movement>>(e).unwrap();
+        assert!(info.stack().is_empty());
+        world.entity_mut(e).remove::<StateLock<Movement>>();
+        world
+            .commands()
+            .entity(e)
+            .push_state::<Movement, _>(Walking(1))
+            .push_state::<Movement, _>(Flying(2));
+        world.flush();
+        let flying = world.component_id::<Flying>().unwrap();
+        let info = world.get::<SuperstateInfo<Movement>>(e).unwrap();
+        assert_eq!(info.stack(), [flying, walking]);
+        // Walking is popped only after the lock stops rejecting it.
+        world.entity_mut(e).insert(StateLock::<Movement>::default());
+        world.commands().entity(e).pop_state::<Movement>();
+        world.flush();
+        assert_eq!(world.get::<Flying>(e).unwrap().0, 2);
+        let info = world.get::<SuperstateInfo<Movement>>(e).unwrap();
+        assert_eq!(info.stack(), [flying, walking]);
+        world.entity_mut(e).remove::<StateLock<Movement>>();
+        world.commands().entity(e).pop_state::<Movement>();
+        world.flush();
+        assert!(world.entity(e).contains::<Walking>());
+        let info = world.get::<SuperstateInfo<Movement>>(e).unwrap();
+        assert_eq!(info.stack(), [flying]);
+        // Flying has no constructor, so it stays on the stack.
+        world.commands().entity(e).pop_state::<Movement>();
+        world.flush();
+        assert!(world.entity(e).contains::<Walking>());
+        let info = world.get::<SuperstateInfo<Movement>>(e).unwrap();
+        assert_eq!(info.stack(), [flying]);
+    }
+
+    #[test]
+    fn state_ids_without_bundle() {
+        let mut world = World::new();
+        register_hooks::<Movement, (Walking, Running, Flying)>(&mut world).unwrap();
+        let bundle = std::any::TypeId::of::<(Walking, Running, Flying)>();
+        assert!(world.bundles().get_id(bundle).is_none());
+        let ids = [
+            world.component_id::<Walking>().unwrap(),
+            world.component_id::<Running>().unwrap(),
+            world.component_id::<Flying>().unwrap(),
+        ];
+        let registry = world.resource::<SuperstateRegistry<Movement>>();
+        assert_eq!(registry.state_ids(), ids);
+    }
+
+    #[test]
+    fn deregister() {
+        let mut world = World::new();
+        assert!(deregister_hooks::<Movement, (Walking, Running, Flying)>(&mut world).is_err());
+        register_hooks::<Movement, (Walking, Running, Flying)>(&mut world).unwrap();
+        deregister_hooks::<Movement, (Walking, Running, Flying)>(&mut world).unwrap();
+        let e = world.spawn((Walking(1), Running(2))).id();
+        assert!(world.entity(e).contains::<Walking>());
+        assert!(world.entity(e).contains::<Running>());
+        world.despawn(e);
+        register_hooks::<Movement, (Walking, Running, Flying)>(&mut world).unwrap();
+        let e = world.spawn((Walking(1), Running(2))).id();
+        assert!(!world.entity(e).contains::<Walking>());
+        assert!(world.entity(e).contains::<Running>());
+    }
+
+    #[test]
+    fn deregister_reconfigure() {
+        let mut world = World::new();
+        let table = TransitionTable::<Movement>::builder(&mut world)
+            .allow::<Walking, Running>()
+            .build();
+        SuperstateBuilder::<Movement, (Walking, Running, Flying)>::new()
+            .transition_table(table)
+            .build_world(&mut world)
+            .unwrap();
+        let priorities = StatePriorities::<Movement>::builder(&mut world)
+            .priority::<Running>(2)
+            .build();
+        world.insert_resource(priorities);
+        deregister_hooks::<Movement, (Walking, Running, Flying)>(&mut world).unwrap();
+        assert!(!world.contains_resource::<SuperstateRegistry<Movement>>());
+        assert!(!world.contains_resource::<TransitionTable<Movement>>());
+        assert!(!world.contains_resource::<StatePriorities<Movement>>());
+
+        // Registered again as a reloaded mod would, with other options.
+        SuperstateBuilder::<Movement, (Walking, Running, Flying)>::new()
+            .conflict_policy(ConflictPolicy::KeepFirst)
+            .default_state::<Walking>()
+            .build_world(&mut world)
+            .unwrap();
+        let registry = world.resource::<SuperstateRegistry<Movement>>();
+        assert_eq!(registry.policy(), ConflictPolicy::KeepFirst);
+        let e = world.spawn((Flying(1), Running(2))).id();
+        assert!(world.entity(e).contains::<Flying>());
+        assert!(!world.entity(e).contains::<Running>());
+        let e = world.spawn(Movement).id();
+        assert!(world.entity(e).contains::<Walking>());
+        // The old table is gone, so Walking -> Flying is allowed.
+        world.entity_mut(e).insert(Flying(1));
+        assert!(world.entity(e).contains::<Flying>());
+
+        // Registering while enabled keeps the options.
+        register_hooks::<Movement, (Walking, Running, Flying)>(&mut world).unwrap();
+        let registry = world.resource::<SuperstateRegistry<Movement>>();
+        assert_eq!(registry.policy(), ConflictPolicy::KeepFirst);
+    }
+
+    #[test]
+    fn default_state() {
+        let mut world = World::new();
+        assert!(
+            register_hooks_with_default::<Movement, (Running, Flying), Walking>(&mut world)
+                .is_err()
+        );
+        register_hooks_with_default::<Movement, (Walking, Running, Flying), Walking>(&mut world)
+            .unwrap();
+        let e = world.spawn(Movement).id();
+        assert!(world.entity(e).contains::<Movement>());
+        assert!(world.entity(e).contains::<Walking>());
+        world.entity_mut(e).insert(Running(1));
+        assert!(!world.entity(e).contains::<Walking>());
+        let e = world.spawn(Flying(1)).id();
+        assert!(!world.entity(e).contains::<Walking>());
+        assert!(world.entity(e).contains::<Flying>());
+    }
+
+    #[test]
+    fn default_state_factory() {
+        let mut world = World::new();
+        SuperstateBuilder::<Movement, (Walking, Running, Flying)>::new()
+            .default_state_with(|| Running(5))
+            .build_world(&mut world)
+            .unwrap();
+        let e = world.spawn(Movement).id();
+        assert_eq!(world.get::<Running>(e).unwrap().0, 5);
+        world.entity_mut(e).remove::<Movement>();
+        world.entity_mut(e).insert(Movement);
+        assert_eq!(world.get::<Running>(e).unwrap().0, 5);
+        let e = world.spawn(Flying(1)).id();
+        assert!(!world.entity(e).contains::<Running>());
+    }
+
+    #[test]
+    fn state_occupancy() {
+        let mut world = World::new();
+        register_hooks::<Movement, (Walking, Running, Flying)>(&mut world).unwrap();
+        let walking = world.register_component::<Walking>();
+        let running = world.register_component::<Running>();
+        let a = world.spawn(Walking(1)).id();
+        world.init_resource::<StateOccupancy<Movement>>();
+        assert_eq!(
+            world
+                .resource::<StateOccupancy<Movement>>()
+                .count_in(walking),
+            1
+        );
+        let b = world.spawn(Walking(1)).id();
+        let c = world.spawn(Walking(1)).id();
+        world.entity_mut(a).insert(Walking(2));
+        world.entity_mut(b).insert(Running(1));
+        world.flush();
+        let occupancy = world.resource::<StateOccupancy<Movement>>();
+        assert_eq!(occupancy.count_in(walking), 2);
+        assert_eq!(occupancy.count_in(running), 1);
+        world.despawn(a);
+        world.entity_mut(c).remove::<Movement>();
+        world.flush();
+        let occupancy = world.resource::<StateOccupancy<Movement>>();
+        assert_eq!(occupancy.count_in(walking), 0);
+        assert_eq!(occupancy.count_in(running), 1);
+        let named = occupancy.named(&world).collect::<Vec<_>>();
+        assert_eq!(named.len(), 1);
+        assert!(named[0].0.ends_with("Running"));
+    }
+
+    #[test]
+    fn transition_log() {
+        let mut world = World::new();
+        SuperstateBuilder::<Movement, (Walking, Running, Flying)>::new()
+            .transition_log(3)
+            .build_world(&mut world)
+            .unwrap();
+        let walking = world.register_component::<Walking>();
+        let running = world.register_component::<Running>();
+        let flying = world.register_component::<Flying>();
+        let a = world.spawn(Walking(1)).id();
+        let b = world.spawn(Running(1)).id();
+        world.entity_mut(a).insert(Walking(2));
+        world.entity_mut(a).insert(Flying(1));
+        let tick = world.change_tick();
+        world.entity_mut(b).insert(Walking(1));
+        let log = world.resource::<TransitionLog<Movement>>();
+        assert_eq!(log.len(), 3);
+        let records = log
+            .iter()
+            .map(|record| (record.entity, record.from, record.to))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            records,
+            [
+                (b, Some(running), walking),
+                (a, Some(walking), flying),
+                (b, None, running),
+            ]
+        );
+        assert_eq!(log.iter().next().unwrap().tick, tick);
+
+        world.resource_mut::<TransitionLog<Movement>>().clear();
+        world.remove_resource::<TransitionLog<Movement>>();
+        world.entity_mut(a).insert(Running(1));
+        assert!(!world.contains_resource::<TransitionLog<Movement>>());
+        world.insert_resource(TransitionLog::<Movement>::new(0));
+        world.entity_mut(a).insert(Walking(1));
+        assert!(world.resource::<TransitionLog<Movement>>().is_empty());
+    }
+
+    #[test]
+    fn remove_super_with_state() {
+        let mut world = World::new();
+        SuperstateBuilder::<Movement, (Walking, Running, Flying)>::new()
+            .fallback_state::<Walking>()
+            .remove_info()
+            .state_occupancy()
+            .build_world(&mut world)
+            .unwrap();
+        let running = world.register_component::<Running>();
+        let a = world.spawn(Running(1)).id();
+        let b = world.spawn(Running(1)).id();
+        let c = world.spawn(Running(1)).id();
+        world.entity_mut(a).remove::<(Movement, Running)>();
+        world.entity_mut(b).remove::<(Running, Movement)>();
+        world
+            .commands()
+            .entity(c)
+            .remove::<Running>()
+            .remove::<Movement>();
+        world.flush();
+        // The fallback state does not bring back the removed `Movement`.
+        for e in [a, b, c] {
+            assert!(!world.entity(e).contains::<Movement>());
+            assert!(!world.entity(e).contains::<Walking>());
+            assert!(!world.entity(e).contains::<Running>());
+            assert!(!world.entity(e).contains::<SuperstateInfo<Movement>>());
+        }
+        let occupancy = world.resource::<StateOccupancy<Movement>>();
+        assert_eq!(occupancy.iter().count(), 0);
+        world.entity_mut(a).insert(Running(1));
+        world.flush();
+        let info = world.get::<SuperstateInfo<Movement>>(a).unwrap();
+        assert_eq!(info.active_states(), [running]);
+    }
+
+    #[test]
+    fn transition_table() {
+        let mut world = World::new();
+        register_hooks::<Movement, (Walking, Running, Flying)>(&mut world).unwrap();
+        let table = TransitionTable::<Movement>::builder(&mut world)
+            .allow_both::<Walking, Running>()
+            .allow::<Running, Flying>()
+            .build();
+        world.insert_resource(table);
+        let e = world.spawn(Walking(1)).id();
+        world.entity_mut(e).insert(Flying(1));
+        assert!(world.entity(e).contains::<Walking>());
+        assert!(!world.entity(e).contains::<Flying>());
+        world.entity_mut(e).insert(Running(1));
+        world.entity_mut(e).insert(Flying(1));
+        assert!(world.entity(e).contains::<Flying>());
+        world.entity_mut(e).insert(Walking(1));
+        assert!(world.entity(e).contains::<Flying>());
+        assert!(!world.entity(e).contains::<Walking>());
+    }
+
+    #[test]
+    #[cfg_attr(debug_assertions, should_panic(expected = "in strict mode"))]
+    fn strict_transition_table() {
+        let mut world = World::new();
+        let table = TransitionTable::<Movement>::builder(&mut world)
+            .allow::<Walking, Running>()
+            .build();
+        SuperstateBuilder::<Movement, (Walking, Running, Flying)>::new()
+            .transition_table(table)
+            .strict()
+            .build_world(&mut world)
+            .unwrap();
+        let e = world.spawn(Walking(1)).id();
+        world.entity_mut(e).insert(Running(1));
+        assert!(world.entity(e).contains::<Running>());
+        world.entity_mut(e).insert(Flying(1));
+        // Release builds drop the transition as usual.
+        assert!(world.entity(e).contains::<Running>());
+    }
+
+    #[test]
+    fn transition_guard() {
+        #[derive(Component)]
+        struct Stamina(u32);
+
+        let mut world = World::new();
+        let flying = world.register_component::<Flying>();
+        register_hooks_with_guard::<Movement, (Walking, Running, Flying)>(
+            &mut world,
+            move |world, entity, _, to| {
+                to != flying || world.get::<Stamina>(entity).is_some_and(|s| s.0 > 0)
+            },
+        )
+        .unwrap();
+        let e = world.spawn((Walking(1), Stamina(0))).id();
+        world.entity_mut(e).insert(Flying(1));
+        assert!(world.entity(e).contains::<Walking>());
+        assert!(!world.entity(e).contains::<Flying>());
+        world.entity_mut(e).insert(Running(1));
+        assert!(world.entity(e).contains::<Running>());
+        world.entity_mut(e).insert((Stamina(1), Flying(1)));
+        assert!(world.entity(e).contains::<Flying>());
+        world.insert_resource(TransitionGuard::<Movement>::new(|_, _, _, _| false));
+        world.entity_mut(e).insert(Walking(1));
+        assert!(world.entity(e).contains::<Flying>());
+    }
+
+    #[test]
+    fn can_transition() {
+        let mut world = World::new();
+        let flying = world.register_component::<Flying>();
+        register_hooks_with_guard::<Movement, (Walking, Running)>(
+            &mut world,
+            move |_, _, _, to| to != flying,
+        )
+        .unwrap();
+        let e = world.spawn(Walking(1)).id();
+        assert!(superstate::can_transition::<Movement, Running>(
+            &mut world, e
+        ));
+        assert!(!superstate::can_transition::<Movement, Flying>(
+            &mut world, e
+        ));
+        let table = TransitionTable::<Movement>::builder(&mut world)
+            .allow::<Running, Walking>()
+            .build();
+        world.insert_resource(table);
+        assert!(!superstate::can_transition::<Movement, Running>(
+            &mut world, e
+        ));
+        assert!(superstate::can_transition::<Movement, Walking>(
+            &mut world, e
+        ));
+        world.entity_mut(e).insert(Running(1));
+        assert!(world.entity(e).contains::<Walking>());
+        let e = world.spawn_empty().id();
+        assert!(superstate::can_transition::<Movement, Running>(
+            &mut world, e
+        ));
+        world.despawn(e);
+        assert!(!superstate::can_transition::<Movement, Running>(
+            &mut world, e
+        ));
+
+        // Parallel regions are entered without the checks, as by the hook.
+        let mut world = World::new();
+        superstate::register_parallel_hooks::<Movement, (Walking, Running)>(&mut world).unwrap();
+        let table = TransitionTable::<Movement>::builder(&mut world)
+            .allow::<Running, Walking>()
+            .build();
+        world.insert_resource(table);
+        let e = world.spawn(Walking(1)).id();
+        assert!(superstate::can_transition::<Movement, Running>(
+            &mut world, e
+        ));
+        world.entity_mut(e).insert(Running(1));
+        assert!(world.entity(e).contains::<Running>());
+    }
+
+    #[test]
+    fn transition_rejected() {
+        #[derive(Resource, Default)]
+        struct Rejected(Vec<(Entity, ComponentId, ComponentId, RejectReason)>);
+
+        let mut world = World::new();
+        register_hooks_with_guard::<Movement, (Walking, Running, Flying)>(
+            &mut world,
+            |_, _, from, to| from != to,
+        )
+        .unwrap();
+        let walking = world.component_id::<Walking>().unwrap();
+        let running = world.component_id::<Running>().unwrap();
+        let flying = world.component_id::<Flying>().unwrap();
+        let table = TransitionTable::<Movement>::builder(&mut world)
+            .allow::<Walking, Running>()
+            .build();
+        world.insert_resource(table);
+        world.init_resource::<Rejected>();
+        world.add_observer(
+            |trigger: Trigger<TransitionRejected<Movement>>, mut rejected: ResMut<Rejected>| {
+                assert_eq!(trigger.target(), trigger.entity);
+                rejected.0.push((
+                    trigger.entity,
+                    trigger.from,
+                    trigger.attempted,
+                    trigger.reason,
+                ));
+            },
+        );
+        let e = world.spawn(Walking(1)).id();
+        world.entity_mut(e).insert(Walking(2));
+        world.entity_mut(e).insert(Running(1));
+        assert!(world.resource::<Rejected>().0.is_empty());
+        world.entity_mut(e).insert(Flying(1));
+        assert_eq!(
+            world.resource::<Rejected>().0,
+            [(e, running, flying, RejectReason::Table)]
+        );
+        world.insert_resource(TransitionGuard::<Movement>::new(|_, _, _, _| false));
+        let table = TransitionTable::<Movement>::builder(&mut world)
+            .allow::<Running, Walking>()
+            .build();
+        world.insert_resource(table);
+        world.entity_mut(e).insert(Walking(1));
+        assert_eq!(
+            world.resource::<Rejected>().0[1],
+            (e, running, walking, RejectReason::Guard)
+        );
+    }
+
+    #[test]
+    fn rejected_never_stateless() {
+        #[derive(Resource)]
+        struct Checks {
+            walking: ComponentId,
+            runs: u32,
+        }
+
+        // Runs while the rejected state is being removed.
+        fn check_state(
+            trigger: Trigger<OnRemove, Running>,
+            q: Query<&SuperstateInfo<Movement>, With<Walking>>,
+            mut checks: ResMut<Checks>,
+        ) {
+            let info = q.get(trigger.target()).unwrap();
+            assert_eq!(info.current_state(), Some(checks.walking));
+            checks.runs += 1;
+        }
+
+        let mut world = World::new();
+        register_hooks_with_guard::<Movement, (Walking, Running, Flying)>(
+            &mut world,
+            |_, _, _, _| false,
+        )
+        .unwrap();
+        let walking = world.component_id::<Walking>().unwrap();
+        world.insert_resource(Checks { walking, runs: 0 });
+        world.add_observer(check_state);
+        let e = world.spawn(Walking(1)).id();
+        let changed = world
+            .entity(e)
+            .get_change_ticks::<SuperstateInfo<Movement>>()
+            .unwrap()
+            .changed;
+        world.entity_mut(e).insert(Running(1));
+        assert_eq!(world.resource::<Checks>().runs, 1);
+        assert!(world.entity(e).contains::<Walking>());
+        assert!(!world.entity(e).contains::<Running>());
+        let info = world.get::<SuperstateInfo<Movement>>(e).unwrap();
+        assert_eq!(info.current_state(), Some(walking));
+        assert_eq!(info.previous_state(), None);
+        let ticks = world
+            .entity(e)
+            .get_change_ticks::<SuperstateInfo<Movement>>()
+            .unwrap();
+        assert_eq!(ticks.changed, changed);
+    }
+
+    #[test]
+    fn state_lock() {
+        #[derive(Resource, Default)]
+        struct Rejected(Vec<RejectReason>);
+
+        let mut world = World::new();
+        register_hooks::<Movement, (Walking, Running, Flying)>(&mut world).unwrap();
+        world.init_resource::<Rejected>();
+        world.add_observer(
+            |trigger: Trigger<TransitionRejected<Movement>>, mut rejected: ResMut<Rejected>| {
+                rejected.0.push(trigger.reason);
+            },
+        );
+        let e = world.spawn(Walking(1)).id();
+        world.commands().entity(e).lock_state::<Movement>();
+        world.flush();
+        assert!(!superstate::can_transition::<Movement, Running>(
+            &mut world, e
+        ));
+        world.entity_mut(e).insert(Running(1));
+        assert!(world.entity(e).contains::<Walking>());
+        assert!(!world.entity(e).contains::<Running>());
+        assert_eq!(world.resource::<Rejected>().0, [RejectReason::Locked]);
+        world.commands().entity(e).unlock_state::<Movement>();
+        world.flush();
+        world.entity_mut(e).insert(Running(1));
+        assert!(!world.entity(e).contains::<Walking>());
+        assert!(world.entity(e).contains::<Running>());
+
+        let e = world
+            .spawn((StateLock::<Movement>::default(), Flying(1)))
+            .id();
+        assert!(world.entity(e).contains::<Flying>());
+    }
+
+    #[test]
+    fn force_state() {
+        let mut world = World::new();
+        register_hooks::<Movement, (Walking, Running, Flying)>(&mut world).unwrap();
+        let table = TransitionTable::<Movement>::builder(&mut world)
+            .allow::<Walking, Running>()
+            .build();
+        world.insert_resource(table);
+        let e = world.spawn(Walking(1)).id();
+        superstate::force_state::<Movement, Flying>(&mut world, e, Flying(1));
+        assert!(!world.entity(e).contains::<Walking>());
+        assert!(world.entity(e).contains::<Flying>());
+        world.entity_mut(e).insert(Walking(1));
+        assert!(world.entity(e).contains::<Flying>());
+        assert!(!world.entity(e).contains::<Walking>());
+
+        world.commands().entity(e).lock_state::<Movement>();
+        world
+            .commands()
+            .entity(e)
+            .force_state::<Movement, _>(Walking(2));
+        world.flush();
+        assert!(!world.entity(e).contains::<Flying>());
+        assert_eq!(world.get::<Walking>(e).unwrap().0, 2);
+        let running = world.component_id::<Running>();
+        world.entity_mut(e).insert(Running(1));
+        assert!(world.entity(e).contains::<Walking>());
+        let info = world.get::<SuperstateInfo<Movement>>(e).unwrap();
+        assert_ne!(info.current_state(), running);
+    }
+
+    #[test]
+    fn parallel_states() {
+        let mut world = World::new();
+        superstate::register_parallel_hooks::<Movement, (Walking, Running, Flying)>(&mut world)
+            .unwrap();
+        let walking = world.component_id::<Walking>().unwrap();
+        let running = world.component_id::<Running>().unwrap();
+        let e = world.spawn(Walking(1)).id();
+        world.entity_mut(e).insert(Running(1));
+        world.flush();
+        assert!(world.entity(e).contains::<Walking>());
+        let info = world.get::<SuperstateInfo<Movement>>(e).unwrap();
+        assert_eq!(info.active_states(), [walking, running]);
+        assert_eq!(info.current_state(), Some(running));
+        world.entity_mut(e).remove::<Walking>();
+        world.flush();
+        assert!(world.entity(e).contains::<Movement>());
+        let info = world.get::<SuperstateInfo<Movement>>(e).unwrap();
+        assert_eq!(info.active_states(), [running]);
+        world.entity_mut(e).remove::<Running>();
+        world.flush();
+        assert!(!world.entity(e).contains::<Movement>());
+
+        let e = world.spawn((Walking(1), Flying(1))).id();
+        world.flush();
+        assert!(world.entity(e).contains::<Walking>());
+        assert!(world.entity(e).contains::<Flying>());
+        world.entity_mut(e).remove::<Movement>();
+        world.flush();
+        assert!(!world.entity(e).contains::<Walking>());
+        assert!(!world.entity(e).contains::<Flying>());
+    }
+
+    #[test]
+    fn state_enum() {
+        let mut world = World::new();
+        SuperstateBuilder::<Movement, (Walking, Running, Flying)>::new()
+            .state_enum::<MovementState>()
+            .build_world(&mut world)
+            .unwrap();
+        assert!(register_state_enum::<Movement, Unknown>(&mut world).is_err());
+        let e = world.spawn(Walking(1)).id();
+        let registry = world.resource::<SuperstateRegistry<Movement>>();
+        let info = world.get::<SuperstateInfo<Movement>>(e).unwrap();
+        assert_eq!(info.current_as(registry), Some(MovementState::Walking));
+        world.entity_mut(e).insert(Flying(1));
+        let registry = world.resource::<SuperstateRegistry<Movement>>();
+        let info = world.get::<SuperstateInfo<Movement>>(e).unwrap();
+        assert_eq!(info.current_as::<MovementState>(registry), None);
+        let running = world.component_id::<Running>().unwrap();
+        assert_eq!(
+            MovementState::from_component_id(running, &world),
+            Some(MovementState::Running)
+        );
+    }
+
+    #[test]
+    fn try_current_state_in_hook() {
+        #[derive(Resource, Default)]
+        struct Probed(Vec<Result<Option<ComponentId>, SuperstateError>>);
+
+        #[derive(Component)]
+        #[component(on_add = probe)]
+        struct Probe;
+
+        fn probe(mut world: DeferredWorld, ctx: HookContext) {
+            let state = try_current_state::<Movement>(&world, ctx.entity);
+            world.resource_mut::<Probed>().0.push(state);
+        }
+
+        let mut world = World::new();
+        let e = world.spawn_empty().id();
+        assert!(matches!(
+            try_current_state::<Movement>(&world, e),
+            Err(SuperstateError::NotRegistered(_))
+        ));
+        register_hooks::<Movement, (Walking, Running, Flying)>(&mut world).unwrap();
+        world.init_resource::<Probed>();
+        let walking = world.register_component::<Walking>();
+        let a = world.spawn((Walking(1), Probe)).id();
+        world.entity_mut(a).remove::<(Movement, Probe)>();
+        world.entity_mut(a).insert(Probe);
+        world.spawn(Probe);
+        let probed = world.resource::<Probed>();
+        assert!(matches!(probed.0[0], Ok(Some(id)) if id == walking));
+        assert!(matches!(probed.0[1], Ok(None)));
+        assert!(matches!(probed.0[2], Err(SuperstateError::NoInfo(_))));
+        world.despawn(a);
+        assert!(matches!(
+            try_current_state::<Movement>(&world, a),
+            Err(SuperstateError::NoEntity(entity)) if entity == a
+        ));
+    }
+
+    #[test]
+    fn export_dot_graph() {
+        use std::any::type_name;
+
+        let mut world = World::new();
+        assert_eq!(
+            export_dot::<Movement>(&world),
+            "digraph \"Movement\" {\n}\n"
+        );
+        register_hooks::<Movement, (Walking, Running, Flying)>(&mut world).unwrap();
+        let (walking, running, flying) = (
+            type_name::<Walking>(),
+            type_name::<Running>(),
+            type_name::<Flying>(),
+        );
+        assert_eq!(
+            export_dot::<Movement>(&world),
+            format!(
+                "digraph \"Movement\" {{\n    label = \"Transitions are unrestricted\";\n    \
+                 \"{walking}\";\n    \"{running}\";\n    \"{flying}\";\n}}\n"
+            )
+        );
+        let table = TransitionTable::<Movement>::builder(&mut world)
+            .allow::<Running, Walking>()
+            .allow::<Walking, Running>()
+            .allow::<Walking, Flying>()
+            .build();
+        world.insert_resource(table);
+        assert_eq!(
+            export_dot::<Movement>(&world),
+            format!(
+                "digraph \"Movement\" {{\n    \"{walking}\";\n    \"{running}\";\n    \"{flying}\";\n    \
+                 \"{walking}\" -> \"{running}\";\n    \"{walking}\" -> \"{flying}\";\n    \
+                 \"{running}\" -> \"{walking}\";\n}}\n"
+            )
+        );
+    }
+
+    #[test]
+    fn state_enum_query() {
+        let mut world = World::new();
+        SuperstateBuilder::<Movement, (Walking, Running, Flying)>::new()
+            .state_enum::<MovementState>()
+            .build_world(&mut world)
+            .unwrap();
+        let a = world.spawn(Walking(1)).id();
+        let b = world.spawn(Running(1)).id();
+        let c = world.spawn(Flying(1)).id();
+        let d = world.spawn(Walking(1)).id();
+        world.entity_mut(d).remove::<Movement>();
+        let get = world.register_system(move |states: StateEnumQuery<Movement, MovementState>| {
+            [states.get(a), states.get(b), states.get(c), states.get(d)]
+        });
+        assert_eq!(
+            world.run_system(get).unwrap(),
+            [
+                Some(MovementState::Walking),
+                Some(MovementState::Running),
+                None,
+                None
+            ]
+        );
+        let iter = world.register_system(move |states: StateEnumQuery<Movement, MovementState>| {
+            let mut states = states.iter().collect::<Vec<_>>();
+            states.sort_by_key(|(entity, _)| *entity);
+            states
+        });
+        assert_eq!(
+            world.run_system(iter).unwrap(),
+            [
+                (a, Some(MovementState::Walking)),
+                (b, Some(MovementState::Running)),
+                (c, None)
+            ]
+        );
+        let unregistered =
+            world.register_system(move |states: StateEnumQuery<Movement, Unknown>| states.get(a));
+        assert!(world.run_system(unregistered).unwrap().is_none());
+    }
+
+    #[test]
+    fn exit_with_value() {
+        #[derive(Resource, Default)]
+        struct Exits(Vec<(&'static str, u32)>);
+
+        let mut world = World::new();
+        register_hooks::<Movement, (Walking, Running, Flying)>(&mut world).unwrap();
+        world.init_resource::<Exits>();
+        on_exit_with::<Movement, Walking>(&mut world, |walking, _, commands| {
+            let speed = walking.0;
+            commands.queue(move |world: &mut World| {
+                world.resource_mut::<Exits>().0.push(("walking", speed));
+            });
+        });
+        on_exit_with::<Movement, Running>(&mut world, |running, _, commands| {
+            let speed = running.0;
+            commands.queue(move |world: &mut World| {
+                world.resource_mut::<Exits>().0.push(("running", speed));
+            });
+        });
+        let e = world.spawn(Walking(5)).id();
+        world.entity_mut(e).insert(Running(7));
+        world.flush();
+        assert_eq!(world.resource::<Exits>().0, [("walking", 5)]);
+        world.entity_mut(e).insert(StateLock::<Movement>::default());
+        world.entity_mut(e).insert(Walking(1));
+        world.flush();
+        assert_eq!(world.resource::<Exits>().0, [("walking", 5)]);
+        world.entity_mut(e).remove::<Movement>();
+        world.flush();
+        assert_eq!(
+            world.resource::<Exits>().0,
+            [("walking", 5), ("running", 7)]
+        );
+        let e = world.spawn(Walking(2)).id();
+        world.despawn(e);
+        world.flush();
+        assert_eq!(world.resource::<Exits>().0.last(), Some(&("walking", 2)));
+        assert_eq!(world.resource::<Exits>().0.len(), 3);
+    }
+
+    #[test]
+    fn transfer_state() {
+        let mut world = World::new();
+        register_hooks::<Movement, (Walking, Running, Flying)>(&mut world).unwrap();
+        register_transfer::<Movement, Walking, Running>(&mut world);
+        let e = world.spawn(Walking(10)).id();
+        world.entity_mut(e).insert(Running(1));
+        assert_eq!(world.get::<Running>(e).unwrap().0, 11);
+        world.entity_mut(e).insert(Flying(1));
+        assert_eq!(world.get::<Flying>(e).unwrap().0, 1);
+    }
+
+    #[test]
+    fn conflict_policy() {
+        let mut world = World::new();
+        register_hooks_with_policy::<Movement, (Walking, Running, Flying)>(
+            &mut world,
+            ConflictPolicy::KeepFirst,
+        )
+        .unwrap();
+        let e = world.spawn((Running(1), Walking(1), Flying(1))).id();
+        assert!(world.entity(e).contains::<Running>());
+        assert!(!world.entity(e).contains::<Walking>());
+        assert!(!world.entity(e).contains::<Flying>());
+        world.entity_mut(e).insert((Flying(1), Walking(1)));
+        assert!(world.entity(e).contains::<Flying>());
+        assert!(!world.entity(e).contains::<Running>());
+        assert!(!world.entity(e).contains::<Walking>());
+
+        let mut world = World::new();
+        register_hooks_with_policy::<Movement, (Walking, Running, Flying)>(
+            &mut world,
+            ConflictPolicy::KeepHighestPriority,
+        )
+        .unwrap();
+        let priorities = StatePriorities::<Movement>::builder(&mut world)
+            .priority::<Running>(2)
+            .priority::<Flying>(1)
+            .build();
+        world.insert_resource(priorities);
+        let e = world.spawn((Walking(1), Flying(1), Running(1))).id();
+        assert!(world.entity(e).contains::<Running>());
+        assert!(!world.entity(e).contains::<Walking>());
+        assert!(!world.entity(e).contains::<Flying>());
+        world.entity_mut(e).insert(Walking(1));
+        assert!(world.entity(e).contains::<Walking>());
+        assert!(!world.entity(e).contains::<Running>());
+    }
+
+    #[test]
+    fn keep_last_reverse_order() {
+        let mut world = World::new();
+        register_hooks_with_policy::<Movement, (Walking, Running, Flying)>(
+            &mut world,
+            ConflictPolicy::KeepLast,
+        )
+        .unwrap();
+        let walking = world.register_component::<Walking>();
+        let running = world.register_component::<Running>();
+        // Added in reverse `States` order, the last added state remains,
+        // not the last one of `States`.
+        let e = world.spawn((Flying(1), Running(1), Walking(1))).id();
+        assert!(world.entity(e).contains::<Walking>());
+        assert!(!world.entity(e).contains::<Running>());
+        assert!(!world.entity(e).contains::<Flying>());
+        let info = world.get::<SuperstateInfo<Movement>>(e).unwrap();
+        assert_eq!(info.active_states(), [walking]);
+        world.entity_mut(e).insert((Flying(1), Running(1)));
+        assert!(world.entity(e).contains::<Running>());
+        assert!(!world.entity(e).contains::<Flying>());
+        assert!(!world.entity(e).contains::<Walking>());
+        let info = world.get::<SuperstateInfo<Movement>>(e).unwrap();
+        assert_eq!(info.active_states(), [running]);
+    }
+
+    #[test]
+    fn state_priority() {
+        let mut world = World::new();
+        register_hooks_with_policy::<Movement, (Walking, Running, Flying)>(
+            &mut world,
+            ConflictPolicy::KeepHighestPriority,
+        )
+        .unwrap();
+        let priorities = StatePriorities::<Movement>::builder(&mut world)
+            .state::<Walking>()
+            .state::<Flying>()
+            .build();
+        world.insert_resource(priorities);
+        let a = world.spawn((Walking(1), Flying(1))).id();
+        let b = world.spawn((Flying(1), Walking(1))).id();
+        let c = world.spawn((Running(1), Walking(1))).id();
+        assert!(world.entity(a).contains::<Flying>());
+        assert!(!world.entity(a).contains::<Walking>());
+        assert!(world.entity(b).contains::<Flying>());
+        assert!(!world.entity(b).contains::<Walking>());
+        assert!(world.entity(c).contains::<Walking>());
+        assert!(!world.entity(c).contains::<Running>());
+    }
+
+    #[test]
+    #[cfg(feature = "bevy_time")]
+    fn time_in_state() {
+        let mut world = World::new();
+        register_hooks::<Movement, (Walking, Running, Flying)>(&mut world).unwrap();
+        world.init_resource::<Time>();
+        world
+            .resource_mut::<Time>()
+            .advance_by(Duration::from_secs(1));
+        let e = world.spawn(Walking(1)).id();
+        world
+            .resource_mut::<Time>()
+            .advance_by(Duration::from_secs(2));
+        let info = world.get::<SuperstateInfo<Movement>>(e).unwrap();
+        assert_eq!(
+            info.time_in_state(world.resource::<Time>()),
+            Duration::from_secs(2)
+        );
+        world.entity_mut(e).insert(Running(1));
+        let info = world.get::<SuperstateInfo<Movement>>(e).unwrap();
+        assert_eq!(info.time_in_state(world.resource::<Time>()), Duration::ZERO);
+    }
+
+    #[test]
+    #[cfg(feature = "bevy_time")]
+    fn min_dwell() {
+        let mut world = World::new();
+        SuperstateBuilder::<Movement, (Walking, Running, Flying)>::new()
+            .min_dwell::<Walking>(Duration::from_secs(2))
+            .build_world(&mut world)
+            .unwrap();
+        world.init_resource::<Time>();
+        let e = world.spawn(Walking(1)).id();
+        world.entity_mut(e).insert(Walking(2));
+        assert_eq!(world.get::<Walking>(e).unwrap().0, 2);
+        world.entity_mut(e).insert(Running(1));
+        assert!(world.entity(e).contains::<Walking>());
+        assert!(!world.entity(e).contains::<Running>());
+        world
+            .resource_mut::<Time>()
+            .advance_by(Duration::from_secs(2));
+        world.entity_mut(e).insert(Running(1));
+        assert!(!world.entity(e).contains::<Walking>());
+        world.entity_mut(e).insert(Flying(1));
+        assert!(world.entity(e).contains::<Flying>());
+    }
+
+    #[test]
+    fn reuse_after_superstate_removed() {
+        #[derive(Component)]
+        struct Unrelated;
+
+        let mut world = World::new();
+        register_hooks_with_policy::<Movement, (Walking, Running, Flying)>(
+            &mut world,
+            ConflictPolicy::KeepFirst,
+        )
+        .unwrap();
+        let e = world.spawn((Walking(1), Running(1))).id();
+        world.entity_mut(e).insert(Flying(1));
+        world.entity_mut(e).remove::<Movement>();
+        world.entity_mut(e).insert(Unrelated);
+        world.entity_mut(e).insert((Running(1), Walking(1)));
+        assert!(world.entity(e).contains::<Running>());
+        assert!(!world.entity(e).contains::<Walking>());
+        let info = world.get::<SuperstateInfo<Movement>>(e).unwrap();
+        assert_eq!(info.current_state(), world.component_id::<Running>());
+        assert_eq!(info.previous_state(), None);
+        let registry = world.resource::<SuperstateRegistry<Movement>>();
+        assert_eq!(
+            registry.state_ids(),
+            [
+                world.component_id::<Walking>().unwrap(),
+                world.component_id::<Running>().unwrap(),
+                world.component_id::<Flying>().unwrap(),
+            ]
+        );
+    }
+
+    #[test]
+    fn fallback() {
+        let mut world = World::new();
+        assert!(
+            register_hooks_with_fallback::<Movement, (Running, Flying), Walking>(&mut world)
+                .is_err()
+        );
+        let mut world = World::new();
+        register_hooks_with_fallback::<Movement, (Walking, Running, Flying), Walking>(&mut world)
+            .unwrap();
+        let e = world.spawn(Running(1)).id();
+        world.entity_mut(e).remove::<Running>();
+        assert!(world.entity(e).contains::<Walking>());
+        assert!(world.entity(e).contains::<Movement>());
+        world.entity_mut(e).insert(Walking(5));
+        world.entity_mut(e).remove::<Walking>();
+        assert_eq!(world.get::<Walking>(e).unwrap().0, 0);
+        world.entity_mut(e).remove::<Movement>();
+        assert!(!world.entity(e).contains::<Walking>());
+        assert!(!world.entity(e).contains::<Movement>());
+        let e = world.spawn(Flying(1)).id();
+        world.despawn(e);
+        world.flush();
+    }
+
+    #[test]
+    fn remove_info() {
+        let mut world = World::new();
+        SuperstateBuilder::<Movement, (Walking, Running, Flying)>::new()
+            .remove_info()
+            .build_world(&mut world)
+            .unwrap();
+        let e = world.spawn(Walking(1)).id();
+        world.entity_mut(e).insert(Running(1));
+        assert!(world.entity(e).contains::<SuperstateInfo<Movement>>());
+        world.entity_mut(e).remove::<Running>();
+        assert!(!world.entity(e).contains::<Movement>());
+        assert!(!world.entity(e).contains::<SuperstateInfo<Movement>>());
+        world.entity_mut(e).insert(Flying(1));
+        let info = world.get::<SuperstateInfo<Movement>>(e).unwrap();
+        assert_eq!(info.previous_state(), None);
+        world.entity_mut(e).remove::<Movement>();
+        assert!(!world.entity(e).contains::<Flying>());
+        assert!(!world.entity(e).contains::<SuperstateInfo<Movement>>());
+    }
+
+    #[test]
+    fn dynamic_state() {
+        #[derive(Component)]
+        struct Swimming;
+
+        let mut world = World::new();
+        let swimming = world.register_component::<Swimming>();
+        assert!(register_state::<Movement>(&mut world, swimming).is_err());
+        register_hooks::<Movement, (Walking, Running)>(&mut world).unwrap();
+        register_state::<Movement>(&mut world, swimming).unwrap();
+        register_state::<Movement>(&mut world, swimming).unwrap();
+        let registry = world.resource::<SuperstateRegistry<Movement>>();
+        assert_eq!(registry.state_ids().len(), 3);
+        assert_eq!(registry.dynamic_state_ids(), [swimming]);
+        let e = world.spawn(Walking(1)).id();
+        world.entity_mut(e).insert(Swimming);
+        assert!(!world.entity(e).contains::<Walking>());
+        let info = world.get::<SuperstateInfo<Movement>>(e).unwrap();
+        assert_eq!(info.current_state(), Some(swimming));
+        world.entity_mut(e).insert(Running(1));
+        assert!(!world.entity(e).contains::<Swimming>());
+        world.entity_mut(e).insert(Swimming);
+        world.entity_mut(e).remove::<Movement>();
+        assert!(!world.entity(e).contains::<Swimming>());
+        let e = world.spawn((Movement, Swimming)).id();
+        world.entity_mut(e).remove::<Swimming>();
+        assert!(!world.entity(e).contains::<Movement>());
+    }
+
+    #[test]
+    fn deregister_dynamic_state() {
+        #[derive(Component)]
+        struct Swimming;
+
+        let mut world = World::new();
+        let swimming = world.register_component::<Swimming>();
+        register_hooks::<Movement, (Walking, Running)>(&mut world).unwrap();
+        register_state::<Movement>(&mut world, swimming).unwrap();
+        deregister_hooks::<Movement, (Walking, Running)>(&mut world).unwrap();
+        register_hooks::<Movement, (Walking, Running)>(&mut world).unwrap();
+        register_state::<Movement>(&mut world, swimming).unwrap();
+        let e = world.spawn(Walking(1)).id();
+        world.entity_mut(e).insert(Swimming);
+        assert!(!world.entity(e).contains::<Walking>());
+        let info = world.get::<SuperstateInfo<Movement>>(e).unwrap();
+        assert_eq!(info.current_state(), Some(swimming));
+        assert_eq!(info.active_states(), [swimming]);
+    }
+
+    #[test]
+    fn state_without_superstate() {
+        #[derive(Component)]
+        struct Swimming;
+
+        let mut world = World::new();
+        register_hooks::<Movement, (Walking, Running)>(&mut world).unwrap();
+        let swimming = world.register_component::<Swimming>();
+        register_state::<Movement>(&mut world, swimming).unwrap();
+        let e = world.spawn_empty().id();
+        OwningPtr::make(Swimming, |ptr| {
+            // SAFETY: `ptr` points to `Swimming` with `swimming` id.
+            unsafe { world.entity_mut(e).insert_by_id(swimming, ptr) };
+        });
+        assert!(world.entity(e).contains::<Movement>());
+        let info = world.get::<SuperstateInfo<Movement>>(e).unwrap();
+        assert_eq!(info.current_state(), Some(swimming));
+        world.entity_mut(e).insert(Walking(1));
+        assert!(!world.entity(e).contains::<Swimming>());
+
+        let e = world.spawn(Walking(1)).id();
+        world.entity_mut(e).remove::<SuperstateInfo<Movement>>();
+        world.entity_mut(e).insert(Swimming);
+        assert!(!world.entity(e).contains::<Walking>());
+        let info = world.get::<SuperstateInfo<Movement>>(e).unwrap();
+        assert_eq!(info.current_state(), Some(swimming));
+    }
+
+    #[test]
+    fn allowing_empty() {
+        let mut world = World::new();
+        register_hooks_allowing_empty::<Movement, (Walking, Running, Flying)>(&mut world).unwrap();
+        let e = world.spawn(Walking(1)).id();
+        world.entity_mut(e).remove::<Walking>();
+        assert!(world.entity(e).contains::<Movement>());
+        let info = world.get::<SuperstateInfo<Movement>>(e).unwrap();
+        assert_eq!(info.current_state(), None);
+        world.entity_mut(e).insert(Running(1));
+        world.commands().entity(e).clear_state::<Movement>();
+        world.flush();
+        assert!(world.entity(e).contains::<Movement>());
+        assert!(!world.entity(e).contains::<Running>());
+        let e = world.spawn(Movement).id();
+        assert!(world.entity(e).contains::<Movement>());
+
+        let mut world = World::new();
+        register_hooks::<Movement, (Walking, Running, Flying)>(&mut world).unwrap();
+        let e = world.spawn(Walking(1)).id();
+        world.commands().entity(e).clear_state::<Movement>();
+        world.flush();
+        assert!(!world.entity(e).contains::<Movement>());
+        assert!(!world.entity(e).contains::<Walking>());
+    }
+
+    #[test]
+    fn exit_state() {
+        #[derive(Component)]
+        struct Tag;
+
+        let mut world = World::new();
+        register_hooks_allowing_empty::<Movement, (Walking, Running, Flying)>(&mut world).unwrap();
+        let e = world.spawn(Walking(1)).id();
+        world.commands().entity(e).exit_state::<Movement, Running>();
+        world.flush();
+        assert!(world.entity(e).contains::<Walking>());
+        world.commands().entity(e).exit_state::<Movement, Walking>();
+        world.flush();
+        assert!(!world.entity(e).contains::<Walking>());
+        assert!(world.entity(e).contains::<Movement>());
+        // A component on the entity, which is not an active state, is kept.
+        world.entity_mut(e).insert(Tag);
+        world.commands().entity(e).exit_state::<Movement, Tag>();
+        world.flush();
+        assert!(world.entity(e).contains::<Tag>());
+    }
+
+    #[test]
+    fn info_from_other_world() {
+        #[derive(Component, Default, Clone)]
+        #[require(SuperstateInfo<Mode>)]
+        struct Mode;
+
+        #[derive(Component)]
+        #[require(Mode)]
+        struct Idle;
+
+        #[derive(Component)]
+        #[require(Mode)]
+        struct Busy;
+
+        #[derive(Component)]
+        #[require(Mode)]
+        struct Away;
+
+        let mut main = World::new();
+        register_hooks::<Mode, (Idle, Busy, Away)>(&mut main).unwrap();
+        let mut sub = World::new();
+        register_hooks::<Mode, (Away, Busy, Idle)>(&mut sub).unwrap();
+        assert_ne!(
+            resolve_state_id::<Idle>(&main),
+            resolve_state_id::<Idle>(&sub)
+        );
+        let e = main.spawn(Idle).id();
+        main.entity_mut(e).insert(Busy);
+        let info = main.get::<SuperstateInfo<Mode>>(e).unwrap().clone();
+        // Ids of `main` mean other states in `sub`, so they are dropped.
+        let e = sub.spawn(info).id();
+        let info = sub.get::<SuperstateInfo<Mode>>(e).unwrap();
+        assert_eq!(info.active_states(), []);
+        assert_eq!(info.previous_state(), None);
+        sub.entity_mut(e).insert(Away);
+        let info = sub.get::<SuperstateInfo<Mode>>(e).unwrap();
+        assert_eq!(
+            info.active_states(),
+            [resolve_state_id::<Away>(&sub).unwrap()]
+        );
+    }
+
+    #[test]
+    fn entities_in_state_by_id() {
+        let mut world = World::new();
+        let walking = world.register_component::<Walking>();
+        assert!(entities_in_state::<Movement>(&world, walking).is_empty());
+        register_hooks::<Movement, (Walking, Running, Flying)>(&mut world).unwrap();
+        let a = world.spawn(Walking(1)).id();
+        let b = world.spawn(Running(1)).id();
+        let c = world.spawn(Walking(1)).id();
+        let mut entities = entities_in_state::<Movement>(&world, walking);
+        entities.sort();
+        assert_eq!(entities, [a, c]);
+        world.entity_mut(c).insert(Flying(1));
+        assert_eq!(entities_in_state::<Movement>(&world, walking), [a]);
+        let running = world.component_id::<Running>().unwrap();
+        assert_eq!(entities_in_state::<Movement>(&world, running), [b]);
+    }
+
+    #[test]
+    fn all_states() {
+        let mut world = World::new();
+        let all =
+            world.register_system(|states: SuperstateQuery<Movement>| states.all_states().to_vec());
+        assert!(world.run_system(all).unwrap().is_empty());
+        assert_eq!(super_component_id::<Movement>(&world), None);
+        register_hooks::<Movement, (Walking, Running)>(&mut world).unwrap();
+        assert_eq!(
+            super_component_id::<Movement>(&world),
+            world.component_id::<Movement>()
+        );
+        let walking = world.component_id::<Walking>().unwrap();
+        let running = world.component_id::<Running>().unwrap();
+        world.register_component::<Flying>();
+        assert_eq!(world.run_system(all).unwrap(), [walking, running]);
+        let names = world.register_system(|states: SuperstateQuery<Movement>| {
+            states
+                .all_state_names()
+                .map(|(_, name)| name.rsplit("::").next().unwrap().to_owned())
+                .collect::<Vec<_>>()
+        });
+        assert_eq!(world.run_system(names).unwrap(), ["Walking", "Running"]);
+    }
+
+    #[test]
+    fn superstate_query_is() {
+        let mut world = World::new();
+        // Initialized before the registration, so ids are looked up in components.
+        let early = world.register_system(|states: SuperstateQuery<Movement>, q: Query<Entity>| {
+            q.iter().filter(|e| states.is::<Walking>(*e)).count()
+        });
+        world.run_system(early).unwrap();
+        register_hooks::<Movement, (Walking, Running)>(&mut world).unwrap();
+        let e = world.spawn(Walking(1)).id();
+        world.spawn(Running(1));
+        let is = world.register_system(|states: SuperstateQuery<Movement>, q: Query<Entity>| {
+            q.iter()
+                .map(|e| (e, states.is::<Walking>(e), states.is::<Flying>(e)))
+                .filter(|(_, walking, flying)| *walking || *flying)
+                .collect::<Vec<_>>()
+        });
+        assert_eq!(world.run_system(is).unwrap(), [(e, true, false)]);
+        assert_eq!(world.run_system(early).unwrap(), 1);
+    }
+
+    #[test]
+    fn superstate_without_default() {
+        #[derive(Component)]
+        #[require(SuperstateInfo<Stance>)]
+        struct Stance(u32);
+
+        #[derive(Component, Default)]
+        #[require(Stance = Stance(1))]
+        struct Standing;
+
+        #[derive(Component)]
+        #[require(Stance = Stance(2))]
+        struct Crouching;
+
+        let mut world = World::new();
+        SuperstateBuilder::<Stance, (Standing, Crouching)>::new()
+            .default_state::<Standing>()
+            .fallback_state::<Standing>()
+            .build_world(&mut world)
+            .unwrap();
+        let e = world.spawn(Crouching).id();
+        world.entity_mut(e).remove::<Crouching>();
+        world.flush();
+        assert!(world.entity(e).contains::<Standing>());
+        assert_eq!(world.get::<Stance>(e).unwrap().0, 2);
+        let e = world.spawn(Stance(3)).id();
+        world.flush();
+        assert!(world.entity(e).contains::<Standing>());
+    }
+
+    #[test]
+    fn any_of_states() {
+        let mut world = World::new();
+        register_hooks::<Movement, (Walking, Running, Flying)>(&mut world).unwrap();
+        let a = world.spawn(Walking(1)).id();
+        world.spawn(Flying(1));
+        let c = world.spawn(Running(1)).id();
+        world.spawn(Walking(1)).remove::<Movement>();
+        let any = world.register_system(
+            |q: Query<Entity, AnyOfStates<Movement, (Walking, Running)>>| {
+                let mut entities = q.iter().collect::<Vec<_>>();
+                entities.sort();
+                entities
+            },
+        );
+        assert_eq!(world.run_system(any).unwrap(), [a, c]);
+    }
+
+    #[test]
+    fn info_snapshot() {
+        let mut world = World::new();
+        register_hooks::<Movement, (Walking, Running, Flying)>(&mut world).unwrap();
+        let walking = world.component_id::<Walking>();
+        let running = world.component_id::<Running>();
+        let e = world.spawn(Walking(1)).id();
+        let before = world.get::<SuperstateInfo<Movement>>(e).unwrap().snapshot();
+        assert_eq!(
+            before,
+            StateSnapshot {
+                active: walking,
+                previous: None
+            }
+        );
+        world.entity_mut(e).insert(Running(1));
+        let after = world.get::<SuperstateInfo<Movement>>(e).unwrap().snapshot();
+        assert_ne!(before, after);
+        assert_eq!(after.active, running);
+        assert_eq!(after.previous, walking);
+    }
+
+    #[test]
+    fn iter_states() {
+        let mut world = World::new();
+        register_hooks_allowing_empty::<Movement, (Walking, Running, Flying)>(&mut world).unwrap();
+        let walking = world.component_id::<Walking>().unwrap();
+        let running = world.component_id::<Running>().unwrap();
+        let a = world.spawn(Walking(1)).id();
+        let b = world.spawn(Running(1)).id();
+        let c = world.spawn(Walking(1)).id();
+        let d = world.spawn(Movement).id();
+        world.entity_mut(c).remove::<Movement>();
+        let iter = world.register_system(|states: SuperstateQuery<Movement>| {
+            let mut states = states.iter().collect::<Vec<_>>();
+            states.sort();
+            states
+        });
+        let mut expected = vec![(a, Some(walking)), (b, Some(running)), (d, None)];
+        expected.sort();
+        assert_eq!(world.run_system(iter).unwrap(), expected);
+    }
+
+    #[test]
+    fn state_changed() {
+        let mut world = World::new();
+        register_hooks::<Movement, (Walking, Running, Flying)>(&mut world).unwrap();
+        let walking = world.component_id::<Walking>().unwrap();
+        let running = world.component_id::<Running>().unwrap();
+        let changed = world.register_system(|mut changed: StateChanged<Movement>| {
+            changed.iter().collect::<Vec<_>>()
+        });
+        let a = world.spawn(Walking(1)).id();
+        let b = world.spawn(Walking(1)).id();
+        assert_eq!(world.run_system(changed).unwrap().len(), 2);
+        assert!(world.run_system(changed).unwrap().is_empty());
+        world.entity_mut(a).insert(Running(1));
+        world.entity_mut(b).insert(Running(1));
+        world.entity_mut(b).insert(Walking(2));
+        assert_eq!(
+            world.run_system(changed).unwrap(),
+            [(a, Some(walking), Some(running))]
+        );
+        world.entity_mut(a).remove::<Movement>();
+        assert_eq!(
+            world.run_system(changed).unwrap(),
+            [(a, Some(running), None)]
+        );
+        // The state stored before the info was removed is forgotten.
+        world
+            .entity_mut(b)
+            .remove::<(Movement, SuperstateInfo<Movement>)>();
+        world.entity_mut(b).insert(Running(1));
+        assert_eq!(
+            world.run_system(changed).unwrap(),
+            [(b, None, Some(running))]
+        );
+    }
+
+    #[test]
+    fn state_just_changed() {
+        let mut world = World::new();
+        register_hooks::<Movement, (Walking, Running, Flying)>(&mut world).unwrap();
+        let changed = world.register_system(|query: Query<Entity, StateJustChanged<Movement>>| {
+            query.iter().collect::<Vec<_>>()
+        });
+        let a = world.spawn(Walking(1)).id();
+        assert_eq!(world.run_system(changed).unwrap(), [a]);
+        assert!(world.run_system(changed).unwrap().is_empty());
+        world.entity_mut(a).insert(Walking(2));
+        assert!(world.run_system(changed).unwrap().is_empty());
+        world.entity_mut(a).insert(StateLock::<Movement>::default());
+        world.entity_mut(a).insert(Running(1));
+        world.flush();
+        assert!(world.run_system(changed).unwrap().is_empty());
+        world.entity_mut(a).remove::<StateLock<Movement>>();
+        world.entity_mut(a).insert(Running(1));
+        world.flush();
+        assert_eq!(world.run_system(changed).unwrap(), [a]);
+        world.entity_mut(a).remove::<Movement>();
+        assert_eq!(world.run_system(changed).unwrap(), [a]);
+    }
+
+    #[test]
+    fn superstate_macro() {
+        superstate::superstate! {
+            #[derive(Debug)]
+            enum Mood {
+                #[derive(Default)]
+                Calm,
+                Angry(u32),
+                Sad { tears: u32 },
+            }
+        }
+
+        let mut world = World::new();
+        Mood::register(&mut world).unwrap();
+        Mood::register(&mut world).unwrap();
+        let e = world.spawn(Angry(1)).id();
+        assert!(world.entity(e).contains::<Mood>());
+        world.entity_mut(e).insert(Sad { tears: 2 });
+        assert!(!world.entity(e).contains::<Angry>());
+        assert_eq!(world.get::<Sad>(e).unwrap().tears, 2);
+        world.entity_mut(e).insert(Calm);
+        assert!(!world.entity(e).contains::<Sad>());
+        world.entity_mut(e).remove::<Calm>();
+        assert!(!world.entity(e).contains::<Mood>());
+        assert_eq!(format!("{:?}", Mood), "Mood");
+        assert_eq!(
+            world
+                .get::<SuperstateInfo<Mood>>(e)
+                .unwrap()
+                .current_state(),
+            None
+        );
+    }
+
     fn no_states_and_superstate_system(
         q: Query<Entity, Or<(With<Movement>, With<Running>, With<Flying>, With<Walking>)>>,
     ) {
@@ -0,0 +1,162 @@
+use std::sync::Mutex;
+
+use bevy_ecs::{component::Component, world::World};
+use log::{Level, LevelFilter, Log, Metadata, Record};
+use superstate::{SuperstateInfo, register_hooks, register_hooks_with_logging};
+
+/// Collects all log messages, filtered by superstate in assertions.
+struct TestLogger(Mutex<Vec<(Level, String)>>);
+
+impl Log for TestLogger {
+    fn enabled(&self, _: &Metadata) -> bool {
+        true
+    }
+
+    fn log(&self, record: &Record) {
+        self.0
+            .lock()
+            .unwrap()
+            .push((record.level(), record.args().to_string()));
+    }
+
+    fn flush(&self) {}
+}
+
+static LOGGER: TestLogger = TestLogger(Mutex::new(Vec::new()));
+
+fn messages(contains: &str) -> Vec<(Level, String)> {
+    let short = |name: &str| {
+        name.split(' ')
+            .map(|part| part.rsplit("::").next().unwrap())
+            .collect::<Vec<_>>()
+            .join(" ")
+    };
+    LOGGER
+        .0
+        .lock()
+        .unwrap()
+        .iter()
+        .filter(|(_, message)| message.contains(contains))
+        .map(|(level, message)| (*level, short(message)))
+        .collect()
+}
+
+#[derive(Default, Component)]
+#[require(SuperstateInfo<Movement>)]
+struct Movement;
+
+#[derive(Component)]
+#[require(Movement)]
+struct Walking;
+
+#[derive(Component)]
+#[require(Movement)]
+struct Running;
+
+#[derive(Default, Component)]
+#[require(SuperstateInfo<Health>)]
+struct Health;
+
+#[derive(Component)]
+#[require(Health)]
+struct Alive;
+
+#[derive(Component)]
+#[require(Health)]
+struct Dead;
+
+fn init_logger() {
+    // Tests share the logger, only the first one sets it.
+    let _ = log::set_logger(&LOGGER);
+    log::set_max_level(LevelFilter::Trace);
+}
+
+#[test]
+fn log_transitions() {
+    init_logger();
+    let mut world = World::new();
+    register_hooks_with_logging::<Movement, (Walking, Running)>(&mut world, Level::Debug).unwrap();
+    register_hooks::<Health, (Alive, Dead)>(&mut world).unwrap();
+    let e = world.spawn((Walking, Alive)).id();
+    world.entity_mut(e).insert((Running, Dead));
+    world.entity_mut(e).insert(Running);
+    assert_eq!(
+        messages("Movement:"),
+        [
+            (Level::Debug, "Movement: None -> Walking".to_owned()),
+            (Level::Debug, "Movement: Walking -> Running".to_owned()),
+        ]
+    );
+    assert!(messages("Health:").is_empty());
+}
+
+#[test]
+#[cfg(debug_assertions)]
+fn warn_unregistered() {
+    #[derive(Default, Component)]
+    #[require(SuperstateInfo<Stance>)]
+    struct Stance;
+
+    #[derive(Component)]
+    #[require(Stance)]
+    struct Crouching;
+
+    #[derive(Default, Component)]
+    #[require(SuperstateInfo<Mood>)]
+    struct Mood;
+
+    #[derive(Component)]
+    #[require(Mood)]
+    struct Calm;
+
+    init_logger();
+    let mut world = World::new();
+    world.spawn(Crouching);
+    world.spawn(Crouching);
+    world.spawn(Calm);
+    world.spawn(Calm);
+    assert_eq!(
+        messages("Mood is not registered"),
+        [(
+            Level::Warn,
+            "Mood> is added, but Mood is not registered, \
+             superstate hooks never run for it. Add its plugin or call register_hooks."
+                .to_owned()
+        )]
+    );
+    assert_eq!(
+        messages("Stance is not registered"),
+        [(
+            Level::Warn,
+            "Stance> is added, but Stance is not registered, \
+             superstate hooks never run for it. Add its plugin or call register_hooks."
+                .to_owned()
+        )]
+    );
+}
+
+#[test]
+fn warn_missing_info_per_superstate() {
+    init_logger();
+    let mut world = World::new();
+    register_hooks::<Movement, (Walking, Running)>(&mut world).unwrap();
+    register_hooks::<Health, (Alive, Dead)>(&mut world).unwrap();
+    let e = world.spawn((Walking, Alive)).id();
+    world
+        .entity_mut(e)
+        .remove::<(SuperstateInfo<Movement>, SuperstateInfo<Health>)>();
+    world.entity_mut(e).remove::<(Walking, Alive)>();
+    assert_eq!(
+        messages("superstate hooks are skipped"),
+        [
+            (
+                Level::Warn,
+                format!("{e} has no Movement>, superstate hooks are skipped.")
+            ),
+            (
+                Level::Warn,
+                format!("{e} has no Health>, superstate hooks are skipped.")
+            ),
+        ]
+    );
+}
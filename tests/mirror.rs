@@ -0,0 +1,59 @@
+#![cfg(all(feature = "bevy_state", feature = "bevy_app"))]
+
+use bevy_app::App;
+use bevy_ecs::component::Component;
+use bevy_state::{
+    app::{AppExtStates, StatesPlugin},
+    state::{State, States},
+};
+use superstate::{StateMirror, SuperstateAppExt, SuperstateInfo, superstate_plugin};
+
+#[derive(Default, Component)]
+#[require(SuperstateInfo<Movement>)]
+struct Movement;
+
+#[derive(Component)]
+#[require(Movement)]
+struct Walking;
+
+#[derive(Component)]
+#[require(Movement)]
+struct Running;
+
+#[derive(Component)]
+#[require(Movement)]
+struct Flying;
+
+#[derive(States, Default, Debug, Clone, PartialEq, Eq, Hash)]
+enum PlayerState {
+    #[default]
+    Idle,
+    Walk,
+    Run,
+}
+
+#[test]
+fn mirror() {
+    let mut app = App::new();
+    app.add_plugins((
+        StatesPlugin,
+        superstate_plugin::<Movement, (Walking, Running, Flying)>,
+    ))
+    .init_state::<PlayerState>();
+    let player = app.world_mut().spawn(Walking).id();
+    let mirror = StateMirror::<Movement, PlayerState>::builder(app.world_mut())
+        .map::<Walking>(PlayerState::Walk)
+        .map::<Running>(PlayerState::Run)
+        .entity(player)
+        .build();
+    app.mirror_state(mirror);
+    app.update();
+    let state = |app: &App| app.world().resource::<State<PlayerState>>().get().clone();
+    assert_eq!(state(&app), PlayerState::Walk);
+    app.world_mut().entity_mut(player).insert(Running);
+    app.update();
+    assert_eq!(state(&app), PlayerState::Run);
+    app.world_mut().entity_mut(player).insert(Flying);
+    app.update();
+    assert_eq!(state(&app), PlayerState::Run);
+}
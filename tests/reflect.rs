@@ -0,0 +1,42 @@
+#![cfg(all(feature = "reflect", feature = "bevy_app"))]
+
+use bevy_app::App;
+use bevy_ecs::{
+    component::{Component, ComponentId},
+    reflect::AppTypeRegistry,
+};
+use bevy_reflect::{PartialReflect, ReflectRef};
+use smallvec::SmallVec;
+use superstate::{SuperstateInfo, superstate_plugin};
+
+#[derive(Default, Component)]
+#[require(SuperstateInfo<Movement>)]
+struct Movement;
+
+#[derive(Component)]
+#[require(Movement)]
+struct Walking;
+
+#[test]
+fn registered() {
+    let mut app = App::new();
+    app.add_plugins(superstate_plugin::<Movement, (Walking,)>);
+    let registry = app.world().resource::<AppTypeRegistry>().clone();
+    assert!(
+        registry
+            .read()
+            .get(std::any::TypeId::of::<SuperstateInfo<Movement>>())
+            .is_some()
+    );
+    let e = app.world_mut().spawn(Walking).id();
+    let walking = app.world().component_id::<Walking>().unwrap();
+    let info = app.world().get::<SuperstateInfo<Movement>>(e).unwrap();
+    let ReflectRef::Struct(info) = info.reflect_ref() else {
+        panic!("SuperstateInfo is not a struct");
+    };
+    let states = info
+        .field("states_on_entity")
+        .and_then(|field| field.try_downcast_ref::<SmallVec<[ComponentId; 1]>>())
+        .unwrap();
+    assert_eq!(states.as_slice(), &[walking]);
+}
@@ -0,0 +1,63 @@
+use bevy_ecs::{component::ComponentId, prelude::Component};
+use superstate::{ConflictPolicy, StatePriorities, resolve_transition};
+
+#[derive(Component)]
+struct Movement;
+
+const WALKING: ComponentId = ComponentId::new(1);
+const RUNNING: ComponentId = ComponentId::new(2);
+const FLYING: ComponentId = ComponentId::new(3);
+
+fn resolve(batch: &[ComponentId], policy: ConflictPolicy) -> Option<ComponentId> {
+    resolve_transition::<Movement>(batch, policy, None)
+}
+
+#[test]
+fn keep_last() {
+    let policy = ConflictPolicy::KeepLast;
+    assert_eq!(resolve(&[WALKING, RUNNING, FLYING], policy), Some(FLYING));
+    // The order of adding matters, not the order of `States`.
+    assert_eq!(resolve(&[FLYING, RUNNING, WALKING], policy), Some(WALKING));
+}
+
+#[test]
+fn keep_first() {
+    let policy = ConflictPolicy::KeepFirst;
+    assert_eq!(resolve(&[WALKING, RUNNING, FLYING], policy), Some(WALKING));
+    assert_eq!(resolve(&[FLYING, RUNNING, WALKING], policy), Some(FLYING));
+}
+
+#[test]
+fn keep_highest_priority() {
+    let policy = ConflictPolicy::KeepHighestPriority;
+    let mut priorities = StatePriorities::<Movement>::default();
+    priorities.set_by_id(RUNNING, 2).set_by_id(FLYING, 1);
+    assert_eq!(
+        resolve_transition(&[WALKING, FLYING, RUNNING], policy, Some(&priorities)),
+        Some(RUNNING)
+    );
+    // Equal priorities keep the state added first.
+    priorities.set_by_id(WALKING, 2);
+    assert_eq!(
+        resolve_transition(&[FLYING, RUNNING, WALKING], policy, Some(&priorities)),
+        Some(RUNNING)
+    );
+    assert_eq!(
+        resolve_transition(&[WALKING, RUNNING], policy, Some(&priorities)),
+        Some(WALKING)
+    );
+    // Without priorities all states are equal.
+    assert_eq!(resolve(&[FLYING, RUNNING], policy), Some(FLYING));
+}
+
+#[test]
+fn single_state() {
+    for policy in [
+        ConflictPolicy::KeepLast,
+        ConflictPolicy::KeepFirst,
+        ConflictPolicy::KeepHighestPriority,
+    ] {
+        assert_eq!(resolve(&[WALKING], policy), Some(WALKING));
+        assert_eq!(resolve(&[], policy), None);
+    }
+}
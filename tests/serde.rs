@@ -0,0 +1,69 @@
+#![cfg(feature = "serde")]
+
+use bevy_ecs::{component::Component, world::World};
+use superstate::{SuperstateInfo, register_hooks, state_alias};
+
+#[derive(Default, Component)]
+#[require(SuperstateInfo<Movement>)]
+struct Movement;
+
+#[derive(Component)]
+#[require(Movement)]
+struct Walking;
+
+#[derive(Component)]
+#[require(Movement)]
+struct Running;
+
+#[test]
+fn roundtrip() {
+    let mut world = World::new();
+    register_hooks::<Movement, (Walking, Running)>(&mut world).unwrap();
+    let e = world.spawn(Walking).id();
+    let info = world.get::<SuperstateInfo<Movement>>(e).unwrap();
+    let json = serde_json::to_string(info).unwrap();
+
+    // Ids in the new world differ from the old one.
+    let mut world = World::new();
+    world.register_component::<Running>();
+    register_hooks::<Movement, (Walking, Running)>(&mut world).unwrap();
+    let info: SuperstateInfo<Movement> = serde_json::from_str(&json).unwrap();
+    let e = world.spawn(Walking).id();
+    world.entity_mut(e).insert(info);
+    let walking = world.component_id::<Walking>();
+    let info = world.get::<SuperstateInfo<Movement>>(e).unwrap();
+    assert_eq!(info.current_state(), None);
+
+    world.entity_mut(e).insert(Running);
+    let info = world.get::<SuperstateInfo<Movement>>(e).unwrap();
+    assert_eq!(info.previous_state(), walking);
+    assert!(!world.entity(e).contains::<Walking>());
+    assert!(world.entity(e).contains::<Running>());
+}
+
+#[test]
+fn alias() {
+    #[derive(Component)]
+    #[require(Movement)]
+    struct Walk;
+
+    let mut world = World::new();
+    register_hooks::<Movement, (Walking, Running)>(&mut world).unwrap();
+    state_alias::<Walking>(&mut world, "walk");
+    let e = world.spawn(Walking).id();
+    let info = world.get::<SuperstateInfo<Movement>>(e).unwrap();
+    let json = serde_json::to_string(info).unwrap();
+    assert_eq!(json, r#"{"state":"walk"}"#);
+
+    // `Walking` is renamed to `Walk`.
+    let mut world = World::new();
+    register_hooks::<Movement, (Walk, Running)>(&mut world).unwrap();
+    state_alias::<Walk>(&mut world, "walk");
+    let info: SuperstateInfo<Movement> = serde_json::from_str(&json).unwrap();
+    let e = world.spawn(Walk).id();
+    world.entity_mut(e).insert(info);
+    world.entity_mut(e).insert(Running);
+    let info = world.get::<SuperstateInfo<Movement>>(e).unwrap();
+    assert_eq!(info.previous_state(), world.component_id::<Walk>());
+    assert!(!world.entity(e).contains::<Walk>());
+}
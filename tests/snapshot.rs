@@ -0,0 +1,99 @@
+#![cfg(feature = "bincode")]
+
+use bevy_ecs::{component::Component, world::World};
+use superstate::{
+    SuperstateInfo, register_default_state, register_hooks, restore_entity_states,
+    snapshot_entity_states, state_alias,
+};
+
+#[derive(Default, Component)]
+#[require(SuperstateInfo<Movement>)]
+struct Movement;
+
+#[derive(Component, Default)]
+#[require(Movement)]
+struct Walking;
+
+#[derive(Component, Default)]
+#[require(Movement)]
+struct Running;
+
+#[derive(Default, Component)]
+#[require(SuperstateInfo<Health>)]
+struct Health;
+
+#[derive(Component, Default)]
+#[require(Health)]
+struct Alive;
+
+#[derive(Component)]
+#[require(Health)]
+struct Dead;
+
+fn register(world: &mut World) {
+    register_hooks::<Movement, (Walking, Running)>(world).unwrap();
+    register_hooks::<Health, (Alive, Dead)>(world).unwrap();
+    register_default_state::<Movement, Walking>(world);
+    register_default_state::<Movement, Running>(world);
+    register_default_state::<Health, Alive>(world);
+}
+
+#[test]
+fn roundtrip() {
+    let mut world = World::new();
+    register(&mut world);
+    let e = world.spawn((Running, Alive)).id();
+    let bytes = snapshot_entity_states(&world, e);
+
+    // Ids in the new world differ from the old one.
+    let mut world = World::new();
+    world.register_component::<Dead>();
+    register(&mut world);
+    let e = world.spawn((Walking, Dead)).id();
+    restore_entity_states(&mut world, e, &bytes).unwrap();
+    assert!(world.entity(e).contains::<Running>());
+    assert!(!world.entity(e).contains::<Walking>());
+    assert!(world.entity(e).contains::<Alive>());
+    assert!(!world.entity(e).contains::<Dead>());
+    let info = world.get::<SuperstateInfo<Movement>>(e).unwrap();
+    assert_eq!(info.previous_state(), world.component_id::<Walking>());
+
+    let bytes = snapshot_entity_states(&world, e);
+    restore_entity_states(&mut world, e, &bytes).unwrap();
+    assert!(world.entity(e).contains::<Running>());
+    assert!(restore_entity_states(&mut world, e, &[255]).is_err());
+}
+
+#[test]
+fn no_constructor() {
+    let mut world = World::new();
+    register(&mut world);
+    let e = world.spawn(Dead).id();
+    let bytes = snapshot_entity_states(&world, e);
+    let e = world.spawn(Alive).id();
+    restore_entity_states(&mut world, e, &bytes).unwrap();
+    assert!(world.entity(e).contains::<Alive>());
+}
+
+#[test]
+fn alias() {
+    #[derive(Component, Default)]
+    #[require(Movement)]
+    struct Run;
+
+    let mut world = World::new();
+    register(&mut world);
+    state_alias::<Running>(&mut world, "run");
+    let e = world.spawn(Running).id();
+    let bytes = snapshot_entity_states(&world, e);
+
+    // `Running` is renamed to `Run`.
+    let mut world = World::new();
+    register_hooks::<Movement, (Walking, Run)>(&mut world).unwrap();
+    register_default_state::<Movement, Run>(&mut world);
+    state_alias::<Run>(&mut world, "run");
+    let e = world.spawn(Walking).id();
+    restore_entity_states(&mut world, e, &bytes).unwrap();
+    assert!(world.entity(e).contains::<Run>());
+    assert!(!world.entity(e).contains::<Walking>());
+}
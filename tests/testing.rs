@@ -0,0 +1,47 @@
+#![cfg(feature = "testing")]
+
+use bevy_ecs::component::Component;
+use superstate::{SuperstateCommands, SuperstateInfo, testing::SuperstateTestApp};
+
+#[derive(Default, Component)]
+#[require(SuperstateInfo<Movement>)]
+struct Movement;
+
+#[derive(Component)]
+#[require(Movement)]
+struct Walking;
+
+#[derive(Component)]
+#[require(Movement)]
+struct Running;
+
+#[test]
+fn drive_transitions() {
+    let mut app = SuperstateTestApp::new();
+    app.register::<Movement, (Walking, Running)>();
+    let e = app.spawn_in((Walking, Running));
+    app.assert_in::<Running>(e);
+    app.commands().entity(e).transition_to(Walking);
+    app.assert_in::<Walking>(e);
+    app.commands().entity(e).clear_state::<Movement>();
+    app.assert_stateless(e);
+    assert!(!app.world().entity(e).contains::<Movement>());
+}
+
+#[test]
+#[should_panic(expected = "is not in")]
+fn assert_in_fails() {
+    let mut app = SuperstateTestApp::new();
+    app.register::<Movement, (Walking, Running)>();
+    let e = app.spawn_in(Walking);
+    app.assert_in::<Running>(e);
+}
+
+#[test]
+#[should_panic(expected = "is in states")]
+fn assert_stateless_fails() {
+    let mut app = SuperstateTestApp::new();
+    app.register::<Movement, (Walking, Running)>();
+    let e = app.spawn_in(Walking);
+    app.assert_stateless(e);
+}
@@ -0,0 +1,52 @@
+#![cfg(all(feature = "validate", feature = "bevy_app", debug_assertions))]
+
+use bevy_app::App;
+use bevy_ecs::component::Component;
+use superstate::{SuperstateBuilder, SuperstateInfo, superstate_plugin};
+
+#[derive(Default, Component)]
+#[require(SuperstateInfo<Movement>)]
+struct Movement;
+
+#[derive(Component)]
+#[require(Movement)]
+struct Walking;
+
+#[derive(Component)]
+#[require(Movement)]
+struct Running;
+
+#[test]
+fn valid() {
+    let mut app = App::new();
+    app.add_plugins(superstate_plugin::<Movement, (Walking, Running)>);
+    let e = app.world_mut().spawn((Walking, Running)).id();
+    app.update();
+    app.world_mut().entity_mut(e).insert(Walking);
+    app.world_mut().entity_mut(e).remove::<Movement>();
+    app.update();
+}
+
+#[test]
+#[should_panic(expected = "is in state")]
+fn info_mismatch() {
+    let mut app = App::new();
+    app.add_plugins(superstate_plugin::<Movement, (Walking, Running)>);
+    let e = app.world_mut().spawn(Walking).id();
+    // Replacing the info bypasses the hooks.
+    app.world_mut()
+        .entity_mut(e)
+        .insert(SuperstateInfo::<Movement>::default());
+    app.update();
+}
+
+#[test]
+fn parallel_valid() {
+    let mut app = App::new();
+    SuperstateBuilder::<Movement, (Walking, Running)>::new()
+        .parallel()
+        .build(&mut app)
+        .unwrap();
+    app.world_mut().spawn((Walking, Running));
+    app.update();
+}
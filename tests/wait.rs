@@ -0,0 +1,67 @@
+#![cfg(feature = "async")]
+
+use std::{
+    pin::pin,
+    task::{Context, Poll, Waker},
+};
+
+use bevy_ecs::{component::Component, world::World};
+use superstate::{SuperstateInfo, WaitCancelled, register_hooks, wait_for_state};
+
+#[derive(Default, Component)]
+#[require(SuperstateInfo<Movement>)]
+struct Movement;
+
+#[derive(Component)]
+#[require(Movement)]
+struct Walking;
+
+#[derive(Component)]
+#[require(Movement)]
+struct Running;
+
+#[derive(Component)]
+struct Other;
+
+#[test]
+fn wait_for_entered_state() {
+    let mut world = World::new();
+    register_hooks::<Movement, (Walking, Running)>(&mut world).unwrap();
+    let mut cx = Context::from_waker(Waker::noop());
+    let e = world.spawn(Walking).id();
+    let mut walking = pin!(wait_for_state::<Movement, Walking>(&mut world, e).unwrap());
+    assert_eq!(walking.as_mut().poll(&mut cx), Poll::Ready(Ok(())));
+    let mut running = pin!(wait_for_state::<Movement, Running>(&mut world, e).unwrap());
+    assert!(running.as_mut().poll(&mut cx).is_pending());
+    world.entity_mut(e).insert(Walking);
+    assert!(running.as_mut().poll(&mut cx).is_pending());
+    world.entity_mut(e).insert(Running);
+    assert_eq!(running.as_mut().poll(&mut cx), Poll::Ready(Ok(())));
+    assert!(wait_for_state::<Movement, Other>(&mut world, e).is_err());
+}
+
+#[test]
+fn wait_cancelled() {
+    let mut world = World::new();
+    register_hooks::<Movement, (Walking, Running)>(&mut world).unwrap();
+    let mut cx = Context::from_waker(Waker::noop());
+    let a = world.spawn(Walking).id();
+    let b = world.spawn(Walking).id();
+    let mut despawned = pin!(wait_for_state::<Movement, Running>(&mut world, a).unwrap());
+    let mut removed = pin!(wait_for_state::<Movement, Running>(&mut world, b).unwrap());
+    world.despawn(a);
+    world.entity_mut(b).remove::<Movement>();
+    assert_eq!(
+        despawned.as_mut().poll(&mut cx),
+        Poll::Ready(Err(WaitCancelled(a)))
+    );
+    assert_eq!(
+        removed.as_mut().poll(&mut cx),
+        Poll::Ready(Err(WaitCancelled(b)))
+    );
+    let mut gone = pin!(wait_for_state::<Movement, Running>(&mut world, a).unwrap());
+    assert_eq!(
+        gone.as_mut().poll(&mut cx),
+        Poll::Ready(Err(WaitCancelled(a)))
+    );
+}